@@ -0,0 +1,269 @@
+use crate::extra::{GlyphExtra, GlyphSection};
+use crate::{Extra, SectionGlyph};
+
+use glyph_brush::GlyphVertex;
+use glyph_brush::ab_glyph::{Rect, point};
+
+/// The function called on every glyph of an animated text, set with
+/// [`set_animation`](struct.GlyphBrush.html#method.set_animation).
+pub(crate) type Animation =
+    Box<dyn FnMut(&mut AnimatedGlyph<'_>) + Send + Sync>;
+
+/// A glyph of an animated [`Text`](struct.Text.html), about to be drawn.
+///
+/// The glyph is rasterized as laid out, so moving or hiding it does not
+/// touch the glyph cache.
+#[derive(Debug)]
+pub struct AnimatedGlyph<'a> {
+    /// The quad of the glyph. Move its `pixel_coords` to move the glyph; its
+    /// `tex_coords` keep sampling the cached glyph. Its `extra` holds the
+    /// color and depth of the text.
+    pub vertex: GlyphVertex<'a, Extra>,
+    /// The index of the section among the sections queued in the frame.
+    pub section_index: usize,
+    /// The index of the `Text` of the glyph in its section.
+    pub text_index: usize,
+    /// The byte offset of the glyph in its `Text`.
+    pub byte_offset: usize,
+    /// The index of the first character of the glyph, counting the
+    /// characters of all the texts of the section.
+    pub char_index: usize,
+    /// The color of the glyph. Defaults to the color of its text.
+    pub color: [f32; 4],
+    /// Whether the glyph is drawn.
+    pub visible: bool,
+}
+
+/// A section with animated texts, queued as pre-positioned glyphs so its
+/// vertices are generated in every frame.
+#[derive(Debug, Clone)]
+pub(crate) struct AnimatedSection {
+    pub index: usize,
+    pub glyphs: Vec<SectionGlyph>,
+    pub extras: Vec<GlyphExtra>,
+    pub char_indices: Vec<usize>,
+    pub bounds: Rect,
+}
+
+impl AnimatedSection {
+    pub fn new(
+        index: usize,
+        section: &GlyphSection<'_>,
+        glyphs: Vec<SectionGlyph>,
+        bounds: Rect,
+    ) -> Self {
+        // The index of the first character of every text and the byte
+        // offsets of its characters
+        let mut offsets = Vec::with_capacity(section.text.len());
+        let mut first = 0;
+
+        for text in &section.text {
+            let bytes: Vec<usize> =
+                text.text.char_indices().map(|(byte, _)| byte).collect();
+
+            first += bytes.len();
+            offsets.push((first - bytes.len(), bytes));
+        }
+
+        let char_indices = glyphs
+            .iter()
+            .map(|sg| {
+                let (first, bytes) = &offsets[sg.section_index];
+
+                first
+                    + bytes
+                        .binary_search(&sg.byte_index)
+                        .unwrap_or_else(|index| index)
+            })
+            .collect();
+
+        AnimatedSection {
+            index,
+            glyphs,
+            extras: section.text.iter().map(|text| text.extra).collect(),
+            char_indices,
+            bounds,
+        }
+    }
+
+    /// Returns the glyphs of the section to queue as pre-positioned glyphs,
+    /// each with an extra holding its id, given the index of the section
+    /// among the animated sections of the frame.
+    pub fn queued(
+        &self,
+        section: usize,
+    ) -> (Vec<SectionGlyph>, Vec<GlyphExtra>) {
+        self.glyphs
+            .iter()
+            .enumerate()
+            .map(|(glyph, sg)| {
+                let mut extra = self.extras[sg.section_index];
+                extra.animation = Some(GlyphId { section, glyph });
+
+                let sg = SectionGlyph {
+                    section_index: glyph,
+                    ..sg.clone()
+                };
+
+                (sg, extra)
+            })
+            .unzip()
+    }
+
+    /// The bounds the section is queued with, so `glyph_brush` never culls
+    /// its glyphs and every cached glyph has a vertex.
+    pub fn unbounded() -> Rect {
+        Rect {
+            min: point(f32::NEG_INFINITY, f32::NEG_INFINITY),
+            max: point(f32::INFINITY, f32::INFINITY),
+        }
+    }
+}
+
+/// Identifies a glyph of an animated section queued in a frame, carried by
+/// its extra into its vertex.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct GlyphId {
+    /// The index of the section among the animated sections of the frame.
+    pub section: usize,
+    /// The index of the glyph in the glyphs of the section.
+    pub glyph: usize,
+}
+
+/// The quad of a glyph of an animated section, as generated by
+/// `glyph_brush`.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct AnimatedVertex {
+    pub id: GlyphId,
+    pub tex_coords: Rect,
+    pub pixel_coords: Rect,
+}
+
+/// Returns whether any `Text` of the section is animated.
+pub(crate) fn is_animated(section: &GlyphSection<'_>) -> bool {
+    section.text.iter().any(|text| text.extra.animated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use glyph_brush::ab_glyph::{Glyph, PxScale};
+
+    fn text(text: &str, animated: bool) -> glyph_brush::Text<'_, GlyphExtra> {
+        glyph_brush::Text::<Extra>::new(text).with_extra(GlyphExtra {
+            animated,
+            ..GlyphExtra::default()
+        })
+    }
+
+    /// A glyph of the character at the given text and byte index.
+    fn glyph(text: usize, byte: usize) -> SectionGlyph {
+        SectionGlyph {
+            section_index: text,
+            byte_index: byte,
+            glyph: Glyph {
+                id: glyph_brush::ab_glyph::GlyphId(byte as u16),
+                scale: PxScale::from(16.0),
+                position: point(byte as f32 * 8.0, 16.0),
+            },
+            font_id: glyph_brush::FontId(0),
+        }
+    }
+
+    #[test]
+    fn sections_with_an_animated_text_are_animated() {
+        let still = GlyphSection::new()
+            .add_text(text("a", false))
+            .add_text(text("b", false));
+
+        assert!(!is_animated(&still));
+        assert!(!is_animated(&GlyphSection::new()));
+        assert!(is_animated(&still.add_text(text("c", true))));
+    }
+
+    #[test]
+    fn characters_are_counted_across_the_texts() {
+        // "é" and "ü" take two bytes, "→" three
+        let section = GlyphSection::new()
+            .add_text(text("héllo", false))
+            .add_text(text("→ü", true))
+            .add_text(text("!", false));
+
+        let glyphs = vec![
+            glyph(0, 0),
+            glyph(0, 3),
+            glyph(1, 0),
+            glyph(1, 3),
+            glyph(2, 0),
+        ];
+
+        let animated = AnimatedSection::new(
+            4,
+            &section,
+            glyphs,
+            AnimatedSection::unbounded(),
+        );
+
+        assert_eq!(animated.index, 4);
+        assert_eq!(animated.char_indices, [0, 2, 5, 6, 7]);
+        assert_eq!(animated.extras.len(), 3);
+        assert!(animated.extras[1].animated);
+    }
+
+    #[test]
+    fn glyphs_inside_a_character_count_from_the_next_one() {
+        // A glyph starting in the middle of a character, as no shaper would
+        // produce it, still gets an index in the text
+        let section = GlyphSection::new().add_text(text("aé", true));
+        let glyphs = vec![glyph(0, 2), glyph(0, 3)];
+
+        let animated = AnimatedSection::new(
+            0,
+            &section,
+            glyphs,
+            AnimatedSection::unbounded(),
+        );
+
+        assert_eq!(animated.char_indices, [2, 2]);
+    }
+
+    #[test]
+    fn queued_glyphs_carry_their_id() {
+        let section = GlyphSection::new()
+            .add_text(text("ab", false))
+            .add_text(text("c", true));
+        let glyphs = vec![glyph(0, 0), glyph(0, 1), glyph(1, 0)];
+
+        let animated = AnimatedSection::new(
+            0,
+            &section,
+            glyphs.clone(),
+            AnimatedSection::unbounded(),
+        );
+        let (queued, extras) = animated.queued(3);
+
+        // Every glyph is queued as a text of its own, so its vertex can be
+        // matched with its extra
+        for (index, (sg, extra)) in queued.iter().zip(&extras).enumerate() {
+            assert_eq!(sg.section_index, index);
+            assert_eq!(sg.byte_index, glyphs[index].byte_index);
+            assert_eq!(sg.glyph.position, glyphs[index].glyph.position);
+            assert_eq!(
+                extra.animation,
+                Some(GlyphId {
+                    section: 3,
+                    glyph: index,
+                })
+            );
+        }
+
+        assert_eq!(
+            extras
+                .iter()
+                .map(|extra| extra.animated)
+                .collect::<Vec<_>>(),
+            [false, false, true]
+        );
+    }
+}
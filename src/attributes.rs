@@ -0,0 +1,516 @@
+use crate::{
+    Affine, Billboard, Decoration, Ellipsis, Fill, InlineObject, Justification,
+    LineHeight, Outline, OwnedSection, Section, Shadow, StyleRef, TabStops,
+    WritingMode,
+};
+
+use std::borrow::Cow;
+
+/// The attributes of a [`Text`](struct.Text.html) of a
+/// [`RichSection`](struct.RichSection.html) that its
+/// [`Extra`](struct.Extra.html) does not carry: how its glyphs are painted
+/// and decorated, and how it is spaced.
+///
+/// ```
+/// use wgpu_glyph::{
+///     Decoration, DecorationStyle, RichSection, Section, SectionAttributes,
+///     Text, TextAttributes,
+/// };
+///
+/// let section = Section::default()
+///     .add_text(Text::new("Read the "))
+///     .add_text(Text::new("docs").with_color([0.1, 0.3, 0.9, 1.0]));
+///
+/// let attributes = SectionAttributes::default().with_text(
+///     1,
+///     TextAttributes::default()
+///         .with_underline(Decoration::new(DecorationStyle::Solid)),
+/// );
+///
+/// let link = RichSection::new(section, attributes);
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TextAttributes {
+    /// How the glyphs are painted. Decorations without a color of their own
+    /// keep using the color of the text.
+    pub fill: Fill,
+    /// The line drawn under the text, if any.
+    pub underline: Option<Decoration>,
+    /// The line drawn through the text, if any.
+    pub strikethrough: Option<Decoration>,
+    /// The line drawn over the text, if any.
+    pub overline: Option<Decoration>,
+    /// The outline drawn around the glyphs, if any.
+    pub outline: Option<Outline>,
+    /// Whether the glyphs are passed to the animation of the brush before
+    /// being drawn.
+    pub animated: bool,
+    /// The space added after every character, in pixels.
+    pub letter_spacing: f32,
+    /// The [`TextStyle`](struct.TextStyle.html) of the text, if any. Its
+    /// font and scale replace the ones of the text when queued, and its
+    /// color replaces the color of the text.
+    pub style: Option<StyleRef>,
+    /// The index of the color of the glyphs in the palette of the brush, if
+    /// any. It replaces the color of the text and the color of the style.
+    pub palette_index: Option<u32>,
+    /// The box laid out in place of the text, if any.
+    pub inline_object: Option<InlineObject>,
+}
+
+impl TextAttributes {
+    /// The attributes of the texts without any.
+    const DEFAULT: &'static TextAttributes = &TextAttributes {
+        fill: Fill::Solid,
+        underline: None,
+        strikethrough: None,
+        overline: None,
+        outline: None,
+        animated: false,
+        letter_spacing: 0.0,
+        style: None,
+        palette_index: None,
+        inline_object: None,
+    };
+
+    /// Paints the glyphs of the text with a gradient or a texture.
+    #[inline]
+    pub fn with_fill(mut self, fill: Fill) -> Self {
+        self.fill = fill;
+        self
+    }
+
+    /// Draws a line under the text.
+    #[inline]
+    pub fn with_underline(mut self, underline: Decoration) -> Self {
+        self.underline = Some(underline);
+        self
+    }
+
+    /// Draws a line through the text.
+    #[inline]
+    pub fn with_strikethrough(mut self, strikethrough: Decoration) -> Self {
+        self.strikethrough = Some(strikethrough);
+        self
+    }
+
+    /// Draws a line over the text.
+    #[inline]
+    pub fn with_overline(mut self, overline: Decoration) -> Self {
+        self.overline = Some(overline);
+        self
+    }
+
+    /// Draws an outline around the glyphs of the text.
+    #[inline]
+    pub fn with_outline(mut self, outline: Outline) -> Self {
+        self.outline = Some(outline);
+        self
+    }
+
+    /// Passes the glyphs of the text to the animation of the brush, set with
+    /// [`set_animation`](struct.GlyphBrush.html#method.set_animation).
+    #[inline]
+    pub fn animated(mut self) -> Self {
+        self.animated = true;
+        self
+    }
+
+    /// Adds space after every character of the text, in pixels. Negative
+    /// values tighten it.
+    ///
+    /// Honoured by [`queue`](struct.GlyphBrush.html#method.queue) and the
+    /// methods of the brush without a custom layout. A
+    /// [`TextLayout`](struct.TextLayout.html) sets it with
+    /// [`with_text_letter_spacing`](struct.TextLayout.html#method.with_text_letter_spacing).
+    #[inline]
+    pub fn with_letter_spacing(mut self, spacing: f32) -> Self {
+        self.letter_spacing = spacing;
+        self
+    }
+
+    /// Draws the text with a [`TextStyle`](struct.TextStyle.html) defined on
+    /// the brush, by name or by [`StyleId`](struct.StyleId.html).
+    ///
+    /// Texts with an undefined style keep their own font, scale and color.
+    #[inline]
+    pub fn with_style(mut self, style: impl Into<StyleRef>) -> Self {
+        self.style = Some(style.into());
+        self
+    }
+
+    /// Draws the text with a color of the palette of the brush, set with
+    /// [`set_palette`](struct.GlyphBrush.html#method.set_palette), so
+    /// changing the palette recolors it without laying it out again.
+    #[inline]
+    pub fn with_palette_color(mut self, index: u32) -> Self {
+        self.palette_index = Some(index);
+        self
+    }
+
+    /// Lays out a box in place of the text, see
+    /// [`InlineObject`](struct.InlineObject.html).
+    ///
+    /// Honoured by [`queue`](struct.GlyphBrush.html#method.queue) and the
+    /// methods of the brush without a custom layout. A
+    /// [`TextLayout`](struct.TextLayout.html) sets it with
+    /// [`with_inline_object`](struct.TextLayout.html#method.with_inline_object).
+    #[inline]
+    pub fn with_inline_object(mut self, object: InlineObject) -> Self {
+        self.inline_object = Some(object);
+        self
+    }
+}
+
+/// The attributes of a whole [`RichSection`](struct.RichSection.html),
+/// along with the [`TextAttributes`](struct.TextAttributes.html) of its
+/// texts.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SectionAttributes {
+    /// The height of the lines, if not the one of their fonts.
+    pub line_height: Option<LineHeight>,
+    /// The space added between the paragraphs, in pixels.
+    pub paragraph_spacing: f32,
+    /// The direction the lines flow in.
+    pub writing_mode: WritingMode,
+    /// How the lines are stretched to the width of the bounds, if they are.
+    pub justification: Option<Justification>,
+    /// How the text overflowing the bounds is truncated, if it is.
+    pub ellipsis: Option<Ellipsis>,
+    /// Where the text after tab characters moves to, if anywhere.
+    pub tab_stops: Option<TabStops>,
+    /// The transform of the glyphs and decorations, if any.
+    pub transform: Option<Affine>,
+    /// The world space anchor of the glyphs and decorations, if any.
+    pub billboard: Option<Billboard>,
+    /// The shadow drawn under the glyphs, if any.
+    pub shadow: Option<Shadow>,
+    /// The attributes of the texts, by index. Texts past the end have none.
+    pub texts: Vec<TextAttributes>,
+}
+
+impl SectionAttributes {
+    /// Sets the attributes of the text at the given index.
+    #[inline]
+    pub fn with_text(mut self, index: usize, text: TextAttributes) -> Self {
+        if self.texts.len() <= index {
+            self.texts.resize_with(index + 1, TextAttributes::default);
+        }
+
+        self.texts[index] = text;
+        self
+    }
+
+    /// Returns the attributes of the text at the given index.
+    #[inline]
+    pub fn text(&self, index: usize) -> &TextAttributes {
+        self.texts.get(index).unwrap_or(TextAttributes::DEFAULT)
+    }
+
+    /// Draws a shadow under the glyphs of the section.
+    #[inline]
+    pub fn with_shadow(mut self, shadow: Shadow) -> Self {
+        self.shadow = Some(shadow);
+        self
+    }
+
+    /// Transforms the glyphs of the section when drawing them, so sections
+    /// rotated differently can be drawn together.
+    ///
+    /// Glyphs are clipped to the bounds of the section before being
+    /// transformed.
+    #[inline]
+    pub fn with_transform(mut self, transform: Affine) -> Self {
+        self.transform = Some(transform);
+        self
+    }
+
+    /// Draws the section at an anchor in world space, facing the camera.
+    ///
+    /// A transform set with
+    /// [`with_transform`](#method.with_transform) is applied before placing
+    /// the section at the anchor.
+    #[inline]
+    pub fn with_billboard(mut self, billboard: Billboard) -> Self {
+        self.billboard = Some(billboard);
+        self
+    }
+
+    /// Sets the height of the lines of the section.
+    ///
+    /// Like the letter spacing of a text, it is honoured by
+    /// [`queue`](struct.GlyphBrush.html#method.queue) and the methods of the
+    /// brush without a custom layout. A
+    /// [`TextLayout`](struct.TextLayout.html) sets it with
+    /// [`with_line_height`](struct.TextLayout.html#method.with_line_height).
+    #[inline]
+    pub fn with_line_height(mut self, line_height: LineHeight) -> Self {
+        self.line_height = Some(line_height);
+        self
+    }
+
+    /// Adds space between the paragraphs of the section, in pixels.
+    ///
+    /// A [`TextLayout`](struct.TextLayout.html) sets it with
+    /// [`with_paragraph_spacing`](struct.TextLayout.html#method.with_paragraph_spacing).
+    #[inline]
+    pub fn with_paragraph_spacing(mut self, spacing: f32) -> Self {
+        self.paragraph_spacing = spacing;
+        self
+    }
+
+    /// Sets the direction the lines of the section flow in.
+    ///
+    /// Sections queued with [`queue`](struct.GlyphBrush.html#method.queue)
+    /// in a vertical writing mode turn their sideways glyphs, but are drawn
+    /// without decorations or animation. A
+    /// [`TextLayout`](struct.TextLayout.html) sets it with
+    /// [`with_writing_mode`](struct.TextLayout.html#method.with_writing_mode).
+    #[inline]
+    pub fn with_writing_mode(mut self, writing_mode: WritingMode) -> Self {
+        self.writing_mode = writing_mode;
+        self
+    }
+
+    /// Justifies the lines of the section to fill the width of its bounds,
+    /// except the last line of each paragraph.
+    ///
+    /// A [`TextLayout`](struct.TextLayout.html) sets it with
+    /// [`with_justification`](struct.TextLayout.html#method.with_justification).
+    #[inline]
+    pub fn with_justification(mut self, justification: Justification) -> Self {
+        self.justification = Some(justification);
+        self
+    }
+
+    /// Truncates the text that overflows the bounds of the section with the
+    /// given [`Ellipsis`](struct.Ellipsis.html), instead of clipping it.
+    ///
+    /// A [`TextLayout`](struct.TextLayout.html) sets it with
+    /// [`with_ellipsis`](struct.TextLayout.html#method.with_ellipsis).
+    #[inline]
+    pub fn with_ellipsis(mut self, ellipsis: Ellipsis) -> Self {
+        self.ellipsis = Some(ellipsis);
+        self
+    }
+
+    /// Moves the text after tab characters to the given
+    /// [`TabStops`](struct.TabStops.html). Without them, tabs have no width.
+    ///
+    /// A [`TextLayout`](struct.TextLayout.html) sets them with
+    /// [`with_tab_stops`](struct.TextLayout.html#method.with_tab_stops).
+    #[inline]
+    pub fn with_tab_stops(mut self, tab_stops: TabStops) -> Self {
+        self.tab_stops = Some(tab_stops);
+        self
+    }
+
+    /// Returns whether the attributes change the spacing, the writing mode,
+    /// the justification, the truncation or the tabs of the section, or lay
+    /// out inline objects.
+    pub(crate) fn changes_layout(&self) -> bool {
+        self.line_height.is_some()
+            || self.paragraph_spacing != 0.0
+            || self.writing_mode != WritingMode::default()
+            || self.justification.is_some()
+            || self.ellipsis.is_some()
+            || self.tab_stops.is_some()
+            || self.texts.iter().any(|text| {
+                text.letter_spacing != 0.0 || text.inline_object.is_some()
+            })
+    }
+}
+
+impl<'a> From<SectionAttributes> for Cow<'a, SectionAttributes> {
+    #[inline]
+    fn from(attributes: SectionAttributes) -> Self {
+        Cow::Owned(attributes)
+    }
+}
+
+impl<'a> From<&'a SectionAttributes> for Cow<'a, SectionAttributes> {
+    #[inline]
+    fn from(attributes: &'a SectionAttributes) -> Self {
+        Cow::Borrowed(attributes)
+    }
+}
+
+/// A [`Section`](struct.Section.html) with
+/// [`SectionAttributes`](struct.SectionAttributes.html), queued and measured
+/// by the brush like any section.
+///
+/// Every method of the brush taking a `RichSection` also takes a plain
+/// `Section`, which has no attributes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RichSection<'a> {
+    /// The text and layout of the section.
+    pub section: Cow<'a, Section<'a>>,
+    /// The attributes of the section and its texts.
+    pub attributes: Cow<'a, SectionAttributes>,
+}
+
+impl<'a> RichSection<'a> {
+    /// Creates a new `RichSection`.
+    #[inline]
+    pub fn new(
+        section: impl Into<Cow<'a, Section<'a>>>,
+        attributes: impl Into<Cow<'a, SectionAttributes>>,
+    ) -> Self {
+        RichSection {
+            section: section.into(),
+            attributes: attributes.into(),
+        }
+    }
+}
+
+impl<'a> From<Section<'a>> for RichSection<'a> {
+    #[inline]
+    fn from(section: Section<'a>) -> Self {
+        RichSection::new(section, SectionAttributes::default())
+    }
+}
+
+impl<'a> From<&'a Section<'a>> for RichSection<'a> {
+    #[inline]
+    fn from(section: &'a Section<'a>) -> Self {
+        RichSection::new(section, SectionAttributes::default())
+    }
+}
+
+impl<'a> From<Cow<'a, Section<'a>>> for RichSection<'a> {
+    #[inline]
+    fn from(section: Cow<'a, Section<'a>>) -> Self {
+        RichSection::new(section, SectionAttributes::default())
+    }
+}
+
+/// An owned version of [`RichSection`](struct.RichSection.html).
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct OwnedRichSection {
+    /// The text and layout of the section.
+    pub section: OwnedSection,
+    /// The attributes of the section and its texts.
+    pub attributes: SectionAttributes,
+}
+
+impl OwnedRichSection {
+    /// Borrows the section and its attributes.
+    #[inline]
+    pub fn to_borrowed(&self) -> RichSection<'_> {
+        RichSection::new(self.section.to_borrowed(), &self.attributes)
+    }
+}
+
+impl<'a> From<&'a OwnedRichSection> for RichSection<'a> {
+    #[inline]
+    fn from(section: &'a OwnedRichSection) -> Self {
+        section.to_borrowed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DecorationStyle, OwnedText, Text};
+
+    #[test]
+    fn texts_without_attributes_use_the_defaults() {
+        let underline = Decoration::new(DecorationStyle::Dotted);
+        let attributes = SectionAttributes::default()
+            .with_text(2, TextAttributes::default().with_underline(underline));
+
+        // The texts before the last one set are padded with defaults
+        assert_eq!(attributes.texts.len(), 3);
+        assert_eq!(attributes.text(0), &TextAttributes::default());
+        assert_eq!(attributes.text(2).underline, Some(underline));
+        assert_eq!(attributes.text(usize::MAX), &TextAttributes::default());
+    }
+
+    #[test]
+    fn setting_a_text_again_replaces_it() {
+        let attributes = SectionAttributes::default()
+            .with_text(1, TextAttributes::default().animated())
+            .with_text(0, TextAttributes::default().with_palette_color(3))
+            .with_text(1, TextAttributes::default().with_letter_spacing(2.0));
+
+        assert_eq!(attributes.texts.len(), 2);
+        assert_eq!(attributes.text(0).palette_index, Some(3));
+        assert!(!attributes.text(1).animated);
+        assert_eq!(attributes.text(1).letter_spacing, 2.0);
+    }
+
+    #[test]
+    fn attributes_changing_the_layout() {
+        let text = |text: TextAttributes| {
+            SectionAttributes::default()
+                .with_text(0, text)
+                .changes_layout()
+        };
+
+        assert!(!SectionAttributes::default().changes_layout());
+        assert!(!text(
+            TextAttributes::default()
+                .animated()
+                .with_outline(Outline::new([0.0, 0.0, 0.0, 1.0], 2.0))
+                .with_palette_color(0)
+        ));
+        assert!(
+            !SectionAttributes::default()
+                .with_shadow(Shadow::glow(4.0, [1.0; 4]))
+                .with_transform(Affine::rotation(1.0))
+                .changes_layout()
+        );
+
+        assert!(text(TextAttributes::default().with_letter_spacing(-1.0)));
+        assert!(text(
+            TextAttributes::default()
+                .with_inline_object(InlineObject::new(8.0, 8.0))
+        ));
+        assert!(
+            SectionAttributes::default()
+                .with_line_height(LineHeight::Relative(1.0))
+                .changes_layout()
+        );
+        assert!(
+            SectionAttributes::default()
+                .with_paragraph_spacing(4.0)
+                .changes_layout()
+        );
+        assert!(
+            SectionAttributes::default()
+                .with_writing_mode(WritingMode::VerticalRightToLeft)
+                .changes_layout()
+        );
+        assert!(
+            SectionAttributes::default()
+                .with_justification(Justification::InterWord)
+                .changes_layout()
+        );
+    }
+
+    #[test]
+    fn plain_sections_have_no_attributes() {
+        let section = Section::default().add_text(Text::new("plain"));
+        let rich = RichSection::from(&section);
+
+        assert!(matches!(rich.section, Cow::Borrowed(_)));
+        assert_eq!(*rich.attributes, SectionAttributes::default());
+        assert_eq!(RichSection::from(section.clone()), rich);
+    }
+
+    #[test]
+    fn owned_sections_borrow_their_attributes() {
+        let owned = OwnedRichSection {
+            section: OwnedSection::default()
+                .add_text(OwnedText::new("owned").with_scale(24.0)),
+            attributes: SectionAttributes::default()
+                .with_paragraph_spacing(6.0),
+        };
+        let rich = RichSection::from(&owned);
+
+        assert!(matches!(rich.attributes, Cow::Borrowed(_)));
+        assert_eq!(rich.attributes.paragraph_spacing, 6.0);
+        assert_eq!(rich.section.text[0].text, "owned");
+        assert_eq!(rich.section.text[0].scale, 24.0.into());
+    }
+}
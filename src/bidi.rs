@@ -0,0 +1,298 @@
+//! The Unicode Bidirectional Algorithm (UAX #9), resolved by
+//! `unicode-bidi`.
+//!
+//! The levels of a text are resolved by paragraph, including explicit
+//! embeddings, overrides and isolates. Lines are reordered by the layout,
+//! which resets trailing whitespace to the paragraph level (L1) before
+//! calling [`reorder`].
+
+pub(crate) use unicode_bidi::BidiClass as Class;
+
+use unicode_bidi::BidiInfo;
+
+use std::sync::OnceLock;
+
+/// Returns the bidirectional class of the given character.
+pub(crate) fn class(c: char) -> Class {
+    unicode_bidi::bidi_class(c)
+}
+
+/// Returns whether the class is reset to the paragraph level when it trails
+/// a line or precedes a separator: whitespace, isolate formatting characters
+/// and the characters removed by X9.
+pub(crate) fn is_whitespace(class: Class) -> bool {
+    matches!(
+        class,
+        Class::WS
+            | Class::BN
+            | Class::LRE
+            | Class::RLE
+            | Class::LRO
+            | Class::RLO
+            | Class::PDF
+            | Class::LRI
+            | Class::RLI
+            | Class::FSI
+            | Class::PDI
+    )
+}
+
+/// Returns whether the class is a segment or paragraph separator.
+pub(crate) fn is_separator(class: Class) -> bool {
+    matches!(class, Class::S | Class::B)
+}
+
+/// The resolved embedding level of a character.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) struct Level {
+    /// The level of the character. Odd levels are right-to-left.
+    pub level: u8,
+    /// The level of the paragraph the character belongs to.
+    pub paragraph: u8,
+}
+
+/// Returns whether the text has characters that can start right-to-left
+/// runs, so left-to-right text skips resolving its levels.
+pub(crate) fn has_right_to_left(text: &str) -> bool {
+    text.chars().any(|c| {
+        matches!(
+            class(c),
+            Class::R
+                | Class::AL
+                | Class::AN
+                | Class::RLE
+                | Class::RLO
+                | Class::RLI
+        )
+    })
+}
+
+/// Resolves the level of every byte of the given text, splitting it into
+/// paragraphs at paragraph separators.
+///
+/// Returns `None` when no character of the text is right-to-left, in which
+/// case every level is `0`.
+pub(crate) fn levels(text: &str) -> Option<Vec<Level>> {
+    if !has_right_to_left(text) {
+        return None;
+    }
+
+    let info = BidiInfo::new(text, None);
+
+    if !info.has_rtl() {
+        return None;
+    }
+
+    let mut levels = vec![Level::default(); text.len()];
+
+    for paragraph in &info.paragraphs {
+        let range = paragraph.range.clone();
+
+        for (level, resolved) in
+            levels[range.clone()].iter_mut().zip(&info.levels[range])
+        {
+            *level = Level {
+                level: resolved.number(),
+                paragraph: paragraph.level.number(),
+            };
+        }
+    }
+
+    Some(levels)
+}
+
+/// Returns the visual order of a line, given the levels of its items in
+/// logical order (L2).
+pub(crate) fn reorder(levels: &[u8]) -> Vec<usize> {
+    let levels: Vec<unicode_bidi::Level> = levels
+        .iter()
+        .map(|&level| {
+            // The levels are resolved by `levels`, which never exceed the
+            // maximum depth
+            unicode_bidi::Level::new(level)
+                .unwrap_or_else(|_| unicode_bidi::Level::rtl())
+        })
+        .collect();
+
+    BidiInfo::reorder_visual(&levels)
+}
+
+/// Returns the mirrored glyph of a character displayed right-to-left (L4),
+/// from the `Bidi_Mirroring_Glyph` property.
+///
+/// Characters are only mirrored to characters of the same UTF-8 length, so
+/// the byte offsets of a mirrored text match the original.
+pub(crate) fn mirror(c: char) -> char {
+    static PAIRS: OnceLock<Vec<(char, char)>> = OnceLock::new();
+
+    // `get_mirrored` misses some pairs when looked up by their second
+    // character, so the pairs, all in the BMP, are collected both ways once
+    let pairs = PAIRS.get_or_init(|| {
+        let mut pairs: Vec<(char, char)> = ('\0'..='\u{FFFF}')
+            .filter_map(|c| Some((c, unicode_bidi_mirroring::get_mirrored(c)?)))
+            .filter(|(c, mirrored)| c.len_utf8() == mirrored.len_utf8())
+            .flat_map(|(c, mirrored)| [(c, mirrored), (mirrored, c)])
+            .collect();
+
+        pairs.sort_unstable();
+        pairs.dedup_by_key(|(c, _)| *c);
+        pairs
+    });
+
+    pairs
+        .binary_search_by_key(&c, |(c, _)| *c)
+        .map_or(c, |index| pairs[index].1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the level of every character of the text.
+    fn char_levels(text: &str) -> Vec<u8> {
+        let levels = levels(text).expect("right-to-left text");
+
+        text.char_indices()
+            .map(|(index, _)| levels[index].level)
+            .collect()
+    }
+
+    #[test]
+    fn classes() {
+        assert_eq!(class('a'), Class::L);
+        assert_eq!(class('\u{5D0}'), Class::R);
+        assert_eq!(class('\u{628}'), Class::AL);
+        assert_eq!(class('1'), Class::EN);
+        assert_eq!(class('\u{661}'), Class::AN);
+        assert_eq!(class(' '), Class::WS);
+        assert_eq!(class('\t'), Class::S);
+        assert_eq!(class('\n'), Class::B);
+        assert_eq!(class('\u{64E}'), Class::NSM);
+        assert_eq!(class('\u{200D}'), Class::BN);
+        assert_eq!(class('\u{4E00}'), Class::L);
+
+        // Scripts and blocks far from Hebrew and Arabic
+        assert_eq!(class('\u{710}'), Class::AL);
+        assert_eq!(class('\u{7C0}'), Class::R);
+        assert_eq!(class('\u{FB1D}'), Class::R);
+        assert_eq!(class('\u{10800}'), Class::R);
+        assert_eq!(class('\u{1E900}'), Class::R);
+    }
+
+    #[test]
+    fn whitespace_and_separators() {
+        for c in [' ', '\u{200B}', '\u{202B}', '\u{202C}', '\u{2067}'] {
+            assert!(is_whitespace(class(c)), "{c:?}");
+        }
+
+        assert!(!is_whitespace(class('\t')));
+        assert!(is_separator(class('\t')));
+        assert!(is_separator(class('\u{2029}')));
+        assert!(!is_separator(class('a')));
+    }
+
+    #[test]
+    fn left_to_right_text_has_no_levels() {
+        assert_eq!(levels(""), None);
+        assert_eq!(levels("hello, world 42"), None);
+
+        // An Arabic number alone in a left-to-right paragraph keeps its
+        // position
+        assert_eq!(levels("a \u{661}"), None);
+    }
+
+    #[test]
+    fn explicit_overrides() {
+        // Right-to-left override of Latin letters, then a pop
+        assert_eq!(char_levels("a\u{202E}bc\u{202C}d"), [0, 0, 1, 1, 1, 0]);
+
+        // A right-to-left isolate in a left-to-right paragraph
+        assert_eq!(
+            char_levels("a \u{2067}\u{5D0}\u{2069} b"),
+            [0, 0, 0, 1, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn levels_of_mixed_text() {
+        // A left-to-right paragraph with a Hebrew word
+        assert_eq!(
+            char_levels("ab \u{5D0}\u{5D1} cd"),
+            [0, 0, 0, 1, 1, 0, 0, 0]
+        );
+
+        // A right-to-left paragraph with European numbers, which follow a
+        // Latin word like Latin letters
+        assert_eq!(char_levels("\u{5D0} 12"), [1, 1, 2, 2]);
+        assert_eq!(char_levels("\u{5D0} ab 12"), [1, 1, 2, 2, 2, 2, 2]);
+
+        // Numbers after Arabic letters are Arabic numbers
+        assert_eq!(char_levels("\u{628} 12"), [1, 1, 2, 2]);
+
+        // Neutrals between characters of the opposite direction take the
+        // paragraph direction
+        assert_eq!(char_levels("\u{5D0} - a"), [1, 1, 1, 1, 2]);
+    }
+
+    #[test]
+    fn paragraphs_resolve_their_own_level() {
+        let text = "a \u{5D0}\n\u{5D0} a";
+        let levels = levels(text).unwrap();
+        let paragraphs: Vec<u8> = text
+            .char_indices()
+            .map(|(index, _)| levels[index].paragraph)
+            .collect();
+
+        assert_eq!(paragraphs, [0, 0, 0, 0, 1, 1, 1]);
+
+        // The separator ends the first paragraph
+        assert_eq!(char_levels(text), [0, 0, 1, 0, 1, 1, 2]);
+    }
+
+    #[test]
+    fn levels_cover_every_byte() {
+        let text = "\u{5D0}\u{5D1}";
+        let levels = levels(text).unwrap();
+
+        assert_eq!(levels.len(), text.len());
+        assert!(levels.iter().all(|level| level.level == 1));
+    }
+
+    #[test]
+    fn marks_and_boundary_neutrals_follow_their_base() {
+        // An Arabic letter with a fatha, then a zero width joiner
+        assert_eq!(char_levels("a \u{628}\u{64E}\u{200D}"), [0, 0, 1, 1, 1]);
+    }
+
+    #[test]
+    fn reorder_reverses_right_to_left_runs() {
+        assert_eq!(reorder(&[]), Vec::<usize>::new());
+        assert_eq!(reorder(&[0, 0, 0]), [0, 1, 2]);
+        assert_eq!(reorder(&[1, 1, 1]), [2, 1, 0]);
+        assert_eq!(reorder(&[0, 1, 1, 0]), [0, 2, 1, 3]);
+
+        // Numbers in right-to-left text keep their order
+        assert_eq!(reorder(&[1, 1, 2, 2, 1]), [4, 2, 3, 1, 0]);
+    }
+
+    #[test]
+    fn mirrored_pairs() {
+        for c in "()<>[]{}«»‹›≤≥「」【】∈∋⟨⟩⦃⦄≺≻".chars()
+        {
+            let mirrored = mirror(c);
+
+            assert_ne!(mirrored, c);
+            assert_eq!(mirror(mirrored), c);
+        }
+
+        assert_eq!(mirror('a'), 'a');
+        assert_eq!(mirror('/'), '/');
+    }
+
+    #[test]
+    fn mirroring_keeps_byte_offsets() {
+        for c in (0..=0x10FFFF).filter_map(char::from_u32) {
+            assert_eq!(mirror(c).len_utf8(), c.len_utf8(), "{c:?}");
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use std::hash::{Hash, Hasher};
+
+/// An anchor in world space for a [`Section`](struct.Section.html), drawn
+/// facing the camera, like a nameplate over a character.
+///
+/// The `screen_position` of the section is placed at the anchor, so its
+/// layout alignment positions the text around it. The section is drawn with
+/// the depth of the anchor, and not at all when the anchor is behind the
+/// camera.
+///
+/// Billboards are projected with the camera set with
+/// [`GlyphBrush::set_camera`](struct.GlyphBrush.html#method.set_camera)
+/// when drawing them, so moving the camera does not lay them out again. The
+/// transform passed to `draw_queued_with_transform` is ignored for them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Billboard {
+    /// The position of the section in world space.
+    pub anchor: [f32; 3],
+    /// How the section is sized.
+    pub mode: BillboardMode,
+}
+
+/// How a [`Billboard`](struct.Billboard.html) is sized.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BillboardMode {
+    /// The section is sized in world space, `scale` world units per pixel of
+    /// its layout, and shrinks with distance.
+    World {
+        /// The size of a pixel of the layout, in world units.
+        scale: f32,
+    },
+    /// The section keeps the same size on screen at any distance: a pixel of
+    /// its layout covers a pixel of a target of the given size.
+    Screen {
+        /// The width of the target, in pixels.
+        width: f32,
+        /// The height of the target, in pixels.
+        height: f32,
+    },
+}
+
+impl Billboard {
+    /// Creates a new `Billboard`.
+    pub fn new(anchor: [f32; 3], mode: BillboardMode) -> Self {
+        Billboard { anchor, mode }
+    }
+}
+
+impl Hash for Billboard {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.anchor.map(f32::to_bits).hash(state);
+        self.mode.hash(state);
+    }
+}
+
+impl Hash for BillboardMode {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+
+        match *self {
+            BillboardMode::World { scale } => scale.to_bits().hash(state),
+            BillboardMode::Screen { width, height } => {
+                width.to_bits().hash(state);
+                height.to_bits().hash(state);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::style::Styles;
+    use crate::{RichSection, Section, SectionAttributes, Text};
+
+    use std::collections::hash_map::DefaultHasher;
+
+    fn hash(value: impl Hash) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn hashing_tells_modes_apart() {
+        let anchor = [1.0, 2.0, 3.0];
+        let world = Billboard::new(anchor, BillboardMode::World { scale: 1.0 });
+        let screen = Billboard::new(
+            anchor,
+            BillboardMode::Screen {
+                width: 1.0,
+                height: 1.0,
+            },
+        );
+
+        assert_eq!(
+            hash(world),
+            hash(Billboard::new(anchor, BillboardMode::World { scale: 1.0 }))
+        );
+        assert_ne!(hash(world), hash(screen));
+        assert_ne!(
+            hash(screen),
+            hash(Billboard::new(
+                anchor,
+                BillboardMode::Screen {
+                    width: 1.0,
+                    height: 2.0,
+                },
+            ))
+        );
+        assert_ne!(
+            hash(world),
+            hash(Billboard {
+                anchor: [1.0, 2.0, -3.0],
+                ..world
+            })
+        );
+
+        // Anchors are hashed by their bits, so a `NaN` anchor keeps its hash
+        let nan = Billboard {
+            anchor: [f32::NAN; 3],
+            ..world
+        };
+
+        assert_eq!(hash(nan), hash(nan));
+    }
+
+    #[test]
+    fn every_text_of_the_section_is_anchored() {
+        let billboard = Billboard::new(
+            [0.0, 10.0, -5.0],
+            BillboardMode::World { scale: 0.01 },
+        );
+        let section = Section::default()
+            .add_text(Text::new("Guard"))
+            .add_text(Text::new(" (level 3)"));
+        let attributes = SectionAttributes::default().with_billboard(billboard);
+
+        let glyph_section = crate::extra::glyph_section(
+            &RichSection::new(section, attributes),
+            &Styles::new(),
+        );
+
+        assert_eq!(glyph_section.text.len(), 2);
+
+        for text in &glyph_section.text {
+            assert_eq!(text.extra.billboard, Some(billboard));
+        }
+    }
+}
@@ -0,0 +1,639 @@
+mod bdf;
+mod pcf;
+mod sfnt;
+
+use glyph_brush::ab_glyph::{
+    CodepointIdIter, Font, FontVec, GlyphId, GlyphSvg, Outline, OutlineCurve,
+    PxScale, Rect, point, v2,
+};
+
+use std::fmt;
+use std::sync::OnceLock;
+
+/// A font of fixed-size bitmaps, loaded from a BDF or a PCF file.
+///
+/// Bitmap fonts have a single [`Strike`](struct.Strike.html): their glyphs
+/// are drawn for one pixel size. Every pixel of a glyph is traced as a
+/// square of its outline, so the glyphs are drawn pixel-exact at the
+/// [`scale`](struct.Strike.html#method.scale) of the strike, or at integer
+/// multiples of it, when placed at whole pixels. Build the brush with
+/// `texture_filter_method(wgpu::FilterMode::Nearest)` to sample them
+/// without blurring.
+///
+/// A `BitmapFont` is a [`Font`](ab_glyph/trait.Font.html), so it can be
+/// mixed with vector fonts by wrapping them all in a `FontArc`.
+///
+/// ```
+/// use wgpu_glyph::ab_glyph::{Font, point};
+/// use wgpu_glyph::{BitmapFont, GlyphBrushBuilder};
+///
+/// let font = BitmapFont::from_bdf(include_bytes!("../examples/tiny.bdf"))?;
+/// let strike = font.strike();
+///
+/// assert_eq!((strike.pixel_size, strike.ascent, strike.descent), (8, 7, 1));
+/// assert_eq!(font.advance(font.glyph_id('A')), Some(6));
+///
+/// // Glyphs at the scale of the strike cover whole pixels
+/// let glyph = font.glyph_id('A').with_scale_and_position(
+///     strike.scale(),
+///     point(0.0, 7.0),
+/// );
+///
+/// font.outline_glyph(glyph)
+///     .expect("glyph has pixels")
+///     .draw(|_, _, coverage| assert!(coverage == 0.0 || coverage == 1.0));
+///
+/// let builder = GlyphBrushBuilder::using_font(font)
+///     .texture_filter_method(wgpu::FilterMode::Nearest);
+/// # let _ = builder;
+/// # Ok::<(), wgpu_glyph::InvalidBitmapFont>(())
+/// ```
+pub struct BitmapFont {
+    metrics: Metrics,
+    /// The outline of every glyph, if it has pixels.
+    outlines: Vec<Option<Outline>>,
+}
+
+/// The pixel size and metrics of the glyphs of a
+/// [`BitmapFont`](struct.BitmapFont.html).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Strike {
+    /// The size of the em square, in pixels.
+    pub pixel_size: u16,
+    /// The distance from the baseline to the top of the lines, in pixels.
+    pub ascent: i32,
+    /// The distance from the baseline to the bottom of the lines, in
+    /// pixels.
+    pub descent: i32,
+}
+
+impl Strike {
+    /// Returns the scale that draws every pixel of the glyphs with exactly
+    /// one pixel.
+    pub fn scale(&self) -> PxScale {
+        PxScale::from(self.ascent as f32 + self.descent as f32)
+    }
+}
+
+/// An error returned when a BDF or PCF file cannot be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidBitmapFont {
+    reason: String,
+}
+
+impl InvalidBitmapFont {
+    pub(crate) fn new(reason: impl Into<String>) -> Self {
+        InvalidBitmapFont {
+            reason: reason.into(),
+        }
+    }
+}
+
+impl fmt::Display for InvalidBitmapFont {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid bitmap font: {}", self.reason)
+    }
+}
+
+impl std::error::Error for InvalidBitmapFont {}
+
+/// The glyph of a character of a bitmap font.
+#[derive(Debug, Clone, Default)]
+struct Bitmap {
+    /// The offset of the left edge of the pixels from the origin.
+    left: i32,
+    /// The offset of the top edge of the pixels from the baseline, growing
+    /// downwards.
+    top: i32,
+    width: u32,
+    height: u32,
+    /// The horizontal distance to the origin of the next glyph.
+    advance: i32,
+    /// Whether every pixel is set, row by row from the top.
+    pixels: Vec<bool>,
+}
+
+/// The contents of a bitmap font file.
+#[derive(Debug)]
+struct Parsed {
+    strike: Strike,
+    default_char: Option<u32>,
+    glyphs: Vec<(u32, Bitmap)>,
+}
+
+impl BitmapFont {
+    /// Parses a font in the Glyph Bitmap Distribution Format.
+    ///
+    /// Character encodings are taken as Unicode code points, which holds
+    /// for fonts in the `ISO10646` and `ISO8859-1` charsets.
+    pub fn from_bdf(data: &[u8]) -> Result<Self, InvalidBitmapFont> {
+        Self::from_parsed(bdf::parse(data)?)
+    }
+
+    /// Parses a font in the Portable Compiled Format. The data must not be
+    /// compressed.
+    ///
+    /// Character encodings are taken as Unicode code points, like in
+    /// [`from_bdf`](#method.from_bdf).
+    pub fn from_pcf(data: &[u8]) -> Result<Self, InvalidBitmapFont> {
+        Self::from_parsed(pcf::parse(data)?)
+    }
+
+    /// Returns the strike of the font.
+    pub fn strike(&self) -> Strike {
+        self.metrics.strike()
+    }
+
+    /// Returns the advance of the given glyph at the strike of the font, in
+    /// pixels.
+    pub fn advance(&self, id: GlyphId) -> Option<i32> {
+        self.metrics.advance(id)
+    }
+
+    fn from_parsed(parsed: Parsed) -> Result<Self, InvalidBitmapFont> {
+        let Parsed {
+            strike,
+            default_char,
+            mut glyphs,
+        } = parsed;
+
+        glyphs.sort_by_key(|(code, _)| *code);
+        glyphs.dedup_by_key(|(code, _)| *code);
+
+        // The first glyph is drawn for missing characters
+        let notdef = default_char
+            .and_then(|default_char| {
+                glyphs.iter().find(|(code, _)| *code == default_char)
+            })
+            .map(|(_, bitmap)| bitmap.clone())
+            .unwrap_or_default();
+
+        let bitmaps: Vec<&Bitmap> = std::iter::once(&notdef)
+            .chain(glyphs.iter().map(|(_, bitmap)| bitmap))
+            .collect();
+
+        if !bitmaps.iter().all(|bitmap| {
+            let left = i64::from(bitmap.left);
+            let top = i64::from(bitmap.top);
+
+            fits(left)
+                && fits(top)
+                && fits(left + i64::from(bitmap.width))
+                && fits(top + i64::from(bitmap.height))
+        }) {
+            return Err(InvalidBitmapFont::new("glyphs are too large"));
+        }
+
+        let metrics = Metrics::new(
+            strike,
+            bitmaps.iter().map(|bitmap| bitmap.advance).collect(),
+            glyphs.iter().map(|(code, _)| *code).collect(),
+        )?;
+
+        Ok(BitmapFont {
+            metrics,
+            outlines: bitmaps.into_iter().map(trace).collect(),
+        })
+    }
+}
+
+/// The largest metric of a glyph, in pixels, so that the edges of its
+/// pixels are exact in the outlines.
+const MAX_PIXELS: i64 = 1 << 24;
+
+/// Returns whether a metric, computed in 64 bits so that no sum of the
+/// parsed values overflows, is not too large.
+fn fits(value: i64) -> bool {
+    value.abs() <= MAX_PIXELS
+}
+
+/// Traces every run of pixels in a row of the bitmap as a rectangle, in
+/// font units of one pixel.
+///
+/// Returns `None` if no pixel is set.
+fn trace(bitmap: &Bitmap) -> Option<Outline> {
+    let width = bitmap.width as usize;
+    let mut curves = Vec::new();
+    let mut bounds: Option<Rect> = None;
+
+    for (row, pixels) in bitmap.pixels.chunks(width.max(1)).enumerate() {
+        let top = -(bitmap.top + row as i32) as f32;
+        let bottom = top - 1.0;
+        let mut column = 0;
+
+        while column < pixels.len() {
+            if !pixels[column] {
+                column += 1;
+                continue;
+            }
+
+            let start = column;
+
+            while column < pixels.len() && pixels[column] {
+                column += 1;
+            }
+
+            let left = (bitmap.left + start as i32) as f32;
+            let right = (bitmap.left + column as i32) as f32;
+
+            // Clockwise, from the top left corner
+            let corners = [
+                point(left, top),
+                point(right, top),
+                point(right, bottom),
+                point(left, bottom),
+            ];
+
+            for (index, corner) in corners.iter().enumerate() {
+                curves.push(OutlineCurve::Line(
+                    *corner,
+                    corners[(index + 1) % 4],
+                ));
+            }
+
+            // Like the outlines of `ab_glyph`, the bounds go from the top
+            // left corner to the bottom right one
+            let run = Rect {
+                min: point(left, top),
+                max: point(right, bottom),
+            };
+
+            bounds = Some(bounds.map_or(run, |bounds| Rect {
+                min: point(bounds.min.x.min(left), bounds.min.y.max(top)),
+                max: point(bounds.max.x.max(right), bounds.max.y.min(bottom)),
+            }));
+        }
+    }
+
+    Some(Outline {
+        bounds: bounds?,
+        curves,
+    })
+}
+
+/// The metrics and the character map of a font of fixed-size bitmaps, in
+/// font units of one pixel.
+pub(crate) struct Metrics {
+    strike: Strike,
+    /// The advance of every glyph, the first one being the glyph of missing
+    /// characters.
+    advances: Vec<i32>,
+    /// The code points of the glyphs following the first one, in ascending
+    /// order.
+    codes: Vec<u32>,
+    /// A font with the character map alone, built the first time the code
+    /// points of the glyphs are listed.
+    character_map: OnceLock<FontVec>,
+}
+
+impl Metrics {
+    /// Creates the metrics of a font with the given strike and advances.
+    /// The first advance is the one of missing characters, and the others
+    /// are the ones of the given code points, in ascending order.
+    pub(crate) fn new(
+        strike: Strike,
+        advances: Vec<i32>,
+        codes: Vec<u32>,
+    ) -> Result<Self, InvalidBitmapFont> {
+        if advances.len() > usize::from(u16::MAX) {
+            return Err(InvalidBitmapFont::new("too many glyphs"));
+        }
+
+        let ascent = i64::from(strike.ascent);
+        let descent = i64::from(strike.descent);
+
+        if !fits(ascent)
+            || !fits(descent)
+            || !advances.iter().all(|advance| fits(i64::from(*advance)))
+        {
+            return Err(InvalidBitmapFont::new("glyphs are too large"));
+        }
+
+        if ascent + descent <= 0 {
+            return Err(InvalidBitmapFont::new("empty line height"));
+        }
+
+        Ok(Metrics {
+            strike,
+            advances,
+            codes,
+            character_map: OnceLock::new(),
+        })
+    }
+
+    pub(crate) fn strike(&self) -> Strike {
+        self.strike
+    }
+
+    pub(crate) fn advance(&self, id: GlyphId) -> Option<i32> {
+        self.advances.get(usize::from(id.0)).copied()
+    }
+
+    pub(crate) fn units_per_em(&self) -> f32 {
+        match self.strike.pixel_size {
+            0 => (self.strike.ascent + self.strike.descent) as f32,
+            pixel_size => f32::from(pixel_size),
+        }
+    }
+
+    pub(crate) fn ascent(&self) -> f32 {
+        self.strike.ascent as f32
+    }
+
+    pub(crate) fn descent(&self) -> f32 {
+        -self.strike.descent as f32
+    }
+
+    pub(crate) fn glyph_id(&self, c: char) -> GlyphId {
+        self.codes
+            .binary_search(&u32::from(c))
+            .map_or(GlyphId(0), |index| GlyphId(index as u16 + 1))
+    }
+
+    pub(crate) fn h_advance(&self, id: GlyphId) -> f32 {
+        self.advance(id).unwrap_or(0).max(0) as f32
+    }
+
+    pub(crate) fn glyph_count(&self) -> usize {
+        self.advances.len()
+    }
+
+    /// Lists the code points of the glyphs from a font holding the
+    /// character map alone, as `ab_glyph` gives no other way to build the
+    /// iterator.
+    pub(crate) fn codepoint_ids(&self) -> CodepointIdIter<'_> {
+        self.character_map
+            .get_or_init(|| {
+                FontVec::try_from_vec(sfnt::character_map(
+                    &self.codes,
+                    self.advances.len() as u16,
+                ))
+                .expect("character map is a valid font")
+            })
+            .codepoint_ids()
+    }
+}
+
+impl fmt::Debug for BitmapFont {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BitmapFont")
+            .field("strike", &self.metrics.strike())
+            .field("glyphs", &self.metrics.glyph_count())
+            .finish()
+    }
+}
+
+/// The metrics are read from the strike and the advances of the font, and
+/// the outlines traced from the pixels of its glyphs.
+impl Font for BitmapFont {
+    #[inline]
+    fn units_per_em(&self) -> Option<f32> {
+        Some(self.metrics.units_per_em())
+    }
+
+    #[inline]
+    fn ascent_unscaled(&self) -> f32 {
+        self.metrics.ascent()
+    }
+
+    #[inline]
+    fn descent_unscaled(&self) -> f32 {
+        self.metrics.descent()
+    }
+
+    #[inline]
+    fn line_gap_unscaled(&self) -> f32 {
+        0.0
+    }
+
+    #[inline]
+    fn italic_angle(&self) -> f32 {
+        0.0
+    }
+
+    #[inline]
+    fn glyph_id(&self, c: char) -> GlyphId {
+        self.metrics.glyph_id(c)
+    }
+
+    #[inline]
+    fn h_advance_unscaled(&self, id: GlyphId) -> f32 {
+        self.metrics.h_advance(id)
+    }
+
+    fn h_side_bearing_unscaled(&self, id: GlyphId) -> f32 {
+        self.outlines
+            .get(usize::from(id.0))
+            .and_then(Option::as_ref)
+            .map_or(0.0, |outline| outline.bounds.min.x)
+    }
+
+    #[inline]
+    fn v_advance_unscaled(&self, _id: GlyphId) -> f32 {
+        0.0
+    }
+
+    #[inline]
+    fn v_side_bearing_unscaled(&self, _id: GlyphId) -> f32 {
+        0.0
+    }
+
+    #[inline]
+    fn kern_unscaled(&self, _first: GlyphId, _second: GlyphId) -> f32 {
+        0.0
+    }
+
+    fn outline(&self, id: GlyphId) -> Option<Outline> {
+        self.outlines.get(usize::from(id.0))?.clone()
+    }
+
+    #[inline]
+    fn glyph_count(&self) -> usize {
+        self.metrics.glyph_count()
+    }
+
+    #[inline]
+    fn codepoint_ids(&self) -> CodepointIdIter<'_> {
+        self.metrics.codepoint_ids()
+    }
+
+    #[inline]
+    fn glyph_raster_image2(
+        &self,
+        _id: GlyphId,
+        _pixel_size: u16,
+    ) -> Option<v2::GlyphImage<'_>> {
+        None
+    }
+
+    #[inline]
+    fn glyph_svg_image(&self, _id: GlyphId) -> Option<GlyphSvg<'_>> {
+        None
+    }
+
+    #[inline]
+    fn font_data(&self) -> &[u8] {
+        &[]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use glyph_brush::ab_glyph::point;
+
+    const STRIKE: Strike = Strike {
+        pixel_size: 10,
+        ascent: 8,
+        descent: 2,
+    };
+
+    /// Builds a bitmap from rows of `#` and `.`, with its top left pixel at
+    /// the given offset from the origin.
+    fn bitmap((left, top): (i32, i32), advance: i32, rows: &[&str]) -> Bitmap {
+        Bitmap {
+            left,
+            top,
+            width: rows.first().map_or(0, |row| row.len() as u32),
+            height: rows.len() as u32,
+            advance,
+            pixels: rows.concat().chars().map(|pixel| pixel == '#').collect(),
+        }
+    }
+
+    /// Draws a glyph at the scale of the strike, with its origin at the
+    /// baseline of a line starting at the top of the pixels, as rows of `#`
+    /// and `.` from the top of its pixel bounds.
+    fn draw(font: &BitmapFont, id: GlyphId) -> (Rect, Vec<String>) {
+        let glyph = id.with_scale_and_position(
+            font.strike().scale(),
+            point(0.0, font.strike().ascent as f32),
+        );
+        let outlined = font.outline_glyph(glyph).unwrap();
+        let bounds = outlined.px_bounds();
+        let width = bounds.width() as usize;
+        let mut rows = vec![vec!['.'; width]; bounds.height() as usize];
+
+        outlined.draw(|x, y, coverage| {
+            assert!(coverage == 0.0 || coverage == 1.0, "{coverage}");
+
+            if coverage == 1.0 {
+                rows[y as usize][x as usize] = '#';
+            }
+        });
+
+        (bounds, rows.into_iter().map(String::from_iter).collect())
+    }
+
+    #[test]
+    fn glyphs_are_drawn_from_their_pixels() {
+        let font = BitmapFont::from_parsed(Parsed {
+            strike: STRIKE,
+            default_char: Some(0),
+            glyphs: vec![
+                (0, bitmap((0, -1), 4, &["##", "##"])),
+                ('n' as u32, bitmap((1, -3), 5, &["#.#", "###", "..."])),
+            ],
+        })
+        .unwrap();
+        let n = font.glyph_id('n');
+
+        assert_eq!(font.units_per_em(), Some(10.0));
+        assert_eq!(font.ascent_unscaled(), 8.0);
+        assert_eq!(font.descent_unscaled(), -2.0);
+        assert_eq!(font.glyph_count(), 3);
+        assert_eq!(n, GlyphId(2));
+        assert_eq!(font.glyph_id('m'), GlyphId(0));
+        assert_eq!(font.h_advance_unscaled(n), 5.0);
+        assert_eq!(font.h_side_bearing_unscaled(n), 1.0);
+
+        // The top row has two runs, the middle row one, the bottom row none
+        let (bounds, rows) = draw(&font, n);
+
+        assert_eq!(bounds.min, point(1.0, 5.0));
+        assert_eq!(bounds.max, point(4.0, 7.0));
+        assert_eq!(rows, ["#.#", "###"]);
+
+        // The default character is drawn for missing ones
+        assert_eq!(draw(&font, GlyphId(0)).1, ["##", "##"]);
+    }
+
+    #[test]
+    fn blank_and_degenerate_glyphs() {
+        // A strike without a pixel size is sized by its lines
+        let font = BitmapFont::from_parsed(Parsed {
+            strike: Strike {
+                pixel_size: 0,
+                ..STRIKE
+            },
+            default_char: None,
+            glyphs: vec![
+                (32, bitmap((0, -1), 4, &["....", "...."])),
+                (0x2190, bitmap((0, -1), -2, &["#"])),
+            ],
+        })
+        .unwrap();
+        let (space, backwards) = (font.glyph_id(' '), font.glyph_id('←'));
+
+        assert_eq!(font.units_per_em(), Some(10.0));
+
+        for id in [GlyphId(0), space] {
+            assert!(font.outline(id).is_none());
+            assert_eq!(font.h_side_bearing_unscaled(id), 0.0);
+        }
+
+        assert_eq!(font.h_advance_unscaled(GlyphId(0)), 0.0);
+        assert_eq!(font.h_advance_unscaled(space), 4.0);
+        assert_eq!(font.advance(backwards), Some(-2));
+        assert_eq!(font.h_advance_unscaled(backwards), 0.0);
+        assert_eq!(draw(&font, backwards).1, ["#"]);
+    }
+
+    #[test]
+    fn code_points_are_listed_with_their_glyphs() {
+        let font = BitmapFont::from_bdf(include_bytes!("../examples/tiny.bdf"))
+            .unwrap();
+
+        let mut ids: Vec<(GlyphId, char)> = font.codepoint_ids().collect();
+        ids.sort();
+
+        assert!(!ids.is_empty());
+
+        for (id, c) in ids {
+            assert_eq!(font.glyph_id(c), id);
+        }
+    }
+
+    #[test]
+    fn rejects_glyphs_beyond_the_outline_range() {
+        let font = |lines: &[&str]| {
+            let lines = lines.join("\n");
+
+            BitmapFont::from_bdf(
+                format!("STARTFONT 2.1\nPIXEL_SIZE 8\n{lines}\n").as_bytes(),
+            )
+            .map(|_| ())
+        };
+        let too_large = Err(InvalidBitmapFont::new("glyphs are too large"));
+
+        assert_eq!(font(&["FONT_ASCENT 7", "FONT_DESCENT 1"]), Ok(()));
+        assert_eq!(
+            font(&["FONT_ASCENT 2147483647", "FONT_DESCENT 1"]),
+            too_large
+        );
+        assert_eq!(
+            font(&[
+                "STARTCHAR A",
+                "ENCODING 65",
+                "BBX 1 1 2147483647 0",
+                "ENDCHAR",
+            ]),
+            too_large
+        );
+        assert_eq!(
+            font(&["FONT_ASCENT 1", "FONT_DESCENT -1"]),
+            Err(InvalidBitmapFont::new("empty line height"))
+        );
+    }
+}
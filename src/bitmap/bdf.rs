@@ -0,0 +1,325 @@
+//! Parses fonts in the Glyph Bitmap Distribution Format, a text format
+//! listing the bitmap of every character in hexadecimal rows.
+
+use super::{Bitmap, InvalidBitmapFont, Parsed, Strike};
+
+pub(super) fn parse(data: &[u8]) -> Result<Parsed, InvalidBitmapFont> {
+    let text = String::from_utf8_lossy(data);
+    let mut lines = text.lines().enumerate().map(|(index, line)| {
+        let mut words = line.split_whitespace();
+        let keyword = words.next().unwrap_or_default();
+
+        (index + 1, keyword, words.collect::<Vec<_>>())
+    });
+
+    if !matches!(lines.next(), Some((_, "STARTFONT", _))) {
+        return Err(InvalidBitmapFont::new("missing STARTFONT"));
+    }
+
+    let mut pixel_size = None;
+    let mut point_size = None;
+    let mut ascent = None;
+    let mut descent = None;
+    let mut bounding_box = None;
+    let mut default_char = None;
+    let mut default_advance = 0;
+    let mut glyphs = Vec::new();
+
+    while let Some((line, keyword, values)) = lines.next() {
+        let int = |index: usize| -> Result<i32, InvalidBitmapFont> {
+            values
+                .get(index)
+                .and_then(|value| value.parse().ok())
+                .ok_or_else(|| {
+                    InvalidBitmapFont::new(format!(
+                        "invalid {keyword} on line {line}"
+                    ))
+                })
+        };
+
+        match keyword {
+            "SIZE" => point_size = Some((int(0)?, int(2)?)),
+            "FONTBOUNDINGBOX" => bounding_box = Some((int(1)?, int(3)?)),
+            "PIXEL_SIZE" => pixel_size = Some(int(0)?),
+            "FONT_ASCENT" => ascent = Some(int(0)?),
+            "FONT_DESCENT" => descent = Some(int(0)?),
+            "DEFAULT_CHAR" => default_char = Some(int(0)? as u32),
+            "DWIDTH" => default_advance = int(0)?,
+            "STARTCHAR" => {
+                if let Some(glyph) = parse_char(&mut lines, default_advance)? {
+                    glyphs.push(glyph);
+                }
+            }
+            "ENDFONT" => break,
+            _ => {}
+        }
+    }
+
+    let overflow = || InvalidBitmapFont::new("metrics are too large");
+
+    let (height, y_offset) = bounding_box.unwrap_or_default();
+    let ascent = match ascent {
+        Some(ascent) => ascent,
+        None => height.checked_add(y_offset).ok_or_else(overflow)?,
+    };
+    let descent = match descent {
+        Some(descent) => descent,
+        None => y_offset.checked_neg().ok_or_else(overflow)?,
+    };
+
+    // The size in points is converted with the vertical resolution
+    let pixel_size = match (pixel_size, point_size) {
+        (Some(pixel_size), _) => pixel_size,
+        (None, Some((size, resolution))) => {
+            size.checked_mul(resolution)
+                .and_then(|size| size.checked_add(36))
+                .ok_or_else(overflow)?
+                / 72
+        }
+        (None, None) => ascent.checked_add(descent).ok_or_else(overflow)?,
+    };
+
+    Ok(Parsed {
+        strike: Strike {
+            pixel_size: pixel_size.clamp(0, i32::from(u16::MAX)) as u16,
+            ascent,
+            descent,
+        },
+        default_char,
+        glyphs,
+    })
+}
+
+/// Parses the lines of a character up to its `ENDCHAR`.
+///
+/// Returns `None` if the character has no encoding.
+fn parse_char<'a>(
+    lines: &mut impl Iterator<Item = (usize, &'a str, Vec<&'a str>)>,
+    default_advance: i32,
+) -> Result<Option<(u32, Bitmap)>, InvalidBitmapFont> {
+    let mut code = None;
+    let mut bitmap = Bitmap {
+        advance: default_advance,
+        ..Bitmap::default()
+    };
+
+    while let Some((line, keyword, values)) = lines.next() {
+        let invalid = || {
+            InvalidBitmapFont::new(format!("invalid {keyword} on line {line}"))
+        };
+        let int = |index: usize| -> Result<i32, InvalidBitmapFont> {
+            values
+                .get(index)
+                .and_then(|value| value.parse().ok())
+                .ok_or_else(invalid)
+        };
+
+        match keyword {
+            "ENCODING" => {
+                // Unencoded characters may have a code in a second field
+                code = match int(0)? {
+                    -1 => int(1).ok(),
+                    code => Some(code),
+                }
+                .and_then(|code| u32::try_from(code).ok());
+            }
+            "DWIDTH" => bitmap.advance = int(0)?,
+            "BBX" => {
+                bitmap.width = u32::try_from(int(0)?).map_err(|_| invalid())?;
+                bitmap.height =
+                    u32::try_from(int(1)?).map_err(|_| invalid())?;
+                bitmap.left = int(2)?;
+                bitmap.top = i32::try_from(bitmap.height)
+                    .ok()
+                    .and_then(|height| int(3).ok()?.checked_add(height))
+                    .and_then(i32::checked_neg)
+                    .ok_or_else(invalid)?;
+            }
+            "BITMAP" => {
+                let width = bitmap.width as usize;
+
+                for _ in 0..bitmap.height {
+                    let (line, row, _) = lines.next().ok_or_else(invalid)?;
+
+                    let bits = row
+                        .as_bytes()
+                        .chunks(2)
+                        .map(|digits| {
+                            std::str::from_utf8(digits)
+                                .ok()
+                                .and_then(|digits| {
+                                    u8::from_str_radix(digits, 16).ok()
+                                })
+                                .ok_or_else(|| {
+                                    InvalidBitmapFont::new(format!(
+                                        "invalid bitmap row on line {line}"
+                                    ))
+                                })
+                        })
+                        .collect::<Result<Vec<u8>, _>>()?;
+
+                    if bits.len() * 8 < width {
+                        return Err(InvalidBitmapFont::new(format!(
+                            "short bitmap row on line {line}"
+                        )));
+                    }
+
+                    bitmap.pixels.extend(
+                        (0..width)
+                            .map(|x| bits[x / 8] & (0x80 >> (x % 8)) != 0),
+                    );
+                }
+            }
+            "ENDCHAR" => return Ok(code.map(|code| (code, bitmap))),
+            _ => {}
+        }
+    }
+
+    Err(InvalidBitmapFont::new("missing ENDCHAR"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parses a font made of the given lines, with a `STARTFONT` line added
+    /// on top.
+    fn parse_lines(lines: &[&str]) -> Result<Parsed, InvalidBitmapFont> {
+        parse(format!("STARTFONT 2.1\n{}\n", lines.join("\n")).as_bytes())
+    }
+
+    #[test]
+    fn parses_tiny_font() {
+        let parsed = parse(include_bytes!("../../examples/tiny.bdf")).unwrap();
+
+        assert_eq!(
+            parsed.strike,
+            Strike {
+                pixel_size: 8,
+                ascent: 7,
+                descent: 1,
+            }
+        );
+        assert_eq!(parsed.default_char, Some(0));
+
+        let codes: Vec<u32> =
+            parsed.glyphs.iter().map(|(code, _)| *code).collect();
+        assert_eq!(codes, [0, 32, 33, 65, 66, 72, 105]);
+
+        let (_, notdef) = &parsed.glyphs[0];
+        assert_eq!((notdef.left, notdef.top), (0, -7));
+        assert_eq!((notdef.width, notdef.height, notdef.advance), (5, 8, 6));
+
+        let (_, a) = &parsed.glyphs[3];
+        assert_eq!((a.top, a.width, a.height), (-7, 5, 7));
+        assert_eq!(
+            a.pixels[..10],
+            [
+                false, true, true, true, false, true, false, false, false, true
+            ]
+        );
+    }
+
+    #[test]
+    fn falls_back_to_point_size_and_bounding_box() {
+        let parsed = parse_lines(&[
+            "SIZE 12 72 96",
+            "FONTBOUNDINGBOX 6 10 0 -2",
+            "ENDFONT",
+        ])
+        .unwrap();
+
+        // 12pt at 96 dpi, rounded to the nearest pixel
+        assert_eq!(
+            parsed.strike,
+            Strike {
+                pixel_size: 16,
+                ascent: 8,
+                descent: 2,
+            }
+        );
+
+        let parsed = parse_lines(&["FONTBOUNDINGBOX 6 10 0 -2"]).unwrap();
+        assert_eq!(parsed.strike.pixel_size, 10);
+    }
+
+    #[test]
+    fn skips_unencoded_characters() {
+        let parsed = parse_lines(&[
+            "STARTCHAR unencoded",
+            "ENCODING -1",
+            "ENDCHAR",
+            "STARTCHAR alternate",
+            "ENCODING -1 66",
+            "DWIDTH 3 0",
+            "ENDCHAR",
+        ])
+        .unwrap();
+
+        assert_eq!(parsed.glyphs.len(), 1);
+        assert_eq!(parsed.glyphs[0].0, 66);
+        assert_eq!(parsed.glyphs[0].1.advance, 3);
+    }
+
+    #[test]
+    fn rejects_malformed_fonts() {
+        let error = |reason: &str| Err(InvalidBitmapFont::new(reason));
+
+        assert_eq!(
+            parse(b"FONT tiny\n").map(|_| ()),
+            error("missing STARTFONT")
+        );
+        assert_eq!(
+            parse_lines(&["FONT_ASCENT seven"]).map(|_| ()),
+            error("invalid FONT_ASCENT on line 2")
+        );
+        assert_eq!(
+            parse_lines(&["STARTCHAR A", "ENCODING 65"]).map(|_| ()),
+            error("missing ENDCHAR")
+        );
+        assert_eq!(
+            parse_lines(&["STARTCHAR A", "BBX -1 1 0 0", "ENDCHAR"])
+                .map(|_| ()),
+            error("invalid BBX on line 3")
+        );
+        assert_eq!(
+            parse_lines(&["STARTCHAR A", "BBX 9 1 0 0", "BITMAP", "FF"])
+                .map(|_| ()),
+            error("short bitmap row on line 5")
+        );
+        assert_eq!(
+            parse_lines(&["STARTCHAR A", "BBX 8 1 0 0", "BITMAP", "GG"])
+                .map(|_| ()),
+            error("invalid bitmap row on line 5")
+        );
+        assert_eq!(
+            parse_lines(&["STARTCHAR A", "BBX 8 2 0 0", "BITMAP", "FF"])
+                .map(|_| ()),
+            error("invalid BITMAP on line 4")
+        );
+    }
+
+    #[test]
+    fn rejects_overflowing_metrics() {
+        let error = |reason: &str| Err(InvalidBitmapFont::new(reason));
+
+        assert_eq!(
+            parse_lines(&["SIZE 2147483647 0 2147483647"]).map(|_| ()),
+            error("metrics are too large")
+        );
+        assert_eq!(
+            parse_lines(&["FONT_ASCENT 2147483647", "FONT_DESCENT 1"])
+                .map(|_| ()),
+            error("metrics are too large")
+        );
+        assert_eq!(
+            parse_lines(&["FONTBOUNDINGBOX 1 1 0 -2147483648"]).map(|_| ()),
+            error("metrics are too large")
+        );
+        assert_eq!(
+            parse_lines(&["STARTCHAR A", "BBX 1 1 0 2147483647", "ENDCHAR"])
+                .map(|_| ()),
+            error("invalid BBX on line 3")
+        );
+    }
+}
@@ -0,0 +1,584 @@
+//! Parses fonts in the Portable Compiled Format, the binary counterpart of
+//! BDF made of tables in the byte and bit orders of the machine that
+//! compiled them.
+
+use super::{Bitmap, InvalidBitmapFont, Parsed, Strike};
+
+const PROPERTIES: u32 = 1 << 0;
+const ACCELERATORS: u32 = 1 << 1;
+const METRICS: u32 = 1 << 2;
+const BITMAPS: u32 = 1 << 3;
+const BDF_ENCODINGS: u32 = 1 << 5;
+const BDF_ACCELERATORS: u32 = 1 << 8;
+
+const GLYPH_PAD_MASK: u32 = 3;
+const BYTE_MASK: u32 = 1 << 2;
+const BIT_MASK: u32 = 1 << 3;
+const SCAN_UNIT_MASK: u32 = 3 << 4;
+const COMPRESSED_METRICS: u32 = 1 << 8;
+
+/// The metrics of a glyph, in pixels.
+struct Metrics {
+    left_bearing: i32,
+    right_bearing: i32,
+    width: i32,
+    ascent: i32,
+    descent: i32,
+}
+
+pub(super) fn parse(data: &[u8]) -> Result<Parsed, InvalidBitmapFont> {
+    if !data.starts_with(b"\x01fcp") {
+        return Err(InvalidBitmapFont::new("missing PCF header"));
+    }
+
+    let mut header = Reader::new(data, 4);
+    let count = header.u32()?;
+    let mut tables = Vec::new();
+
+    for _ in 0..count {
+        let kind = header.u32()?;
+        let _format = header.u32()?;
+        let _size = header.u32()?;
+        let offset = header.u32()?;
+
+        tables.push((kind, offset as usize));
+    }
+
+    let table = |kind: u32| {
+        tables
+            .iter()
+            .find(|(table, _)| *table == kind)
+            .map(|(_, offset)| Table::read(data, *offset))
+            .transpose()
+    };
+
+    let pixel_size = match table(PROPERTIES)? {
+        Some(properties) => pixel_size(properties)?,
+        None => None,
+    };
+
+    // The accelerators computed from the BDF source are more accurate, so
+    // they are preferred
+    let accelerators = match table(BDF_ACCELERATORS)? {
+        Some(accelerators) => Some(accelerators),
+        None => table(ACCELERATORS)?,
+    };
+
+    let (ascent, descent) = match accelerators {
+        Some(Table { mut reader, .. }) => {
+            reader.skip(8)?;
+
+            (reader.i32()?, reader.i32()?)
+        }
+        None => return Err(InvalidBitmapFont::new("missing accelerators")),
+    };
+
+    let metrics = metrics(
+        table(METRICS)?
+            .ok_or_else(|| InvalidBitmapFont::new("missing metrics"))?,
+    )?;
+    let bitmaps = bitmaps(
+        table(BITMAPS)?
+            .ok_or_else(|| InvalidBitmapFont::new("missing bitmaps"))?,
+        &metrics,
+    )?;
+    let (default_char, encodings) = encodings(
+        table(BDF_ENCODINGS)?
+            .ok_or_else(|| InvalidBitmapFont::new("missing encodings"))?,
+    )?;
+
+    let glyphs = encodings
+        .into_iter()
+        .filter_map(|(code, index)| {
+            bitmaps.get(index).map(|bitmap| (code, bitmap.clone()))
+        })
+        .collect();
+
+    let pixel_size = match pixel_size {
+        Some(pixel_size) => pixel_size,
+        None => ascent
+            .checked_add(descent)
+            .ok_or_else(|| InvalidBitmapFont::new("metrics are too large"))?,
+    };
+
+    Ok(Parsed {
+        strike: Strike {
+            pixel_size: pixel_size.clamp(0, i32::from(u16::MAX)) as u16,
+            ascent,
+            descent,
+        },
+        default_char: Some(default_char),
+        glyphs,
+    })
+}
+
+/// Returns the `PIXEL_SIZE` property, if any.
+fn pixel_size(table: Table<'_>) -> Result<Option<i32>, InvalidBitmapFont> {
+    let mut reader = table.reader;
+    let count = reader.u32()? as usize;
+    let mut properties = Vec::new();
+
+    for _ in 0..count {
+        let name = reader.u32()? as usize;
+        let is_string = reader.u8()? != 0;
+        let value = reader.i32()?;
+
+        properties.push((name, is_string, value));
+    }
+
+    // The properties are padded to 4 bytes
+    reader.skip((4 - count % 4) % 4)?;
+
+    let size = reader.u32()? as usize;
+    let strings = reader.bytes(size)?;
+
+    Ok(properties
+        .into_iter()
+        .find(|(name, is_string, _)| {
+            !is_string
+                && strings.get(*name..).is_some_and(|name| {
+                    name.split(|byte| *byte == 0).next() == Some(b"PIXEL_SIZE")
+                })
+        })
+        .map(|(_, _, value)| value))
+}
+
+fn metrics(table: Table<'_>) -> Result<Vec<Metrics>, InvalidBitmapFont> {
+    let mut reader = table.reader;
+
+    if table.format & COMPRESSED_METRICS != 0 {
+        let count = reader.u16()?;
+
+        (0..count)
+            .map(|_| {
+                let mut byte = || -> Result<i32, InvalidBitmapFont> {
+                    Ok(i32::from(reader.u8()?) - 0x80)
+                };
+
+                Ok(Metrics {
+                    left_bearing: byte()?,
+                    right_bearing: byte()?,
+                    width: byte()?,
+                    ascent: byte()?,
+                    descent: byte()?,
+                })
+            })
+            .collect()
+    } else {
+        let count = reader.u32()?;
+
+        (0..count)
+            .map(|_| {
+                let metrics = Metrics {
+                    left_bearing: reader.i16()?,
+                    right_bearing: reader.i16()?,
+                    width: reader.i16()?,
+                    ascent: reader.i16()?,
+                    descent: reader.i16()?,
+                };
+
+                // The attributes are not used
+                let _ = reader.i16()?;
+
+                Ok(metrics)
+            })
+            .collect()
+    }
+}
+
+fn bitmaps(
+    table: Table<'_>,
+    metrics: &[Metrics],
+) -> Result<Vec<Bitmap>, InvalidBitmapFont> {
+    let mut reader = table.reader;
+    let count = reader.u32()? as usize;
+
+    if count != metrics.len() {
+        return Err(InvalidBitmapFont::new("mismatched bitmaps and metrics"));
+    }
+
+    let offsets = (0..count)
+        .map(|_| Ok(reader.u32()? as usize))
+        .collect::<Result<Vec<_>, InvalidBitmapFont>>()?;
+
+    let mut sizes = [0; 4];
+
+    for size in &mut sizes {
+        *size = reader.u32()? as usize;
+    }
+
+    let pad = 1 << (table.format & GLYPH_PAD_MASK);
+    let mut data = reader
+        .bytes(sizes[(table.format & GLYPH_PAD_MASK) as usize])?
+        .to_vec();
+
+    // Bring the rows to the most significant bit and byte first, like the
+    // rows of BDF
+    if table.format & BIT_MASK == 0 {
+        for byte in &mut data {
+            *byte = byte.reverse_bits();
+        }
+    }
+
+    if (table.format & BIT_MASK != 0) != (table.format & BYTE_MASK != 0) {
+        let unit = 1 << ((table.format & SCAN_UNIT_MASK) >> 4);
+
+        for unit in data.chunks_exact_mut(unit) {
+            unit.reverse();
+        }
+    }
+
+    offsets
+        .into_iter()
+        .zip(metrics)
+        .map(|(offset, metrics)| {
+            let invalid = || InvalidBitmapFont::new("invalid glyph bitmap");
+
+            let width =
+                u32::try_from(metrics.right_bearing - metrics.left_bearing)
+                    .map_err(|_| invalid())?;
+            let height = u32::try_from(metrics.ascent + metrics.descent)
+                .map_err(|_| invalid())?;
+            let row = (width as usize).div_ceil(8).next_multiple_of(pad);
+
+            let rows = data
+                .get(offset..offset + row * height as usize)
+                .ok_or_else(invalid)?;
+
+            Ok(Bitmap {
+                left: metrics.left_bearing,
+                top: -metrics.ascent,
+                width,
+                height,
+                advance: metrics.width,
+                pixels: rows
+                    .chunks(row.max(1))
+                    .flat_map(|bits| {
+                        (0..width as usize)
+                            .map(|x| bits[x / 8] & (0x80 >> (x % 8)) != 0)
+                    })
+                    .collect(),
+            })
+        })
+        .collect()
+}
+
+/// Returns the default character and the code point of every encoded
+/// glyph, with the index of its bitmap.
+fn encodings(
+    table: Table<'_>,
+) -> Result<(u32, Vec<(u32, usize)>), InvalidBitmapFont> {
+    let mut reader = table.reader;
+    let min_byte2 = reader.u16()? as u32;
+    let max_byte2 = reader.u16()? as u32;
+    let min_byte1 = reader.u16()? as u32;
+    let max_byte1 = reader.u16()? as u32;
+    let default_char = reader.u16()? as u32;
+
+    let mut encodings = Vec::new();
+
+    for byte1 in min_byte1..=max_byte1 {
+        for byte2 in min_byte2..=max_byte2 {
+            let index = reader.u16()?;
+
+            if index != u16::MAX {
+                encodings.push((byte1 << 8 | byte2, usize::from(index)));
+            }
+        }
+    }
+
+    Ok((default_char, encodings))
+}
+
+/// A table of a PCF font, read in its own byte order.
+struct Table<'a> {
+    format: u32,
+    reader: Reader<'a>,
+}
+
+impl<'a> Table<'a> {
+    fn read(data: &'a [u8], offset: usize) -> Result<Self, InvalidBitmapFont> {
+        // The format of a table is always in little endian
+        let format = Reader::new(data, offset).u32()?;
+
+        Ok(Table {
+            format,
+            reader: Reader {
+                data,
+                position: offset + 4,
+                big_endian: format & BYTE_MASK != 0,
+            },
+        })
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+    big_endian: bool,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8], position: usize) -> Self {
+        Reader {
+            data,
+            position,
+            big_endian: false,
+        }
+    }
+
+    fn bytes(&mut self, length: usize) -> Result<&'a [u8], InvalidBitmapFont> {
+        let bytes = self
+            .data
+            .get(self.position..self.position.saturating_add(length))
+            .ok_or_else(|| InvalidBitmapFont::new("unexpected end of data"))?;
+
+        self.position += length;
+
+        Ok(bytes)
+    }
+
+    fn skip(&mut self, length: usize) -> Result<(), InvalidBitmapFont> {
+        self.bytes(length).map(|_| ())
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], InvalidBitmapFont> {
+        let mut bytes: [u8; N] = self.bytes(N)?.try_into().unwrap_or([0; N]);
+
+        if !self.big_endian {
+            bytes.reverse();
+        }
+
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, InvalidBitmapFont> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, InvalidBitmapFont> {
+        self.array().map(u16::from_be_bytes)
+    }
+
+    fn i16(&mut self) -> Result<i32, InvalidBitmapFont> {
+        self.array()
+            .map(|bytes| i32::from(i16::from_be_bytes(bytes)))
+    }
+
+    fn u32(&mut self) -> Result<u32, InvalidBitmapFont> {
+        self.array().map(u32::from_be_bytes)
+    }
+
+    fn i32(&mut self) -> Result<i32, InvalidBitmapFont> {
+        self.array().map(i32::from_be_bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The settings of a font written by [`write`], with a single glyph:
+    /// an `A` of 3 by 2 pixels.
+    struct Fixture {
+        format: u32,
+        compressed: bool,
+        pixel_size: Option<i32>,
+        ascent: i32,
+    }
+
+    impl Default for Fixture {
+        fn default() -> Self {
+            Fixture {
+                format: 0,
+                compressed: false,
+                pixel_size: Some(8),
+                ascent: 7,
+            }
+        }
+    }
+
+    fn write(fixture: &Fixture) -> Vec<u8> {
+        let format = fixture.format;
+        let int = |value: i64, size: usize| {
+            let bytes = value.to_le_bytes();
+
+            if format & BYTE_MASK != 0 {
+                bytes[..size].iter().rev().copied().collect()
+            } else {
+                bytes[..size].to_vec()
+            }
+        };
+
+        let mut properties = int(1, 4);
+
+        if let Some(pixel_size) = fixture.pixel_size {
+            properties.extend(
+                [int(0, 4), vec![0], int(pixel_size.into(), 4)].concat(),
+            );
+        } else {
+            // A string property, which is not a pixel size
+            properties.extend([int(0, 4), vec![1], int(0, 4)].concat());
+        }
+
+        properties.extend(
+            [vec![0; 3], int(11, 4), b"PIXEL_SIZE\0".to_vec()].concat(),
+        );
+
+        let accelerators =
+            [vec![0; 8], int(fixture.ascent.into(), 4), int(1, 4)].concat();
+
+        // Left and right bearings, advance, ascent and descent
+        let values = [0, 3, 4, 2, 0];
+        let metrics = if fixture.compressed {
+            let mut metrics = int(1, 2);
+            metrics.extend(values.map(|value| (value + 0x80) as u8));
+            metrics
+        } else {
+            let mut metrics = int(1, 4);
+            for value in values.into_iter().chain([0]) {
+                metrics.extend(int(value, 2));
+            }
+            metrics
+        };
+
+        // The rows are padded to bytes
+        let mut rows = [0b1010_0000u8, 0b0100_0000];
+
+        if format & BIT_MASK == 0 {
+            rows = rows.map(u8::reverse_bits);
+        }
+
+        let bitmaps = [
+            int(1, 4),
+            int(0, 4),
+            int(2, 4),
+            int(4, 4),
+            int(8, 4),
+            int(16, 4),
+            rows.to_vec(),
+        ]
+        .concat();
+
+        let encodings = [0x41, 0x41, 0, 0, 0x41, 0]
+            .into_iter()
+            .flat_map(|value| int(value, 2))
+            .collect();
+
+        let metrics_format = if fixture.compressed {
+            format | COMPRESSED_METRICS
+        } else {
+            format
+        };
+        let tables: [(u32, u32, Vec<u8>); 5] = [
+            (PROPERTIES, format, properties),
+            (BDF_ACCELERATORS, format, accelerators),
+            (METRICS, metrics_format, metrics),
+            (BITMAPS, format, bitmaps),
+            (BDF_ENCODINGS, format, encodings),
+        ];
+
+        let mut data = b"\x01fcp".to_vec();
+        let mut body = Vec::new();
+        let start = 8 + 16 * tables.len();
+
+        data.extend(5u32.to_le_bytes());
+
+        for (kind, format, table) in tables {
+            let offset = start + body.len();
+
+            data.extend(kind.to_le_bytes());
+            data.extend(format.to_le_bytes());
+            data.extend((table.len() as u32 + 4).to_le_bytes());
+            data.extend((offset as u32).to_le_bytes());
+            body.extend(format.to_le_bytes());
+            body.extend(table);
+        }
+
+        data.extend(body);
+        data
+    }
+
+    #[test]
+    fn parses_every_byte_and_bit_order() {
+        for format in [0, BIT_MASK, BYTE_MASK, BYTE_MASK | BIT_MASK] {
+            for compressed in [false, true] {
+                let parsed = parse(&write(&Fixture {
+                    format,
+                    compressed,
+                    ..Fixture::default()
+                }))
+                .unwrap();
+
+                assert_eq!(
+                    parsed.strike,
+                    Strike {
+                        pixel_size: 8,
+                        ascent: 7,
+                        descent: 1,
+                    }
+                );
+                assert_eq!(parsed.default_char, Some(0x41));
+                assert_eq!(parsed.glyphs.len(), 1);
+
+                let (code, bitmap) = &parsed.glyphs[0];
+
+                assert_eq!(*code, 0x41);
+                assert_eq!((bitmap.left, bitmap.top), (0, -2));
+                assert_eq!((bitmap.width, bitmap.height), (3, 2));
+                assert_eq!(bitmap.advance, 4);
+                assert_eq!(
+                    bitmap.pixels,
+                    [true, false, true, false, true, false],
+                    "format {format:#x}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn falls_back_to_line_height() {
+        let parsed = parse(&write(&Fixture {
+            pixel_size: None,
+            ..Fixture::default()
+        }))
+        .unwrap();
+
+        assert_eq!(parsed.strike.pixel_size, 8);
+    }
+
+    #[test]
+    fn rejects_malformed_fonts() {
+        let data = write(&Fixture::default());
+        let error = |reason: &str| Err(InvalidBitmapFont::new(reason));
+
+        assert_eq!(
+            parse(b"STARTFONT 2.1").map(|_| ()),
+            error("missing PCF header")
+        );
+        assert_eq!(
+            parse(&data[..data.len() - 1]).map(|_| ()),
+            error("unexpected end of data")
+        );
+
+        // Without the encodings in the table of contents
+        let mut data = data;
+        data[4] = 4;
+
+        assert_eq!(parse(&data).map(|_| ()), error("missing encodings"));
+    }
+
+    #[test]
+    fn rejects_overflowing_metrics() {
+        let data = write(&Fixture {
+            pixel_size: None,
+            ascent: i32::MAX,
+            ..Fixture::default()
+        });
+
+        assert_eq!(
+            parse(&data).map(|_| ()),
+            Err(InvalidBitmapFont::new("metrics are too large"))
+        );
+    }
+}
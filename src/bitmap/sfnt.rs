@@ -0,0 +1,162 @@
+//! Builds a font in the OpenType format with the character map of a bitmap
+//! font, to list its code points through `ab_glyph`.
+
+/// Returns the tables of a font with the given number of glyphs and the
+/// code points of the glyphs following the first one, without metrics or
+/// outlines.
+pub(super) fn character_map(codes: &[u32], glyph_count: u16) -> Vec<u8> {
+    let mut head = Vec::new();
+    write_u32(&mut head, 0x0001_0000);
+    write_u32(&mut head, 0x0001_0000);
+    write_u32(&mut head, 0);
+    write_u32(&mut head, 0x5F0F_3CF5);
+    write_u16(&mut head, 0);
+    // The smallest size of the em square
+    write_u16(&mut head, 16);
+    head.extend([0; 34]);
+
+    let mut hhea = Vec::new();
+    write_u32(&mut hhea, 0x0001_0000);
+    hhea.extend([0; 14]);
+    // A vertical caret
+    write_u16(&mut hhea, 1);
+    hhea.extend([0; 16]);
+
+    let mut maxp = Vec::new();
+    write_u32(&mut maxp, 0x0000_5000);
+    write_u16(&mut maxp, glyph_count);
+
+    vec![
+        (*b"cmap", cmap(codes)),
+        (*b"head", head),
+        (*b"hhea", hhea),
+        (*b"maxp", maxp),
+    ]
+    .into_iter()
+    .fold(Directory::new(4), |directory, (tag, table)| {
+        directory.with(tag, table)
+    })
+    .finish()
+}
+
+/// A `cmap` table with a single format 12 subtable for Unicode, mapping
+/// the given code points to the glyphs following the first one.
+fn cmap(codes: &[u32]) -> Vec<u8> {
+    let mut groups: Vec<(u32, u32, u32)> = Vec::new();
+
+    for (index, code) in codes.iter().enumerate() {
+        let glyph = index as u32 + 1;
+
+        match groups.last_mut() {
+            Some((_, end, _)) if *end + 1 == *code => *end = *code,
+            _ => groups.push((*code, *code, glyph)),
+        }
+    }
+
+    let mut cmap = Vec::new();
+    write_u16(&mut cmap, 0);
+    write_u16(&mut cmap, 1);
+    write_u16(&mut cmap, 3);
+    write_u16(&mut cmap, 10);
+    write_u32(&mut cmap, 12);
+    write_u16(&mut cmap, 12);
+    write_u16(&mut cmap, 0);
+    write_u32(&mut cmap, 16 + 12 * groups.len() as u32);
+    write_u32(&mut cmap, 0);
+    write_u32(&mut cmap, groups.len() as u32);
+
+    for (start, end, start_glyph) in groups {
+        write_u32(&mut cmap, start);
+        write_u32(&mut cmap, end);
+        write_u32(&mut cmap, start_glyph);
+    }
+
+    cmap
+}
+
+/// The table directory of a font, followed by its tables.
+struct Directory {
+    header: Vec<u8>,
+    tables: Vec<u8>,
+    offset: usize,
+}
+
+impl Directory {
+    fn new(tables: u16) -> Self {
+        let entry_selector = 15 - tables.leading_zeros() as u16;
+        let search_range = 16 << entry_selector;
+
+        let mut header = Vec::new();
+        write_u32(&mut header, 0x0001_0000);
+        write_u16(&mut header, tables);
+        write_u16(&mut header, search_range);
+        write_u16(&mut header, entry_selector);
+        write_u16(&mut header, tables * 16 - search_range);
+
+        Directory {
+            header,
+            tables: Vec::new(),
+            offset: 12 + 16 * usize::from(tables),
+        }
+    }
+
+    /// Adds a table. Tables must be added in the order of their tags.
+    fn with(mut self, tag: [u8; 4], mut table: Vec<u8>) -> Self {
+        self.header.extend(tag);
+        write_u32(&mut self.header, 0);
+        write_u32(&mut self.header, (self.offset + self.tables.len()) as u32);
+        write_u32(&mut self.header, table.len() as u32);
+
+        table.resize(table.len().next_multiple_of(4), 0);
+        self.tables.extend(table);
+        self
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        self.header.extend(self.tables);
+        self.header
+    }
+}
+
+fn write_u16(data: &mut Vec<u8>, value: u16) {
+    data.extend(value.to_be_bytes());
+}
+
+fn write_u32(data: &mut Vec<u8>, value: u32) {
+    data.extend(value.to_be_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ttf_parser::{Face, GlyphId};
+
+    #[test]
+    fn code_points_are_grouped_in_ranges() {
+        let codes = ['a', 'b', 'c', 'x', '\u{1F600}'].map(u32::from);
+
+        let data = character_map(&codes, 6);
+        let face = Face::parse(&data, 0).unwrap();
+
+        assert_eq!(face.number_of_glyphs(), 6);
+
+        for (index, code) in codes.into_iter().enumerate() {
+            let c = char::from_u32(code).unwrap();
+
+            assert_eq!(face.glyph_index(c), Some(GlyphId(index as u16 + 1)));
+        }
+
+        assert_eq!(face.glyph_index('d'), None);
+        assert_eq!(face.glyph_index('w'), None);
+    }
+
+    #[test]
+    fn fonts_without_characters_are_still_valid() {
+        let data = character_map(&[], 1);
+        let face = Face::parse(&data, 0).unwrap();
+
+        assert_eq!(face.number_of_glyphs(), 1);
+        assert_eq!(face.glyph_index('a'), None);
+    }
+}
@@ -0,0 +1,600 @@
+mod binary;
+mod text;
+
+use crate::bitmap::{InvalidBitmapFont, Metrics, Strike};
+use crate::extra::{GlyphExtra, GlyphSection};
+use crate::inline::{ImageId, Images};
+use crate::pipeline::Quad;
+use crate::{FontId, SectionGlyph};
+
+use glyph_brush::ab_glyph::{
+    CodepointIdIter, Font, FontArc, Glyph, GlyphId, GlyphSvg, Outline, Rect,
+    point, v2,
+};
+
+use std::collections::HashMap;
+use std::fmt;
+
+/// A font of pre-rendered glyphs laid out in the pages of a texture, loaded
+/// from a descriptor of the AngelCode BMFont tool.
+///
+/// Both the text and the binary descriptor formats are supported. The
+/// pages are not read, since they are separate image files: add the font
+/// to a brush with
+/// [`GlyphBrush::add_bm_font`](struct.GlyphBrush.html#method.add_bm_font)
+/// together with the RGBA pixels of its [`pages`](#method.pages). Its
+/// glyphs are then drawn by `queue` straight from the pages, and laid out
+/// with the advances and kerning pairs of the descriptor, like the glyphs
+/// of any other font.
+///
+/// The glyphs are drawn as is, unless the font is
+/// [`tinted`](#method.tinted). They match the pixels of the pages at the
+/// [`scale`](struct.Strike.html#method.scale) of the
+/// [`strike`](#method.strike) of the font, when placed at whole pixels and
+/// sampled with `wgpu::FilterMode::Nearest`.
+///
+/// ```
+/// use wgpu_glyph::{BmFont, GlyphBrush, InvalidBitmapFont, Section, Text};
+/// use wgpu_glyph::ab_glyph::Font;
+///
+/// const DESCRIPTOR: &str = r#"
+/// info face="Tiny" size=8 unicode=1 padding=0,0,0,0 spacing=1,1
+/// common lineHeight=10 base=8 scaleW=64 scaleH=64 pages=1 packed=0
+/// page id=0 file="tiny_0.png"
+/// chars count=2
+/// char id=65 x=0 y=0 width=6 height=8 xoffset=0 yoffset=0 xadvance=7 page=0 chnl=15
+/// char id=86 x=7 y=0 width=6 height=8 xoffset=0 yoffset=0 xadvance=7 page=0 chnl=15
+/// kernings count=1
+/// kerning first=65 second=86 amount=-1
+/// "#;
+///
+/// let font = BmFont::from_text(DESCRIPTOR)?;
+///
+/// assert_eq!(font.pages(), ["tiny_0.png"]);
+/// assert_eq!(font.advance(font.glyph_id('A')), Some(7));
+///
+/// fn title(
+///     brush: &mut GlyphBrush<()>,
+///     page: &[u8],
+/// ) -> Result<(), InvalidBitmapFont> {
+///     let font = BmFont::from_text(DESCRIPTOR)?.tinted();
+///     let scale = font.strike().scale();
+///     let font_id = brush.add_bm_font(font, &[page])?;
+///
+///     brush.queue(
+///         Section::default().add_text(
+///             Text::new("AVA")
+///                 .with_font_id(font_id)
+///                 .with_scale(scale)
+///                 .with_color([1.0, 0.8, 0.2, 1.0]),
+///         ),
+///     );
+///
+///     Ok(())
+/// }
+/// # Ok::<(), InvalidBitmapFont>(())
+/// ```
+pub struct BmFont {
+    page_size: (u32, u32),
+    pages: Vec<String>,
+    /// The character of every glyph, the first one being drawn for missing
+    /// characters.
+    chars: Vec<Char>,
+    /// The kerning pairs of glyphs, sorted, with their amount in pixels.
+    kernings: Vec<((u16, u16), i32)>,
+    tinted: bool,
+    metrics: Metrics,
+}
+
+/// The contents of a BMFont descriptor.
+#[derive(Debug, Default)]
+struct Descriptor {
+    size: i32,
+    line_height: i32,
+    base: i32,
+    page_size: (u32, u32),
+    packed: bool,
+    pages: Vec<String>,
+    /// Every character with its code point, where `u32::MAX` marks the
+    /// character drawn for missing ones.
+    chars: Vec<(u32, Char)>,
+    /// The kerning pairs of code points, with their amount in pixels.
+    kernings: Vec<(u32, u32, i32)>,
+}
+
+/// A character of a BMFont.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Char {
+    /// The left edge of the character in its page, in pixels.
+    x: u32,
+    /// The top edge of the character in its page, in pixels.
+    y: u32,
+    width: u32,
+    height: u32,
+    /// The offset of the left edge of the character from the origin.
+    x_offset: i32,
+    /// The offset of the top edge of the character from the top of the
+    /// line.
+    y_offset: i32,
+    /// The horizontal distance to the origin of the next character.
+    x_advance: i32,
+    page: u32,
+}
+
+impl BmFont {
+    /// Parses a BMFont descriptor in the text format.
+    ///
+    /// Character IDs are taken as Unicode code points, which holds for
+    /// fonts exported with the `unicode` option.
+    pub fn from_text(descriptor: &str) -> Result<Self, InvalidBitmapFont> {
+        Self::from_descriptor(text::parse(descriptor)?)
+    }
+
+    /// Parses a BMFont descriptor in the binary format, version 3.
+    ///
+    /// Character IDs are taken as Unicode code points, like in
+    /// [`from_text`](#method.from_text).
+    pub fn from_binary(data: &[u8]) -> Result<Self, InvalidBitmapFont> {
+        Self::from_descriptor(binary::parse(data)?)
+    }
+
+    /// Multiplies the glyphs by the color of their text, like the glyphs of
+    /// other fonts. Use it for pages of white glyphs.
+    pub fn tinted(mut self) -> Self {
+        self.tinted = true;
+        self
+    }
+
+    /// Returns the strike of the font, with the height of its lines as
+    /// ascent and descent.
+    pub fn strike(&self) -> Strike {
+        self.metrics.strike()
+    }
+
+    /// Returns the file names of the pages of the font, in the order of
+    /// their IDs.
+    pub fn pages(&self) -> &[String] {
+        &self.pages
+    }
+
+    /// Returns the width and height of the pages of the font, in pixels.
+    pub fn page_size(&self) -> (u32, u32) {
+        self.page_size
+    }
+
+    /// Returns the advance of the given glyph at the strike of the font, in
+    /// pixels.
+    pub fn advance(&self, id: GlyphId) -> Option<i32> {
+        self.chars.get(usize::from(id.0)).map(|char| char.x_advance)
+    }
+
+    fn from_descriptor(
+        descriptor: Descriptor,
+    ) -> Result<Self, InvalidBitmapFont> {
+        let Descriptor {
+            size,
+            line_height,
+            base,
+            page_size,
+            packed,
+            pages,
+            mut chars,
+            kernings,
+        } = descriptor;
+
+        if packed {
+            return Err(InvalidBitmapFont::new(
+                "characters packed in channels are not supported",
+            ));
+        }
+
+        if page_size.0 == 0 || page_size.1 == 0 {
+            return Err(InvalidBitmapFont::new("empty pages"));
+        }
+
+        if line_height <= 0 {
+            return Err(InvalidBitmapFont::new("empty line height"));
+        }
+
+        // The base is the ascent of the lines, and what is left of them is
+        // their descent
+        if !(0..=line_height).contains(&base) {
+            return Err(InvalidBitmapFont::new("base outside of the lines"));
+        }
+
+        let fits = |start: u32, length: u32, end: u32| {
+            start.checked_add(length).is_some_and(|last| last <= end)
+        };
+
+        if let Some((code, _)) = chars.iter().find(|(_, char)| {
+            !fits(char.x, char.width, page_size.0)
+                || !fits(char.y, char.height, page_size.1)
+        }) {
+            return Err(InvalidBitmapFont::new(format!(
+                "character {code} outside of its page"
+            )));
+        }
+
+        chars.sort_by_key(|(code, _)| *code);
+        chars.dedup_by_key(|(code, _)| *code);
+
+        // The invalid character is drawn for missing characters
+        let notdef = match chars.last() {
+            Some((u32::MAX, _)) => chars.pop().map(|(_, char)| char),
+            _ => None,
+        }
+        .unwrap_or_default();
+
+        let codes: Vec<u32> = chars.iter().map(|(code, _)| *code).collect();
+        let chars: Vec<Char> = std::iter::once(notdef)
+            .chain(chars.into_iter().map(|(_, char)| char))
+            .collect();
+
+        let strike = Strike {
+            pixel_size: match size.unsigned_abs() {
+                0 => line_height,
+                size => size as i32,
+            }
+            .clamp(0, i32::from(u16::MAX)) as u16,
+            ascent: base,
+            descent: line_height - base,
+        };
+
+        let advances: Vec<i32> =
+            chars.iter().map(|char| char.x_advance).collect();
+        let glyph = |code: u32| {
+            codes
+                .binary_search(&code)
+                .ok()
+                .map(|index| index as u16 + 1)
+        };
+
+        let mut kernings: Vec<((u16, u16), i32)> = kernings
+            .into_iter()
+            .filter_map(|(first, second, amount)| {
+                Some(((glyph(first)?, glyph(second)?), amount))
+            })
+            .collect();
+
+        kernings.sort_by_key(|(pair, _)| *pair);
+        kernings.dedup_by_key(|(pair, _)| *pair);
+
+        Ok(BmFont {
+            metrics: Metrics::new(strike, advances, codes)?,
+            page_size,
+            pages,
+            chars,
+            kernings,
+            tinted: false,
+        })
+    }
+}
+
+impl fmt::Debug for BmFont {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BmFont")
+            .field("strike", &self.strike())
+            .field("pages", &self.pages)
+            .field("glyphs", &self.chars.len())
+            .field("tinted", &self.tinted)
+            .finish()
+    }
+}
+
+/// The metrics are read from the descriptor, and the glyphs have no
+/// outlines, so the draw cache has nothing to rasterize and the glyphs are
+/// drawn from the pages instead.
+impl Font for BmFont {
+    #[inline]
+    fn units_per_em(&self) -> Option<f32> {
+        Some(self.metrics.units_per_em())
+    }
+
+    #[inline]
+    fn ascent_unscaled(&self) -> f32 {
+        self.metrics.ascent()
+    }
+
+    #[inline]
+    fn descent_unscaled(&self) -> f32 {
+        self.metrics.descent()
+    }
+
+    #[inline]
+    fn line_gap_unscaled(&self) -> f32 {
+        0.0
+    }
+
+    #[inline]
+    fn italic_angle(&self) -> f32 {
+        0.0
+    }
+
+    #[inline]
+    fn glyph_id(&self, c: char) -> GlyphId {
+        self.metrics.glyph_id(c)
+    }
+
+    #[inline]
+    fn h_advance_unscaled(&self, id: GlyphId) -> f32 {
+        self.metrics.h_advance(id)
+    }
+
+    #[inline]
+    fn h_side_bearing_unscaled(&self, _id: GlyphId) -> f32 {
+        0.0
+    }
+
+    #[inline]
+    fn v_advance_unscaled(&self, _id: GlyphId) -> f32 {
+        0.0
+    }
+
+    #[inline]
+    fn v_side_bearing_unscaled(&self, _id: GlyphId) -> f32 {
+        0.0
+    }
+
+    fn kern_unscaled(&self, first: GlyphId, second: GlyphId) -> f32 {
+        self.kernings
+            .binary_search_by_key(&(first.0, second.0), |(pair, _)| *pair)
+            .map_or(0.0, |index| self.kernings[index].1 as f32)
+    }
+
+    #[inline]
+    fn outline(&self, _id: GlyphId) -> Option<Outline> {
+        None
+    }
+
+    #[inline]
+    fn glyph_count(&self) -> usize {
+        self.metrics.glyph_count()
+    }
+
+    #[inline]
+    fn codepoint_ids(&self) -> CodepointIdIter<'_> {
+        self.metrics.codepoint_ids()
+    }
+
+    #[inline]
+    fn glyph_raster_image2(
+        &self,
+        _id: GlyphId,
+        _pixel_size: u16,
+    ) -> Option<v2::GlyphImage<'_>> {
+        None
+    }
+
+    #[inline]
+    fn glyph_svg_image(&self, _id: GlyphId) -> Option<GlyphSvg<'_>> {
+        None
+    }
+
+    #[inline]
+    fn font_data(&self) -> &[u8] {
+        &[]
+    }
+}
+
+impl From<BmFont> for FontArc {
+    fn from(font: BmFont) -> Self {
+        FontArc::new(font)
+    }
+}
+
+/// The BMFonts added to a brush, by font.
+#[derive(Debug, Default)]
+pub(crate) struct BmFonts(HashMap<FontId, Atlas>);
+
+/// The characters of a BMFont and the images of its pages.
+#[derive(Debug)]
+pub(crate) struct Atlas {
+    line_height: i32,
+    base: i32,
+    page_size: (u32, u32),
+    chars: Vec<Char>,
+    pages: Vec<ImageId>,
+    tinted: bool,
+}
+
+impl BmFonts {
+    /// Keeps the atlas of the given font.
+    pub(crate) fn insert(&mut self, font_id: FontId, atlas: Atlas) {
+        let _ = self.0.insert(font_id, atlas);
+    }
+
+    /// Returns whether any `Text` of the section uses a BMFont.
+    pub(crate) fn is_used(&self, section: &GlyphSection<'_>) -> bool {
+        !self.0.is_empty()
+            && section
+                .text
+                .iter()
+                .any(|text| self.0.contains_key(&text.font_id))
+    }
+
+    /// Builds the quads of the glyphs of a section drawn with a BMFont,
+    /// clipped to `bounds`.
+    pub(crate) fn quads(
+        &self,
+        images: &Images,
+        section: &GlyphSection<'_>,
+        glyphs: &[SectionGlyph],
+        bounds: Rect,
+        out: &mut Vec<Quad>,
+    ) {
+        for sg in glyphs {
+            let Some(atlas) = self.0.get(&sg.font_id) else {
+                continue;
+            };
+
+            let extra = &section.text[sg.section_index].extra;
+
+            out.extend(atlas.quad(images, &sg.glyph, extra, bounds));
+        }
+    }
+}
+
+impl Atlas {
+    /// Creates the atlas of a font with the images of its pages.
+    pub(crate) fn new(font: &BmFont, pages: Vec<ImageId>) -> Self {
+        Atlas {
+            line_height: font.strike().ascent + font.strike().descent,
+            base: font.strike().ascent,
+            page_size: font.page_size,
+            chars: font.chars.clone(),
+            pages,
+            tinted: font.tinted,
+        }
+    }
+
+    fn quad(
+        &self,
+        images: &Images,
+        glyph: &Glyph,
+        extra: &GlyphExtra,
+        bounds: Rect,
+    ) -> Option<Quad> {
+        let char = self.chars.get(usize::from(glyph.id.0))?;
+        let page = images.get(*self.pages.get(char.page as usize)?)?;
+
+        if char.width == 0 || char.height == 0 {
+            return None;
+        }
+
+        // The scale of the glyph is the height of its line
+        let scale_x = glyph.scale.x / self.line_height as f32;
+        let scale_y = glyph.scale.y / self.line_height as f32;
+
+        let min = point(
+            glyph.position.x + char.x_offset as f32 * scale_x,
+            glyph.position.y
+                + (char.y_offset as f32 - self.base as f32) * scale_y,
+        );
+
+        let rect = Rect {
+            min,
+            max: point(
+                min.x + char.width as f32 * scale_x,
+                min.y + char.height as f32 * scale_y,
+            ),
+        };
+
+        let (page_width, page_height) = self.page_size;
+        let tex_coords = |x: u32, y: u32| {
+            point(
+                page.min.x + page.width() * x as f32 / page_width as f32,
+                page.min.y + page.height() * y as f32 / page_height as f32,
+            )
+        };
+
+        let color = if self.tinted { extra.color } else { [1.0; 4] };
+
+        let instance = Quad::image(
+            rect,
+            Rect {
+                min: tex_coords(char.x, char.y),
+                max: tex_coords(char.x + char.width, char.y + char.height),
+            },
+            bounds,
+            extra.z,
+            color,
+        )?
+        .transformed(extra);
+
+        Some(if self.tinted {
+            instance.paletted(extra)
+        } else {
+            instance
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parses a text descriptor with the given common block and a 4 by 6
+    /// pixel `A`.
+    fn font(common: &str) -> Result<BmFont, InvalidBitmapFont> {
+        BmFont::from_text(&format!(
+            "common {common}\n\
+             char id=65 x=60 y=0 width=4 height=6 xoffset=0 yoffset=1 \
+             xadvance=5 page=0\n"
+        ))
+    }
+
+    #[test]
+    fn takes_metrics_from_common_block() {
+        let font = font("lineHeight=10 base=8 scaleW=64 scaleH=64").unwrap();
+
+        assert_eq!(
+            font.strike(),
+            Strike {
+                pixel_size: 10,
+                ascent: 8,
+                descent: 2,
+            }
+        );
+        assert_eq!(font.page_size(), (64, 64));
+        assert_eq!(font.advance(font.glyph_id('A')), Some(5));
+        assert_eq!(font.advance(font.glyph_id('B')), Some(0));
+    }
+
+    #[test]
+    fn draws_invalid_character_for_missing_ones() {
+        let font = BmFont::from_text(
+            "common lineHeight=10 base=8 scaleW=64 scaleH=64\n\
+             char id=65 x=0 y=0 width=4 height=6 xoffset=0 yoffset=1 \
+             xadvance=5 page=0\n\
+             char id=-1 x=8 y=0 width=4 height=6 xoffset=0 yoffset=1 \
+             xadvance=6 page=0\n\
+             kerning first=65 second=65 amount=-2\n\
+             kerning first=65 second=66 amount=-1\n",
+        )
+        .unwrap();
+        let a = font.glyph_id('A');
+
+        assert_eq!(font.glyph_id('B'), GlyphId(0));
+        assert_eq!(font.advance(GlyphId(0)), Some(6));
+        assert_eq!(font.kern_unscaled(a, a), -2.0);
+
+        // Pairs of missing characters are dropped
+        assert_eq!(font.kern_unscaled(a, GlyphId(0)), 0.0);
+    }
+
+    #[test]
+    fn rejects_invalid_metrics() {
+        let error = |common: &str| font(common).map(|_| ()).unwrap_err();
+
+        assert_eq!(
+            error("lineHeight=10 base=11 scaleW=64 scaleH=64"),
+            InvalidBitmapFont::new("base outside of the lines")
+        );
+        assert_eq!(
+            error("lineHeight=10 base=-1 scaleW=64 scaleH=64"),
+            InvalidBitmapFont::new("base outside of the lines")
+        );
+        assert_eq!(
+            error("lineHeight=0 base=0 scaleW=64 scaleH=64"),
+            InvalidBitmapFont::new("empty line height")
+        );
+        assert_eq!(
+            error("lineHeight=-2147483648 base=2147483647 scaleW=64 scaleH=64"),
+            InvalidBitmapFont::new("empty line height")
+        );
+        assert_eq!(
+            error("lineHeight=10 base=8 scaleW=0 scaleH=64"),
+            InvalidBitmapFont::new("empty pages")
+        );
+        assert_eq!(
+            error("lineHeight=10 base=8 scaleW=62 scaleH=64"),
+            InvalidBitmapFont::new("character 65 outside of its page")
+        );
+        assert_eq!(
+            error("lineHeight=10 base=8 scaleW=64 scaleH=64 packed=1"),
+            InvalidBitmapFont::new(
+                "characters packed in channels are not supported"
+            )
+        );
+    }
+}
@@ -0,0 +1,234 @@
+//! Parses BMFont descriptors in the binary format, a header followed by
+//! blocks of little endian fields.
+
+use super::{Char, Descriptor};
+use crate::bitmap::InvalidBitmapFont;
+
+const INFO: u8 = 1;
+const COMMON: u8 = 2;
+const PAGES: u8 = 3;
+const CHARS: u8 = 4;
+const KERNING_PAIRS: u8 = 5;
+
+/// The bit of the common block marking characters packed in channels.
+const PACKED: u8 = 1 << 7;
+
+pub(super) fn parse(data: &[u8]) -> Result<Descriptor, InvalidBitmapFont> {
+    let Some(version) = data.strip_prefix(b"BMF").and_then(|rest| rest.first())
+    else {
+        return Err(InvalidBitmapFont::new("missing BMF header"));
+    };
+
+    if *version != 3 {
+        return Err(InvalidBitmapFont::new(format!(
+            "unsupported version {version}"
+        )));
+    }
+
+    let mut descriptor = Descriptor::default();
+    let mut has_common = false;
+    let mut rest = &data[4..];
+
+    while let Some((kind, after)) = rest.split_first() {
+        let size = u32::from_le_bytes(read(after, 0)?) as usize;
+        let block = after
+            .get(4..4usize.saturating_add(size))
+            .ok_or_else(|| InvalidBitmapFont::new("truncated block"))?;
+
+        match *kind {
+            INFO => descriptor.size = i16(block, 0)?,
+            COMMON => {
+                descriptor.line_height = u16(block, 0)? as i32;
+                descriptor.base = u16(block, 2)? as i32;
+                descriptor.page_size = (u16(block, 4)?, u16(block, 6)?);
+                descriptor.packed = read::<1>(block, 10)?[0] & PACKED != 0;
+                has_common = true;
+            }
+            PAGES => {
+                // Null-terminated file names
+                descriptor.pages = block
+                    .split(|byte| *byte == 0)
+                    .filter(|name| !name.is_empty())
+                    .map(|name| String::from_utf8_lossy(name).into_owned())
+                    .collect();
+            }
+            CHARS => {
+                for char in block.chunks_exact(20) {
+                    descriptor.chars.push((
+                        u32::from_le_bytes(read(char, 0)?),
+                        Char {
+                            x: u16(char, 4)?,
+                            y: u16(char, 6)?,
+                            width: u16(char, 8)?,
+                            height: u16(char, 10)?,
+                            x_offset: i16(char, 12)?,
+                            y_offset: i16(char, 14)?,
+                            x_advance: i16(char, 16)?,
+                            page: u32::from(char[18]),
+                        },
+                    ));
+                }
+            }
+            KERNING_PAIRS => {
+                for pair in block.chunks_exact(10) {
+                    descriptor.kernings.push((
+                        u32::from_le_bytes(read(pair, 0)?),
+                        u32::from_le_bytes(read(pair, 4)?),
+                        i16(pair, 8)?,
+                    ));
+                }
+            }
+            _ => {}
+        }
+
+        rest = &after[4 + size..];
+    }
+
+    if !has_common {
+        return Err(InvalidBitmapFont::new("missing common block"));
+    }
+
+    Ok(descriptor)
+}
+
+fn read<const N: usize>(
+    block: &[u8],
+    offset: usize,
+) -> Result<[u8; N], InvalidBitmapFont> {
+    block
+        .get(offset..offset + N)
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| InvalidBitmapFont::new("truncated block"))
+}
+
+fn u16(block: &[u8], offset: usize) -> Result<u32, InvalidBitmapFont> {
+    read(block, offset).map(|bytes| u32::from(u16::from_le_bytes(bytes)))
+}
+
+fn i16(block: &[u8], offset: usize) -> Result<i32, InvalidBitmapFont> {
+    read(block, offset).map(|bytes| i32::from(i16::from_le_bytes(bytes)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Writes a version 3 descriptor made of the given blocks.
+    fn descriptor(blocks: &[(u8, Vec<u8>)]) -> Vec<u8> {
+        let mut data = b"BMF\x03".to_vec();
+
+        for (kind, block) in blocks {
+            data.push(*kind);
+            data.extend((block.len() as u32).to_le_bytes());
+            data.extend(block);
+        }
+
+        data
+    }
+
+    /// A common block of 12 pixel high lines, in one page of 256 by 128
+    /// pixels.
+    fn common(flags: u8) -> (u8, Vec<u8>) {
+        let fields: [u16; 5] = [12, 10, 256, 128, 1];
+        let mut block: Vec<u8> = fields
+            .iter()
+            .flat_map(|field| field.to_le_bytes())
+            .collect();
+
+        block.extend([flags, 0, 0, 0, 0]);
+
+        (COMMON, block)
+    }
+
+    #[test]
+    fn parses_blocks() {
+        let mut info = (-16i16).to_le_bytes().to_vec();
+        info.extend(b"\0\0\0\0\0\0\0\0\0\0\0\0Tiny\0");
+
+        let mut char = 66u32.to_le_bytes().to_vec();
+        for field in [1u16, 2, 6, 9] {
+            char.extend(field.to_le_bytes());
+        }
+        for field in [-1i16, 3, 7] {
+            char.extend(field.to_le_bytes());
+        }
+        char.extend([2, 15]);
+
+        let mut kerning = 65u32.to_le_bytes().to_vec();
+        kerning.extend(66u32.to_le_bytes());
+        kerning.extend((-3i16).to_le_bytes());
+
+        let data = descriptor(&[
+            (INFO, info),
+            common(0),
+            (PAGES, b"a_0.png\0a_1.png\0a_2.png\0".to_vec()),
+            (CHARS, char),
+            (KERNING_PAIRS, kerning),
+            // Unknown blocks are skipped
+            (9, vec![1, 2, 3]),
+        ]);
+        let descriptor = parse(&data).unwrap();
+
+        assert_eq!(descriptor.size, -16);
+        assert_eq!((descriptor.line_height, descriptor.base), (12, 10));
+        assert_eq!(descriptor.page_size, (256, 128));
+        assert!(!descriptor.packed);
+        assert_eq!(descriptor.pages, ["a_0.png", "a_1.png", "a_2.png"]);
+        assert_eq!(
+            descriptor.chars,
+            [(
+                66,
+                Char {
+                    x: 1,
+                    y: 2,
+                    width: 6,
+                    height: 9,
+                    x_offset: -1,
+                    y_offset: 3,
+                    x_advance: 7,
+                    page: 2,
+                }
+            )]
+        );
+        assert_eq!(descriptor.kernings, [(65, 66, -3)]);
+    }
+
+    #[test]
+    fn reads_packed_flag() {
+        assert!(parse(&descriptor(&[common(PACKED)])).unwrap().packed);
+        assert!(!parse(&descriptor(&[common(1)])).unwrap().packed);
+    }
+
+    #[test]
+    fn rejects_malformed_descriptors() {
+        let error = |data: &[u8]| parse(data).map(|_| ()).unwrap_err();
+
+        assert_eq!(
+            error(b"info"),
+            InvalidBitmapFont::new("missing BMF header")
+        );
+        assert_eq!(
+            error(b"BMF\x02"),
+            InvalidBitmapFont::new("unsupported version 2")
+        );
+        assert_eq!(
+            error(&descriptor(&[(INFO, vec![0; 14])])),
+            InvalidBitmapFont::new("missing common block")
+        );
+
+        let data = descriptor(&[common(0)]);
+
+        assert_eq!(
+            error(&data[..data.len() - 1]),
+            InvalidBitmapFont::new("truncated block")
+        );
+        assert_eq!(
+            error(&data[..6]),
+            InvalidBitmapFont::new("truncated block")
+        );
+        assert_eq!(
+            error(&descriptor(&[(COMMON, vec![0; 10])])),
+            InvalidBitmapFont::new("truncated block")
+        );
+    }
+}
@@ -0,0 +1,242 @@
+//! Parses BMFont descriptors in the text format, a line of `key=value`
+//! attributes after a tag for every block and character.
+
+use super::{Char, Descriptor};
+use crate::bitmap::InvalidBitmapFont;
+
+pub(super) fn parse(text: &str) -> Result<Descriptor, InvalidBitmapFont> {
+    let mut descriptor = Descriptor::default();
+    let mut has_common = false;
+
+    for (index, content) in text.lines().enumerate() {
+        let line = index + 1;
+        let (tag, attributes) = tokenize(content).ok_or_else(|| {
+            InvalidBitmapFont::new(format!("unclosed quote on line {line}"))
+        })?;
+
+        let value = |key: &str| -> Result<i64, InvalidBitmapFont> {
+            attributes
+                .iter()
+                .find(|(name, _)| *name == key)
+                .and_then(|(_, value)| value.parse().ok())
+                .ok_or_else(|| {
+                    InvalidBitmapFont::new(format!(
+                        "invalid {key} of {tag} on line {line}"
+                    ))
+                })
+        };
+        let int = |key: &str| -> Result<i32, InvalidBitmapFont> {
+            value(key).and_then(|value| {
+                i32::try_from(value).map_err(|_| {
+                    InvalidBitmapFont::new(format!(
+                        "out of range {key} of {tag} on line {line}"
+                    ))
+                })
+            })
+        };
+        let unsigned = |key: &str| -> Result<u32, InvalidBitmapFont> {
+            value(key).and_then(|value| {
+                u32::try_from(value).map_err(|_| {
+                    InvalidBitmapFont::new(format!(
+                        "negative {key} of {tag} on line {line}"
+                    ))
+                })
+            })
+        };
+
+        match tag {
+            "info" => descriptor.size = int("size")?,
+            "common" => {
+                descriptor.line_height = int("lineHeight")?;
+                descriptor.base = int("base")?;
+                descriptor.page_size =
+                    (unsigned("scaleW")?, unsigned("scaleH")?);
+                descriptor.packed = value("packed").unwrap_or(0) != 0;
+                has_common = true;
+            }
+            "page" => {
+                // Like in the binary format, a character has at most 256
+                // pages to pick from
+                let id = usize::from(u8::try_from(unsigned("id")?).map_err(
+                    |_| {
+                        InvalidBitmapFont::new(format!(
+                            "out of range id of page on line {line}"
+                        ))
+                    },
+                )?);
+                let file = attributes
+                    .iter()
+                    .find(|(name, _)| *name == "file")
+                    .map_or("", |(_, file)| file);
+
+                if descriptor.pages.len() <= id {
+                    descriptor.pages.resize(id + 1, String::new());
+                }
+
+                descriptor.pages[id] = file.to_owned();
+            }
+            "char" => {
+                // The invalid character has an ID of -1
+                let code = match value("id")? {
+                    -1 => u32::MAX,
+                    code => u32::try_from(code).map_err(|_| {
+                        InvalidBitmapFont::new(format!(
+                            "invalid id of char on line {line}"
+                        ))
+                    })?,
+                };
+
+                descriptor.chars.push((
+                    code,
+                    Char {
+                        x: unsigned("x")?,
+                        y: unsigned("y")?,
+                        width: unsigned("width")?,
+                        height: unsigned("height")?,
+                        x_offset: int("xoffset")?,
+                        y_offset: int("yoffset")?,
+                        x_advance: int("xadvance")?,
+                        page: unsigned("page")?,
+                    },
+                ));
+            }
+            "kerning" => descriptor.kernings.push((
+                unsigned("first")?,
+                unsigned("second")?,
+                int("amount")?,
+            )),
+            _ => {}
+        }
+    }
+
+    if !has_common {
+        return Err(InvalidBitmapFont::new("missing common block"));
+    }
+
+    Ok(descriptor)
+}
+
+/// Splits a line into its tag and its attributes, with the quotes of the
+/// values removed.
+///
+/// Returns `None` if a quote is not closed.
+fn tokenize(line: &str) -> Option<(&str, Vec<(&str, &str)>)> {
+    let line = line.trim_start();
+    let (tag, mut rest) =
+        line.split_once(char::is_whitespace).unwrap_or((line, ""));
+    let mut attributes = Vec::new();
+
+    loop {
+        rest = rest.trim_start();
+
+        let Some((key, after)) = rest.split_once('=') else {
+            break;
+        };
+
+        let (value, after) = match after.strip_prefix('"') {
+            Some(quoted) => quoted.split_once('"')?,
+            None => {
+                after.split_once(char::is_whitespace).unwrap_or((after, ""))
+            }
+        };
+
+        attributes.push((key.trim(), value));
+        rest = after;
+    }
+
+    Some((tag, attributes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_blocks() {
+        let descriptor = parse(concat!(
+            "info face=\"Tiny Sans\" size=-12 bold=0\n",
+            "common lineHeight=14 base=11 scaleW=128 scaleH=64 packed=0\n",
+            "page id=1 file=\"tiny 1.png\"\n",
+            "page id=0 file=tiny_0.png\n",
+            "chars count=2\n",
+            "char id=-1 x=0 y=0 width=4 height=9 xoffset=1 yoffset=2 ",
+            "xadvance=6 page=0 chnl=15\n",
+            "char id=65 x=5 y=0 width=7 height=9 xoffset=-1 yoffset=2 ",
+            "xadvance=7 page=1 chnl=15\n",
+            "kerning first=65 second=86 amount=-2\n",
+        ))
+        .unwrap();
+
+        assert_eq!(descriptor.size, -12);
+        assert_eq!((descriptor.line_height, descriptor.base), (14, 11));
+        assert_eq!(descriptor.page_size, (128, 64));
+        assert!(!descriptor.packed);
+        assert_eq!(descriptor.pages, ["tiny_0.png", "tiny 1.png"]);
+        assert_eq!(descriptor.chars.len(), 2);
+        assert_eq!(descriptor.chars[0].0, u32::MAX);
+        assert_eq!(
+            descriptor.chars[1],
+            (
+                65,
+                Char {
+                    x: 5,
+                    y: 0,
+                    width: 7,
+                    height: 9,
+                    x_offset: -1,
+                    y_offset: 2,
+                    x_advance: 7,
+                    page: 1,
+                }
+            )
+        );
+        assert_eq!(descriptor.kernings, [(65, 86, -2)]);
+    }
+
+    #[test]
+    fn splits_quoted_values() {
+        assert_eq!(
+            tokenize("  info face=\"A = B\" size=8"),
+            Some(("info", vec![("face", "A = B"), ("size", "8")]))
+        );
+        assert_eq!(tokenize("common"), Some(("common", vec![])));
+        assert_eq!(tokenize("info face=\"Tiny"), None);
+    }
+
+    #[test]
+    fn rejects_invalid_values() {
+        let common = "common lineHeight=10 base=8 scaleW=64 scaleH=64\n";
+        let error = |lines: &str| {
+            parse(&format!("{common}{lines}")).map(|_| ()).unwrap_err()
+        };
+
+        assert_eq!(
+            parse("info size=8\n").map(|_| ()),
+            Err(InvalidBitmapFont::new("missing common block"))
+        );
+        assert_eq!(
+            error("info face=\"Tiny\n"),
+            InvalidBitmapFont::new("unclosed quote on line 2")
+        );
+        assert_eq!(
+            error("kerning first=65 second=86\n"),
+            InvalidBitmapFont::new("invalid amount of kerning on line 2")
+        );
+        assert_eq!(
+            error("kerning first=-65 second=86 amount=1\n"),
+            InvalidBitmapFont::new("negative first of kerning on line 2")
+        );
+        assert_eq!(
+            error("kerning first=65 second=86 amount=3000000000\n"),
+            InvalidBitmapFont::new("out of range amount of kerning on line 2")
+        );
+        assert_eq!(
+            error("\nchar id=-2 x=0 y=0\n"),
+            InvalidBitmapFont::new("invalid id of char on line 3")
+        );
+        assert_eq!(
+            error("page id=4000000000 file=\"huge.png\"\n"),
+            InvalidBitmapFont::new("out of range id of page on line 2")
+        );
+    }
+}
@@ -4,14 +4,15 @@ use glyph_brush::DefaultSectionHasher;
 use glyph_brush::ab_glyph::Font;
 use glyph_brush::delegate_glyph_brush_builder_fns;
 
-use super::GlyphBrush;
+use super::{FontDatabase, GlyphBrush, GlyphRasterizer, RasterizedFont};
 
 /// Builder for a [`GlyphBrush`](struct.GlyphBrush.html).
 pub struct GlyphBrushBuilder<D, F, H = DefaultSectionHasher> {
-    inner: glyph_brush::GlyphBrushBuilder<F, H>,
-    texture_filter_method: wgpu::FilterMode,
-    multisample_state: wgpu::MultisampleState,
-    depth: D,
+    pub(crate) inner: glyph_brush::GlyphBrushBuilder<F, H>,
+    pub(crate) texture_filter_method: wgpu::FilterMode,
+    pub(crate) multisample_state: wgpu::MultisampleState,
+    pub(crate) font_database: FontDatabase,
+    pub(crate) depth: D,
 }
 
 impl<F, H> From<glyph_brush::GlyphBrushBuilder<F, H>>
@@ -22,6 +23,7 @@ impl<F, H> From<glyph_brush::GlyphBrushBuilder<F, H>>
             inner,
             texture_filter_method: wgpu::FilterMode::Linear,
             multisample_state: wgpu::MultisampleState::default(),
+            font_database: FontDatabase::new(),
             depth: (),
         }
     }
@@ -40,6 +42,7 @@ impl GlyphBrushBuilder<(), ()> {
             inner: glyph_brush::GlyphBrushBuilder::using_fonts(fonts),
             texture_filter_method: wgpu::FilterMode::Linear,
             multisample_state: wgpu::MultisampleState::default(),
+            font_database: FontDatabase::new(),
             depth: (),
         }
     }
@@ -83,6 +86,14 @@ impl<F: Font, D, H: BuildHasher> GlyphBrushBuilder<D, F, H> {
         self
     }
 
+    /// Sets the [`FontDatabase`](struct.FontDatabase.html) used to load fonts
+    /// lazily by family name with
+    /// [`GlyphBrush::font_id`](struct.GlyphBrush.html#method.font_id).
+    pub fn font_database(mut self, font_database: FontDatabase) -> Self {
+        self.font_database = font_database;
+        self
+    }
+
     /// Sets the section hasher. `GlyphBrush` cannot handle absolute section
     /// hash collisions so use a good hash algorithm.
     ///
@@ -98,6 +109,32 @@ impl<F: Font, D, H: BuildHasher> GlyphBrushBuilder<D, F, H> {
             inner: self.inner.section_hasher(section_hasher),
             texture_filter_method: self.texture_filter_method,
             multisample_state: self.multisample_state,
+            font_database: self.font_database,
+            depth: self.depth,
+        }
+    }
+
+    /// Rasterizes the glyphs of the fonts with the given
+    /// [`GlyphRasterizer`](trait.GlyphRasterizer.html), instead of filling
+    /// their outlines with `ab_glyph`.
+    ///
+    /// The fonts of the brush become
+    /// [`RasterizedFont`](struct.RasterizedFont.html)s sharing the
+    /// rasterizer.
+    pub fn rasterizer<R>(
+        self,
+        rasterizer: R,
+    ) -> GlyphBrushBuilder<D, RasterizedFont<F>, H>
+    where
+        R: GlyphRasterizer<F> + 'static,
+    {
+        GlyphBrushBuilder {
+            inner: self.inner.replace_fonts(|fonts| {
+                RasterizedFont::wrap_all(fonts, rasterizer)
+            }),
+            texture_filter_method: self.texture_filter_method,
+            multisample_state: self.multisample_state,
+            font_database: self.font_database,
             depth: self.depth,
         }
     }
@@ -111,6 +148,7 @@ impl<F: Font, D, H: BuildHasher> GlyphBrushBuilder<D, F, H> {
             inner: self.inner,
             texture_filter_method: self.texture_filter_method,
             multisample_state: self.multisample_state,
+            font_database: self.font_database,
             depth: depth_stencil_state,
         }
     }
@@ -124,13 +162,7 @@ impl<F: Font + Sync, H: BuildHasher> GlyphBrushBuilder<(), F, H> {
         device: &wgpu::Device,
         render_format: wgpu::TextureFormat,
     ) -> GlyphBrush<(), F, H> {
-        GlyphBrush::<(), F, H>::new(
-            device,
-            self.texture_filter_method,
-            self.multisample_state,
-            render_format,
-            self.inner,
-        )
+        GlyphBrush::<(), F, H>::new(device, render_format, self)
     }
 }
 
@@ -146,11 +178,8 @@ impl<F: Font + Sync, H: BuildHasher>
     ) -> GlyphBrush<wgpu::DepthStencilState, F, H> {
         GlyphBrush::<wgpu::DepthStencilState, F, H>::new(
             device,
-            self.texture_filter_method,
-            self.multisample_state,
             render_format,
-            self.depth,
-            self.inner,
+            self,
         )
     }
 }
@@ -0,0 +1,292 @@
+use crate::TextPosition;
+use crate::extra::GlyphSection;
+use crate::text_position::Lines;
+
+use glyph_brush::ab_glyph::{Rect, point};
+
+use std::ops::Range;
+
+/// The selection and caret of an editable section, drawn together with its
+/// text by
+/// [`queue_with_cursor`](struct.GlyphBrush.html#method.queue_with_cursor).
+///
+/// The selection highlight is drawn behind the glyphs of the section and the
+/// caret in front of them. Both use the `z` of the `Text` they are in, so a
+/// depth test needs to let equal depths pass.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Cursor {
+    /// The selected text, if any.
+    pub selection: Option<Selection>,
+    /// The caret, if any.
+    pub caret: Option<Caret>,
+}
+
+impl Cursor {
+    /// Creates a `Cursor` without selection nor caret.
+    pub fn new() -> Self {
+        Cursor::default()
+    }
+
+    /// Sets the selection of the cursor.
+    pub fn with_selection(mut self, selection: Selection) -> Self {
+        self.selection = Some(selection);
+        self
+    }
+
+    /// Sets the caret of the cursor.
+    pub fn with_caret(mut self, caret: Caret) -> Self {
+        self.caret = Some(caret);
+        self
+    }
+}
+
+/// A range of selected text, highlighted with a color.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Selection {
+    /// The selected range. It may be reversed.
+    pub range: Range<TextPosition>,
+    /// The color of the highlight.
+    pub color: [f32; 4],
+}
+
+impl Selection {
+    /// Creates a new `Selection`.
+    pub fn new(range: Range<TextPosition>, color: [f32; 4]) -> Self {
+        Selection { range, color }
+    }
+}
+
+/// The shape of a [`Caret`](struct.Caret.html).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum CaretStyle {
+    /// A vertical bar centered on the caret position.
+    #[default]
+    Bar,
+    /// A box covering the character after the caret.
+    Block,
+    /// A horizontal bar under the character after the caret.
+    Underline,
+}
+
+/// An insertion point in the text of a section.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Caret {
+    /// The position of the caret.
+    pub position: TextPosition,
+    /// The color of the caret.
+    pub color: [f32; 4],
+    /// The shape of the caret.
+    pub style: CaretStyle,
+    /// The thickness of a bar or underline caret, in pixels.
+    pub width: f32,
+    /// The phase of the blinking of the caret. The caret is visible during
+    /// the first half of every unit, i.e. when the fractional part of the
+    /// phase is less than `0.5`.
+    pub blink_phase: f32,
+}
+
+impl Caret {
+    /// Creates a black bar `Caret` at the given position.
+    pub fn new(position: TextPosition) -> Self {
+        Caret {
+            position,
+            color: [0.0, 0.0, 0.0, 1.0],
+            style: CaretStyle::Bar,
+            width: 1.0,
+            blink_phase: 0.0,
+        }
+    }
+
+    /// Sets the color of the caret.
+    pub fn with_color(mut self, color: [f32; 4]) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Sets the shape of the caret.
+    pub fn with_style(mut self, style: CaretStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Sets the thickness of a bar or underline caret.
+    pub fn with_width(mut self, width: f32) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Sets the blink phase of the caret.
+    pub fn with_blink_phase(mut self, blink_phase: f32) -> Self {
+        self.blink_phase = blink_phase;
+        self
+    }
+
+    /// Returns whether the caret is visible at its blink phase.
+    pub fn is_visible(&self) -> bool {
+        self.blink_phase.rem_euclid(1.0) < 0.5
+    }
+
+    /// Returns the rectangle covered by the caret.
+    pub(crate) fn rect(
+        &self,
+        lines: &Lines,
+        section: &GlyphSection<'_>,
+    ) -> Option<Rect> {
+        let caret = lines.caret_rect(section, self.position)?;
+
+        if self.style == CaretStyle::Bar {
+            let half = self.width / 2.0;
+
+            return Some(Rect {
+                min: point(caret.min.x - half, caret.min.y),
+                max: point(caret.max.x + half, caret.max.y),
+            });
+        }
+
+        // Block and underline carets cover the character after the caret
+        let next = section
+            .text
+            .get(self.position.text_index)
+            .and_then(|text| text.text.get(self.position.byte_offset..))
+            .and_then(|rest| rest.chars().next())
+            .filter(|c| !c.is_control())
+            .map(|c| {
+                TextPosition::new(
+                    self.position.text_index,
+                    self.position.byte_offset + c.len_utf8(),
+                )
+            });
+
+        let (left, right) = next
+            .and_then(|next| {
+                lines
+                    .selection_rects(section, self.position..next)
+                    .into_iter()
+                    .find(|rect| {
+                        rect.min.y <= caret.min.y && caret.max.y <= rect.max.y
+                    })
+            })
+            .map_or(
+                (caret.min.x, caret.min.x + caret.height() / 2.0),
+                |rect| (rect.min.x, rect.max.x),
+            );
+
+        let top = match self.style {
+            CaretStyle::Underline => caret.max.y - self.width,
+            _ => caret.min.y,
+        };
+
+        Some(Rect {
+            min: point(left, top),
+            max: point(right, caret.max.y),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extra::GlyphExtra;
+    use crate::test_util::{advance, assert_near, font, glyphs};
+
+    fn section(text: &str) -> GlyphSection<'_> {
+        glyph_brush::Section::<GlyphExtra>::new()
+            .add_text(glyph_brush::Text::new(text))
+    }
+
+    fn lines(section: &GlyphSection<'_>) -> Lines {
+        let glyphs = glyphs(&section.layout, section);
+
+        Lines::new(std::slice::from_ref(&font()), section, &glyphs)
+    }
+
+    #[test]
+    fn blinking() {
+        let caret = Caret::new(TextPosition::new(0, 0));
+
+        assert!(caret.is_visible());
+        assert!(caret.with_blink_phase(0.49).is_visible());
+        assert!(!caret.with_blink_phase(0.5).is_visible());
+        assert!(caret.with_blink_phase(3.25).is_visible());
+        assert!(!caret.with_blink_phase(-0.25).is_visible());
+    }
+
+    #[test]
+    fn bars_are_centered_on_the_caret() {
+        let section = section("abc");
+        let lines = lines(&section);
+        let position = TextPosition::new(0, 1);
+        let caret = lines.caret_rect(&section, position).unwrap();
+
+        let rect = Caret::new(position)
+            .with_width(4.0)
+            .rect(&lines, &section)
+            .unwrap();
+
+        assert_near(rect.min.x, advance() - 2.0);
+        assert_near(rect.max.x, advance() + 2.0);
+        assert_eq!((rect.min.y, rect.max.y), (caret.min.y, caret.max.y));
+    }
+
+    #[test]
+    fn blocks_and_underlines_cover_the_next_character() {
+        let section = section("abc");
+        let lines = lines(&section);
+        let w = advance();
+        let caret = Caret::new(TextPosition::new(0, 1)).with_width(2.0);
+        let line = lines.caret_rect(&section, caret.position).unwrap();
+
+        let block = caret
+            .with_style(CaretStyle::Block)
+            .rect(&lines, &section)
+            .unwrap();
+
+        assert_near(block.min.x, w);
+        assert_near(block.max.x, 2.0 * w);
+        assert_eq!((block.min.y, block.max.y), (line.min.y, line.max.y));
+
+        let underline = caret
+            .with_style(CaretStyle::Underline)
+            .rect(&lines, &section)
+            .unwrap();
+
+        assert_near(underline.min.x, w);
+        assert_near(underline.max.x, 2.0 * w);
+        assert_near(underline.min.y, line.max.y - 2.0);
+    }
+
+    #[test]
+    fn blocks_at_the_end_of_the_text_are_half_as_wide_as_tall() {
+        let section = section("ab\ncd");
+        let lines = lines(&section);
+
+        // At the end of a line, before the line break, and of the text
+        for position in [TextPosition::new(0, 2), TextPosition::new(0, 5)] {
+            let caret = lines.caret_rect(&section, position).unwrap();
+            let block = Caret::new(position)
+                .with_style(CaretStyle::Block)
+                .rect(&lines, &section)
+                .unwrap();
+
+            assert_near(block.min.x, caret.min.x);
+            assert_near(block.width(), caret.height() / 2.0);
+        }
+    }
+
+    #[test]
+    fn positions_past_the_text_are_placed_at_its_end() {
+        let section = section("ab");
+        let lines = lines(&section);
+        let end = Caret::new(TextPosition::new(0, 2))
+            .with_style(CaretStyle::Block)
+            .rect(&lines, &section);
+
+        assert!(end.is_some());
+
+        for position in [TextPosition::new(1, 0), TextPosition::new(0, 10)] {
+            let caret = Caret::new(position).with_style(CaretStyle::Block);
+
+            assert_eq!(caret.rect(&lines, &section), end);
+        }
+    }
+}
@@ -0,0 +1,601 @@
+use glyph_brush::ab_glyph::FontVec;
+use log::warn;
+
+use std::collections::HashSet;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// An index of font faces found on disk, queryable by family name, weight,
+/// style and stretch.
+///
+/// Indexing only reads the metadata of each face. The font data itself is
+/// loaded on demand with [`load`](#method.load), usually through
+/// [`GlyphBrush::font_id`](struct.GlyphBrush.html#method.font_id).
+#[derive(Debug, Clone, Default)]
+pub struct FontDatabase {
+    faces: Vec<FaceInfo>,
+}
+
+/// The metadata of a single font face in a [`FontDatabase`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FaceInfo {
+    /// The file containing the face.
+    pub path: PathBuf,
+    /// The index of the face inside the file. Always `0` unless the file is
+    /// a font collection (`.ttc` or `.otc`).
+    pub index: u32,
+    /// The family names of the face, typographic names first.
+    pub families: Vec<String>,
+    /// The weight of the face.
+    pub weight: Weight,
+    /// The style of the face.
+    pub style: Style,
+    /// The stretch of the face.
+    pub stretch: Stretch,
+}
+
+/// A face query, matched following the CSS font matching rules.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Query<'a> {
+    /// The family name to look for. Matched case-insensitively.
+    pub family: &'a str,
+    /// The preferred weight.
+    pub weight: Weight,
+    /// The preferred style.
+    pub style: Style,
+    /// The preferred stretch.
+    pub stretch: Stretch,
+}
+
+impl<'a> Query<'a> {
+    /// Creates a query for the normal face of the given family.
+    pub fn new(family: &'a str) -> Self {
+        Query {
+            family,
+            weight: Weight::Normal,
+            style: Style::Normal,
+            stretch: Stretch::Normal,
+        }
+    }
+
+    /// Sets the preferred weight.
+    pub fn with_weight(mut self, weight: Weight) -> Self {
+        self.weight = weight;
+        self
+    }
+
+    /// Sets the preferred style.
+    pub fn with_style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Sets the preferred stretch.
+    pub fn with_stretch(mut self, stretch: Stretch) -> Self {
+        self.stretch = stretch;
+        self
+    }
+}
+
+/// The weight of a font face.
+///
+/// Weights are compared by number, so `Weight::Other(400)` equals
+/// `Weight::Normal`.
+#[derive(Debug, Clone, Copy, Default)]
+pub enum Weight {
+    Thin,
+    ExtraLight,
+    Light,
+    #[default]
+    Normal,
+    Medium,
+    SemiBold,
+    Bold,
+    ExtraBold,
+    Black,
+    Other(u16),
+}
+
+impl Weight {
+    /// Returns the weight with the given numeric representation, named if it
+    /// has a name.
+    pub fn from_number(number: u16) -> Self {
+        match number {
+            100 => Weight::Thin,
+            200 => Weight::ExtraLight,
+            300 => Weight::Light,
+            400 => Weight::Normal,
+            500 => Weight::Medium,
+            600 => Weight::SemiBold,
+            700 => Weight::Bold,
+            800 => Weight::ExtraBold,
+            900 => Weight::Black,
+            n => Weight::Other(n),
+        }
+    }
+
+    /// Returns the numeric representation of the weight, from `1` to `1000`.
+    pub fn to_number(self) -> u16 {
+        match self {
+            Weight::Thin => 100,
+            Weight::ExtraLight => 200,
+            Weight::Light => 300,
+            Weight::Normal => 400,
+            Weight::Medium => 500,
+            Weight::SemiBold => 600,
+            Weight::Bold => 700,
+            Weight::ExtraBold => 800,
+            Weight::Black => 900,
+            Weight::Other(n) => n,
+        }
+    }
+}
+
+impl PartialEq for Weight {
+    fn eq(&self, other: &Self) -> bool {
+        self.to_number() == other.to_number()
+    }
+}
+
+impl Eq for Weight {}
+
+impl Hash for Weight {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.to_number().hash(state);
+    }
+}
+
+impl From<ttf_parser::Weight> for Weight {
+    fn from(weight: ttf_parser::Weight) -> Self {
+        Weight::from_number(weight.to_number())
+    }
+}
+
+/// The style of a font face.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Style {
+    #[default]
+    Normal,
+    Italic,
+    Oblique,
+}
+
+/// The stretch (width) of a font face.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum Stretch {
+    UltraCondensed,
+    ExtraCondensed,
+    Condensed,
+    SemiCondensed,
+    #[default]
+    Normal,
+    SemiExpanded,
+    Expanded,
+    ExtraExpanded,
+    UltraExpanded,
+}
+
+impl From<ttf_parser::Width> for Stretch {
+    fn from(width: ttf_parser::Width) -> Self {
+        use ttf_parser::Width;
+
+        match width {
+            Width::UltraCondensed => Stretch::UltraCondensed,
+            Width::ExtraCondensed => Stretch::ExtraCondensed,
+            Width::Condensed => Stretch::Condensed,
+            Width::SemiCondensed => Stretch::SemiCondensed,
+            Width::Normal => Stretch::Normal,
+            Width::SemiExpanded => Stretch::SemiExpanded,
+            Width::Expanded => Stretch::Expanded,
+            Width::ExtraExpanded => Stretch::ExtraExpanded,
+            Width::UltraExpanded => Stretch::UltraExpanded,
+        }
+    }
+}
+
+impl FontDatabase {
+    /// Creates an empty database.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the indexed faces.
+    ///
+    /// The index of a face in this slice is used as its id in
+    /// [`query`](#method.query) and [`load`](#method.load).
+    pub fn faces(&self) -> &[FaceInfo] {
+        &self.faces
+    }
+
+    /// Indexes the fonts found in the standard font directories of the
+    /// current platform.
+    ///
+    /// Directories that do not exist are skipped.
+    pub fn load_system_fonts(&mut self) {
+        for dir in system_font_dirs() {
+            self.load_fonts_dir(dir);
+        }
+    }
+
+    /// Indexes every font file found in the given directory, recursively.
+    ///
+    /// Unreadable files and files that do not contain valid fonts are
+    /// skipped. Symbolic links are followed, but every directory is only
+    /// visited once.
+    pub fn load_fonts_dir<P: AsRef<Path>>(&mut self, dir: P) {
+        self.load_dir(dir.as_ref(), &mut HashSet::new());
+    }
+
+    fn load_dir(&mut self, dir: &Path, visited: &mut HashSet<PathBuf>) {
+        // Links may lead back to a parent directory
+        let Ok(canonical) = fs::canonicalize(dir) else {
+            return;
+        };
+
+        if !visited.insert(canonical) {
+            return;
+        }
+
+        let Ok(entries) = fs::read_dir(dir) else {
+            return;
+        };
+
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|entry| Some(entry.ok()?.path()))
+            .collect();
+
+        // Keep face ids stable across runs
+        paths.sort();
+
+        for path in paths {
+            if path.is_dir() {
+                self.load_dir(&path, visited);
+            } else if is_font_file(&path)
+                && let Err(error) = self.load_font_file(&path)
+            {
+                warn!("Skipping font file {}: {error}", path.display());
+            }
+        }
+    }
+
+    /// Indexes all the faces contained in the given font file.
+    pub fn load_font_file<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+    ) -> io::Result<()> {
+        let path = path.as_ref();
+        let data = fs::read(path)?;
+        let count = ttf_parser::fonts_in_collection(&data).unwrap_or(1);
+        let indexed = self.faces.len();
+
+        for index in 0..count {
+            let Ok(face) = ttf_parser::Face::parse(&data, index) else {
+                continue;
+            };
+
+            let families = family_names(&face);
+
+            if families.is_empty() {
+                continue;
+            }
+
+            self.faces.push(FaceInfo {
+                path: path.to_owned(),
+                index,
+                families,
+                weight: face.weight().into(),
+                style: match face.style() {
+                    ttf_parser::Style::Normal => Style::Normal,
+                    ttf_parser::Style::Italic => Style::Italic,
+                    ttf_parser::Style::Oblique => Style::Oblique,
+                },
+                stretch: face.width().into(),
+            });
+        }
+
+        if self.faces.len() == indexed {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "no valid font faces found",
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Finds the face that best matches the given query.
+    ///
+    /// Returns the id of the face, see [`faces`](#method.faces).
+    pub fn query(&self, query: &Query<'_>) -> Option<usize> {
+        let candidates: Vec<usize> = self
+            .faces
+            .iter()
+            .enumerate()
+            .filter(|(_, face)| {
+                face.families
+                    .iter()
+                    .any(|family| family.eq_ignore_ascii_case(query.family))
+            })
+            .map(|(id, _)| id)
+            .collect();
+
+        let stretch = candidates
+            .iter()
+            .map(|&id| self.faces[id].stretch)
+            .min_by_key(|&stretch| stretch_distance(query.stretch, stretch))?;
+
+        let style = candidates
+            .iter()
+            .map(|&id| &self.faces[id])
+            .filter(|face| face.stretch == stretch)
+            .map(|face| face.style)
+            .min_by_key(|&style| style_distance(query.style, style))?;
+
+        candidates
+            .into_iter()
+            .filter(|&id| {
+                self.faces[id].stretch == stretch
+                    && self.faces[id].style == style
+            })
+            .min_by_key(|&id| {
+                weight_distance(query.weight, self.faces[id].weight)
+            })
+    }
+
+    /// Loads the font data of the face with the given id.
+    ///
+    /// # Panics
+    /// Panics if the id does not belong to a face in this database.
+    pub fn load(&self, id: usize) -> io::Result<FontVec> {
+        let face = &self.faces[id];
+        let data = fs::read(&face.path)?;
+
+        FontVec::try_from_vec_and_index(data, face.index)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+}
+
+fn family_names(face: &ttf_parser::Face<'_>) -> Vec<String> {
+    use ttf_parser::name_id;
+
+    let mut families = Vec::new();
+
+    for name_id in [name_id::TYPOGRAPHIC_FAMILY, name_id::FAMILY] {
+        for name in face.names().into_iter().filter(|n| n.name_id == name_id) {
+            if let Some(family) = name.to_string()
+                && !families.contains(&family)
+            {
+                families.push(family);
+            }
+        }
+    }
+
+    families
+}
+
+fn is_font_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| {
+            ["ttf", "otf", "ttc", "otc"]
+                .iter()
+                .any(|known| extension.eq_ignore_ascii_case(known))
+        })
+}
+
+fn system_font_dirs() -> Vec<PathBuf> {
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+    let mut dirs = Vec::new();
+
+    if cfg!(target_os = "macos") {
+        dirs.push(PathBuf::from("/System/Library/Fonts"));
+        dirs.push(PathBuf::from("/Library/Fonts"));
+        dirs.extend(home.map(|home| home.join("Library/Fonts")));
+    } else if cfg!(target_os = "windows") {
+        let windir = std::env::var_os("WINDIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("C:\\Windows"));
+
+        dirs.push(windir.join("Fonts"));
+    } else {
+        dirs.push(PathBuf::from("/usr/share/fonts"));
+        dirs.push(PathBuf::from("/usr/local/share/fonts"));
+
+        match std::env::var_os("XDG_DATA_HOME") {
+            Some(data_home) => {
+                dirs.push(PathBuf::from(data_home).join("fonts"))
+            }
+            None => {
+                dirs.extend(home.as_ref().map(|h| h.join(".local/share/fonts")))
+            }
+        }
+
+        dirs.extend(home.map(|home| home.join(".fonts")));
+    }
+
+    dirs
+}
+
+fn stretch_distance(wanted: Stretch, available: Stretch) -> (u8, u8) {
+    let (wanted, available) = (wanted as u8, available as u8);
+
+    // Narrower stretches are preferred for normal or condensed queries and
+    // wider ones for expanded queries
+    if wanted <= Stretch::Normal as u8 {
+        if available <= wanted {
+            (0, wanted - available)
+        } else {
+            (1, available - wanted)
+        }
+    } else if available >= wanted {
+        (0, available - wanted)
+    } else {
+        (1, wanted - available)
+    }
+}
+
+fn style_distance(wanted: Style, available: Style) -> u8 {
+    let preference: [Style; 3] = match wanted {
+        Style::Normal => [Style::Normal, Style::Oblique, Style::Italic],
+        Style::Italic => [Style::Italic, Style::Oblique, Style::Normal],
+        Style::Oblique => [Style::Oblique, Style::Italic, Style::Normal],
+    };
+
+    preference
+        .iter()
+        .position(|&style| style == available)
+        .unwrap_or(preference.len()) as u8
+}
+
+fn weight_distance(wanted: Weight, available: Weight) -> (u8, u16) {
+    let (wanted, available) = (wanted.to_number(), available.to_number());
+
+    if (400..=500).contains(&wanted) {
+        // Look between the wanted weight and 500 first, then lighter
+        // weights, then heavier ones
+        if (wanted..=500).contains(&available) {
+            (0, available - wanted)
+        } else if available < wanted {
+            (1, wanted - available)
+        } else {
+            (2, available - wanted)
+        }
+    } else if wanted < 400 {
+        if available <= wanted {
+            (0, wanted - available)
+        } else {
+            (1, available - wanted)
+        }
+    } else if available >= wanted {
+        (0, available - wanted)
+    } else {
+        (1, wanted - available)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::INCONSOLATA;
+
+    /// A directory removed when dropped.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir()
+                .join(format!("wgpu_glyph-{name}-{}", std::process::id()));
+
+            let _ = fs::remove_dir_all(&path);
+            fs::create_dir_all(&path).unwrap();
+
+            TempDir(path)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn loads_fonts_from_directories() {
+        let dir = TempDir::new("fonts");
+        let nested = dir.0.join("nested");
+
+        fs::create_dir(&nested).unwrap();
+        fs::write(nested.join("Inconsolata.TTF"), INCONSOLATA).unwrap();
+        fs::write(dir.0.join("broken.ttf"), b"not a font").unwrap();
+        fs::write(dir.0.join("readme.txt"), INCONSOLATA).unwrap();
+
+        let mut database = FontDatabase::new();
+        database.load_fonts_dir(&dir.0);
+
+        assert_eq!(database.faces().len(), 1);
+
+        let face = &database.faces()[0];
+
+        assert_eq!(face.path, nested.join("Inconsolata.TTF"));
+        assert_eq!(face.index, 0);
+        assert!(face.families.iter().any(|family| family == "Inconsolata"));
+        assert_eq!(face.weight, Weight::Normal);
+        assert_eq!(face.style, Style::Normal);
+
+        let id = database.query(&Query::new("inconsolata")).unwrap();
+
+        assert!(database.load(id).is_ok());
+        assert_eq!(database.query(&Query::new("Missing")), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symbolic_link_loops_are_visited_once() {
+        let dir = TempDir::new("links");
+
+        fs::write(dir.0.join("Inconsolata.ttf"), INCONSOLATA).unwrap();
+        std::os::unix::fs::symlink(&dir.0, dir.0.join("loop")).unwrap();
+
+        let mut database = FontDatabase::new();
+        database.load_fonts_dir(&dir.0);
+
+        assert_eq!(database.faces().len(), 1);
+    }
+
+    #[test]
+    fn invalid_font_files_are_errors() {
+        let dir = TempDir::new("invalid");
+        let path = dir.0.join("broken.otf");
+
+        fs::write(&path, b"not a font").unwrap();
+
+        let mut database = FontDatabase::new();
+
+        assert!(database.load_font_file(&path).is_err());
+        assert!(database.load_font_file(dir.0.join("missing.otf")).is_err());
+        assert!(database.faces().is_empty());
+    }
+
+    #[test]
+    fn weights_are_compared_by_number() {
+        assert_eq!(Weight::Other(400), Weight::Normal);
+        assert_eq!(Weight::from_number(700), Weight::Bold);
+        assert!(matches!(Weight::from_number(700), Weight::Bold));
+        assert!(matches!(Weight::from_number(350), Weight::Other(350)));
+        assert_ne!(Weight::Other(350), Weight::Light);
+    }
+
+    #[test]
+    fn queries_match_the_closest_face() {
+        let face = |weight, style| FaceInfo {
+            path: PathBuf::new(),
+            index: 0,
+            families: vec!["Family".to_owned()],
+            weight,
+            style,
+            stretch: Stretch::Normal,
+        };
+
+        let database = FontDatabase {
+            faces: vec![
+                face(Weight::Light, Style::Normal),
+                face(Weight::Bold, Style::Normal),
+                face(Weight::Normal, Style::Oblique),
+                face(Weight::Black, Style::Normal),
+            ],
+        };
+
+        let query = |weight, style| {
+            database.query(
+                &Query::new("family").with_weight(weight).with_style(style),
+            )
+        };
+
+        assert_eq!(query(Weight::Normal, Style::Normal), Some(0));
+        assert_eq!(query(Weight::SemiBold, Style::Normal), Some(1));
+        assert_eq!(query(Weight::Other(850), Style::Normal), Some(3));
+        assert_eq!(query(Weight::Bold, Style::Italic), Some(2));
+    }
+}
@@ -0,0 +1,404 @@
+use crate::extra::GlyphSection;
+use crate::pipeline::Quad;
+use crate::quads::Quads;
+use crate::{SectionAttributes, SectionGlyph, TextAttributes};
+
+use glyph_brush::FontId;
+use glyph_brush::ab_glyph::{Font, Rect, ScaleFont, point};
+
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+/// A line drawn under, through or over the text of a
+/// [`Text`](struct.Text.html).
+///
+/// By default, the line has the color of the text and the thickness
+/// suggested by its font.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Decoration {
+    /// The style of the line.
+    pub style: DecorationStyle,
+    /// The color of the line. Defaults to the color of the text.
+    pub color: Option<[f32; 4]>,
+    /// The thickness of the line, in pixels. Defaults to the thickness
+    /// suggested by the font.
+    pub thickness: Option<f32>,
+}
+
+impl Decoration {
+    /// Creates a `Decoration` with the given style.
+    pub fn new(style: DecorationStyle) -> Self {
+        Decoration {
+            style,
+            ..Decoration::default()
+        }
+    }
+
+    /// Sets the color of the line.
+    pub fn with_color(mut self, color: [f32; 4]) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    /// Sets the thickness of the line, in pixels.
+    pub fn with_thickness(mut self, thickness: f32) -> Self {
+        self.thickness = Some(thickness);
+        self
+    }
+}
+
+impl Hash for Decoration {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.style.hash(state);
+        self.color.map(|color| color.map(f32::to_bits)).hash(state);
+        self.thickness.map(f32::to_bits).hash(state);
+    }
+}
+
+/// The pattern of a [`Decoration`](struct.Decoration.html).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum DecorationStyle {
+    /// A continuous line.
+    #[default]
+    Solid,
+    /// Dashes three times as long as the line is thick.
+    Dashed,
+    /// Round dots.
+    Dotted,
+    /// A wave, like the ones used to highlight spelling mistakes.
+    Wavy,
+}
+
+/// The position and thickness of the decorations of a font, in font units.
+///
+/// Positions are the distance from the baseline to the top of the line,
+/// positive upwards.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Metrics {
+    underline_position: f32,
+    underline_thickness: f32,
+    strikeout_position: f32,
+    strikeout_thickness: f32,
+}
+
+impl Metrics {
+    /// Reads the metrics from the `post` and `OS/2` tables of the font,
+    /// guessing them from its size when they are missing.
+    pub(crate) fn new<F: Font>(font: &F) -> Self {
+        let face = crate::face::parse(font);
+        let em = font.units_per_em().unwrap_or(font.height_unscaled());
+
+        let underline = face.as_ref().and_then(|face| face.underline_metrics());
+        let strikeout = face.as_ref().and_then(|face| face.strikeout_metrics());
+
+        let (underline_position, underline_thickness) = underline
+            .filter(|metrics| metrics.thickness > 0)
+            .map_or((-0.1 * em, 0.05 * em), |metrics| {
+                (f32::from(metrics.position), f32::from(metrics.thickness))
+            });
+
+        let (strikeout_position, strikeout_thickness) = strikeout
+            .filter(|metrics| metrics.thickness > 0)
+            .map_or((0.3 * em, underline_thickness), |metrics| {
+                (f32::from(metrics.position), f32::from(metrics.thickness))
+            });
+
+        Metrics {
+            underline_position,
+            underline_thickness,
+            strikeout_position,
+            strikeout_thickness,
+        }
+    }
+}
+
+/// A run of contiguous glyphs of the same `Text` in a line.
+#[derive(Debug, Clone, Copy)]
+struct Segment {
+    text_index: usize,
+    baseline: f32,
+    left: f32,
+    right: f32,
+    /// The right edge of the last non-whitespace glyph of the segment.
+    ink_right: f32,
+}
+
+impl Segment {
+    /// Drops the whitespace at the end of the segment.
+    fn trimmed(self) -> Self {
+        Segment {
+            right: self.ink_right,
+            ..self
+        }
+    }
+}
+
+/// Returns whether any `Text` of the section is decorated.
+pub(crate) fn is_decorated(attributes: &SectionAttributes) -> bool {
+    attributes.texts.iter().any(is_text_decorated)
+}
+
+fn is_text_decorated(text: &TextAttributes) -> bool {
+    text.underline.is_some()
+        || text.strikethrough.is_some()
+        || text.overline.is_some()
+}
+
+/// Builds the quads of the decorations of a section from its glyphs, as
+/// returned by
+/// [`GlyphCruncher::glyphs`](trait.GlyphCruncher.html#method.glyphs).
+///
+/// Underlines and overlines are pushed behind the glyphs and strikethroughs
+/// in front of them. Quads are clipped to `bounds`.
+pub(crate) fn quads<F: Font>(
+    fonts: &[F],
+    metrics: &mut HashMap<FontId, Metrics>,
+    section: &GlyphSection<'_>,
+    attributes: &SectionAttributes,
+    glyphs: &[SectionGlyph],
+    bounds: Rect,
+    quads: &mut Quads,
+) {
+    for segment in segments(fonts, section, attributes, glyphs) {
+        let text = &section.text[segment.text_index];
+        let text_attributes = attributes.text(segment.text_index);
+        let font = &fonts[text.font_id.0];
+        let scaled = font.as_scaled(text.scale);
+        let factor = scaled.v_scale_factor();
+
+        let metrics = *metrics
+            .entry(text.font_id)
+            .or_insert_with(|| Metrics::new(font));
+
+        let lines = [
+            (
+                text_attributes.underline,
+                metrics.underline_position,
+                metrics.underline_thickness,
+            ),
+            (
+                text_attributes.overline,
+                scaled.ascent() / factor,
+                metrics.underline_thickness,
+            ),
+            (
+                text_attributes.strikethrough,
+                metrics.strikeout_position,
+                metrics.strikeout_thickness,
+            ),
+        ];
+
+        for (index, (decoration, position, thickness)) in
+            lines.into_iter().enumerate()
+        {
+            let Some(decoration) = decoration else {
+                continue;
+            };
+
+            let thickness = decoration
+                .thickness
+                .unwrap_or((thickness * factor).max(1.0));
+
+            let top = segment.baseline - position * factor;
+
+            let rect = Rect {
+                min: point(segment.left, top),
+                max: point(segment.right, top + thickness),
+            };
+
+            let instance = Quad::decoration(
+                rect,
+                bounds,
+                text.extra.z,
+                decoration.color.unwrap_or(text.extra.color),
+                decoration.style,
+            )
+            .map(|instance| match decoration.color {
+                Some(_) => instance.transformed(&text.extra),
+                None => instance.transformed(&text.extra).paletted(&text.extra),
+            });
+
+            // Like in CSS, strikethroughs are drawn over the text and the
+            // other lines under it
+            if index == 2 {
+                quads.front.extend(instance);
+            } else {
+                quads.behind.extend(instance);
+            }
+        }
+    }
+}
+
+/// Groups the glyphs of the decorated texts of a section into segments.
+///
+/// Segments are trimmed of their trailing whitespace, unless they are
+/// followed on the same line by the glyphs of another decorated text.
+fn segments<F: Font>(
+    fonts: &[F],
+    section: &GlyphSection<'_>,
+    attributes: &SectionAttributes,
+    glyphs: &[SectionGlyph],
+) -> Vec<Segment> {
+    let mut segments = Vec::new();
+    let mut open: Option<Segment> = None;
+    let mut baseline = None;
+
+    for sg in glyphs {
+        let y = sg.glyph.position.y;
+        let new_line = baseline.is_some_and(|b: f32| (b - y).abs() >= 0.5);
+        baseline = Some(y);
+
+        if new_line && let Some(segment) = open.take() {
+            segments.push(segment.trimmed());
+        }
+
+        let text = &section.text[sg.section_index];
+
+        if !is_text_decorated(attributes.text(sg.section_index)) {
+            segments.extend(open.take().map(Segment::trimmed));
+            continue;
+        }
+
+        let left = sg.glyph.position.x;
+        let right = left
+            + fonts[sg.font_id.0]
+                .as_scaled(sg.glyph.scale)
+                .h_advance(sg.glyph.id);
+
+        let whitespace = text.text[sg.byte_index..]
+            .chars()
+            .next()
+            .is_none_or(char::is_whitespace);
+
+        match &mut open {
+            Some(segment)
+                if segment.text_index == sg.section_index
+                    && left >= segment.left - 0.5
+                    && left <= segment.right + 0.5 =>
+            {
+                segment.right = segment.right.max(right);
+
+                if !whitespace {
+                    segment.ink_right = segment.ink_right.max(right);
+                }
+            }
+            _ => {
+                segments.extend(open.take().map(|segment| {
+                    if (left - segment.right).abs() <= 0.5 {
+                        segment
+                    } else {
+                        segment.trimmed()
+                    }
+                }));
+
+                open = Some(Segment {
+                    text_index: sg.section_index,
+                    baseline: y,
+                    left,
+                    right,
+                    ink_right: if whitespace { left } else { right },
+                });
+            }
+        }
+    }
+
+    segments.extend(open.map(Segment::trimmed));
+
+    segments.retain(|segment| segment.right > segment.left);
+    segments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extra::GlyphExtra;
+    use crate::test_util::font;
+
+    use glyph_brush::ab_glyph::FontRef;
+    use glyph_brush::{GlyphPositioner, Layout, SectionGeometry, Text};
+
+    /// Lays out texts of Inconsolata, a monospace font, at 20 pixels.
+    fn layout(
+        texts: &[&'static str],
+    ) -> (FontRef<'static>, GlyphSection<'static>, Vec<SectionGlyph>) {
+        let font = font();
+
+        let section = GlyphSection::new().with_text(
+            texts
+                .iter()
+                .map(|text| Text::<GlyphExtra>::new(text).with_scale(20.0))
+                .collect(),
+        );
+
+        let glyphs = Layout::default().calculate_glyphs(
+            std::slice::from_ref(&font),
+            &SectionGeometry::from(&section),
+            &section.text,
+        );
+
+        (font, section, glyphs)
+    }
+
+    fn underlined(texts: &[usize]) -> SectionAttributes {
+        texts
+            .iter()
+            .fold(SectionAttributes::default(), |attributes, text| {
+                attributes.with_text(
+                    *text,
+                    TextAttributes::default()
+                        .with_underline(Decoration::default()),
+                )
+            })
+    }
+
+    fn extents(
+        texts: &[&'static str],
+        attributes: &SectionAttributes,
+    ) -> (f32, Vec<(usize, f32, f32)>) {
+        let (font, section, glyphs) = layout(texts);
+        let advance = font.as_scaled(20.0).h_advance(font.glyph_id('a'));
+
+        let segments = segments(&[font], &section, attributes, &glyphs)
+            .into_iter()
+            .map(|segment| (segment.text_index, segment.left, segment.right))
+            .collect();
+
+        (advance, segments)
+    }
+
+    #[test]
+    fn trims_segments_before_undecorated_text() {
+        let (advance, segments) = extents(&["ab ", "cd"], &underlined(&[0]));
+
+        assert_eq!(segments, [(0, 0.0, 2.0 * advance)]);
+    }
+
+    #[test]
+    fn keeps_whitespace_between_decorated_texts() {
+        let (advance, segments) =
+            extents(&["ab ", "cd "], &underlined(&[0, 1]));
+
+        assert_eq!(
+            segments,
+            [(0, 0.0, 3.0 * advance), (1, 3.0 * advance, 5.0 * advance)]
+        );
+    }
+
+    #[test]
+    fn trims_segments_at_line_ends() {
+        let (advance, segments) = extents(&["ab  \ncd"], &underlined(&[0]));
+
+        assert_eq!(
+            segments,
+            [(0, 0.0, 2.0 * advance), (0, 0.0, 2.0 * advance)]
+        );
+    }
+
+    #[test]
+    fn skips_whitespace_only_segments() {
+        let (_, segments) = extents(&["ab", "  ", "cd"], &underlined(&[1]));
+
+        assert!(segments.is_empty());
+    }
+}
@@ -0,0 +1,183 @@
+/// How a [`TextLayout`](struct.TextLayout.html) truncates the text that
+/// overflows the bounds of a section.
+///
+/// Characters are dropped until the rest of the line fits along with the
+/// ellipsis, which is drawn in the font, scale and color of the text it
+/// replaces.
+///
+/// ```
+/// use wgpu_glyph::{Ellipsis, EllipsisPosition, Layout, TextLayout};
+///
+/// let layout = TextLayout::new(Layout::default_single_line())
+///     .with_ellipsis(Ellipsis::new(EllipsisPosition::Middle));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Ellipsis {
+    /// Where characters are dropped in a line that is too wide.
+    pub position: EllipsisPosition,
+    /// The text inserted in place of the dropped characters. Defaults to
+    /// `…`.
+    pub text: String,
+}
+
+impl Ellipsis {
+    /// Creates an `Ellipsis` dropping characters at the given position.
+    pub fn new(position: EllipsisPosition) -> Self {
+        Ellipsis {
+            position,
+            ..Ellipsis::default()
+        }
+    }
+
+    /// Sets the text inserted in place of the dropped characters.
+    pub fn with_text(mut self, text: impl Into<String>) -> Self {
+        self.text = text.into();
+        self
+    }
+}
+
+impl Default for Ellipsis {
+    #[inline]
+    fn default() -> Self {
+        Ellipsis {
+            position: EllipsisPosition::default(),
+            text: String::from("…"),
+        }
+    }
+}
+
+/// Where an [`Ellipsis`](struct.Ellipsis.html) drops characters in a line
+/// that is too wide for its bounds.
+///
+/// Lines that do not fit under the bounds of a wrapping layout are always
+/// dropped at the end, and the last visible line ends with the ellipsis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum EllipsisPosition {
+    /// The start of the line is dropped.
+    Start,
+    /// The middle of the line is dropped, keeping both of its ends.
+    Middle,
+    /// The end of the line is dropped.
+    #[default]
+    End,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{advance, font, glyphs};
+    use crate::{Layout, Section, Text, TextLayout};
+
+    use glyph_brush::ab_glyph::{Font, ScaleFont};
+
+    /// Lays out the text in bounds of the given number of characters and
+    /// returns the drawn lines.
+    fn lay_out(
+        layout: Layout<crate::BuiltInLineBreaker>,
+        ellipsis: Ellipsis,
+        (columns, rows): (f32, f32),
+        text: &str,
+    ) -> Vec<String> {
+        let font = font();
+        let marks: Vec<_> = ellipsis
+            .text
+            .chars()
+            .map(|c| (font.glyph_id(c), c))
+            .collect();
+        let layout = TextLayout::new(layout).with_ellipsis(ellipsis);
+        let height = font.as_scaled(16.0).height();
+        let section = Section::default()
+            .with_bounds((columns * advance(), rows * height))
+            .add_text(Text::new(text));
+        let mut glyphs = glyphs(&layout, &section);
+
+        glyphs.sort_by(|a, b| {
+            (a.glyph.position.y, a.glyph.position.x)
+                .partial_cmp(&(b.glyph.position.y, b.glyph.position.x))
+                .unwrap()
+        });
+
+        let mut lines: Vec<(f32, String)> = Vec::new();
+
+        for sg in glyphs {
+            let c = text[sg.byte_index..].chars().next().unwrap();
+            let c = match marks.iter().find(|(id, _)| *id == sg.glyph.id) {
+                Some((_, mark)) if font.glyph_id(c) != sg.glyph.id => *mark,
+                _ => c,
+            };
+
+            match lines.last_mut() {
+                Some((y, line)) if *y == sg.glyph.position.y => line.push(c),
+                _ => lines.push((sg.glyph.position.y, c.to_string())),
+            }
+        }
+
+        lines.into_iter().map(|(_, line)| line).collect()
+    }
+
+    fn single_line(
+        position: EllipsisPosition,
+        columns: f32,
+        text: &str,
+    ) -> Vec<String> {
+        lay_out(
+            Layout::default_single_line(),
+            Ellipsis::new(position),
+            (columns, 1.0),
+            text,
+        )
+    }
+
+    #[test]
+    fn positions() {
+        let text = "abcdefghijkl";
+
+        assert_eq!(single_line(EllipsisPosition::End, 6.0, text), ["abcde…"]);
+        assert_eq!(single_line(EllipsisPosition::Start, 6.0, text), ["…hijkl"]);
+        assert_eq!(
+            single_line(EllipsisPosition::Middle, 6.0, text),
+            ["ab…jkl"]
+        );
+    }
+
+    #[test]
+    fn lines_that_fit_are_kept() {
+        assert_eq!(
+            single_line(EllipsisPosition::End, 6.0, "abcdef"),
+            ["abcdef"]
+        );
+        assert_eq!(
+            single_line(EllipsisPosition::End, 6.0, ""),
+            [] as [&str; 0]
+        );
+    }
+
+    #[test]
+    fn custom_text() {
+        let lines = lay_out(
+            Layout::default_single_line(),
+            Ellipsis::new(EllipsisPosition::End).with_text("..."),
+            (6.0, 1.0),
+            "abcdefghijkl",
+        );
+
+        assert_eq!(lines, ["abc..."]);
+    }
+
+    #[test]
+    fn too_narrow_bounds_keep_the_ellipsis() {
+        assert_eq!(single_line(EllipsisPosition::End, 0.5, "abc"), ["…"]);
+    }
+
+    #[test]
+    fn the_last_visible_line_ends_with_the_ellipsis() {
+        let lines = lay_out(
+            Layout::default_wrap(),
+            Ellipsis::default(),
+            (8.0, 2.0),
+            "one two three four five",
+        );
+
+        assert_eq!(lines, ["one two ", "three…"]);
+    }
+}
@@ -0,0 +1,371 @@
+use crate::animation::GlyphId;
+use crate::palette::Palette;
+use crate::style::Styles;
+use crate::{
+    Affine, Billboard, Extra, Fill, RichSection, SectionAttributes, TextStyle,
+};
+
+use std::hash::{Hash, Hasher};
+
+/// A section passed to `glyph_brush`, with the data needed to draw its
+/// glyphs.
+pub(crate) type GlyphSection<'a> = glyph_brush::Section<'a, GlyphExtra>;
+
+/// The data of the glyphs of a `Text`, resolved from its `Extra`, its
+/// `TextAttributes` and the attributes of its section when queued.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct GlyphExtra {
+    pub color: [f32; 4],
+    pub z: f32,
+    pub fill: Fill,
+    pub outline: Option<Outline>,
+    pub shadow: Option<Shadow>,
+    pub animated: bool,
+    /// The id of the glyph, if it is queued as a glyph of an animated
+    /// section.
+    pub animation: Option<GlyphId>,
+    /// The transform of the glyphs, around their origin.
+    pub transform: Option<Affine>,
+    pub billboard: Option<Billboard>,
+    /// The screen position of the section of the glyphs, which their fill
+    /// and transform are relative to.
+    ///
+    /// It is part of the geometry of the section, so it is not hashed: a
+    /// moved section keeps the hash of its extras.
+    pub origin: (f32, f32),
+    /// The entry of the palette uniform buffer holding the color of the
+    /// glyphs plus one, or zero if they are drawn with `color`.
+    pub palette: u32,
+    /// The key of the section of the glyphs, if quads are drawn around them.
+    pub section: Option<u64>,
+}
+
+impl From<Extra> for GlyphExtra {
+    fn from(extra: Extra) -> Self {
+        GlyphExtra {
+            color: extra.color,
+            z: extra.z,
+            fill: Fill::default(),
+            outline: None,
+            shadow: None,
+            animated: false,
+            animation: None,
+            transform: None,
+            billboard: None,
+            origin: (0.0, 0.0),
+            palette: 0,
+            section: None,
+        }
+    }
+}
+
+impl Default for GlyphExtra {
+    #[inline]
+    fn default() -> Self {
+        Extra::default().into()
+    }
+}
+
+impl Hash for GlyphExtra {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.color.map(f32::to_bits).hash(state);
+        self.z.to_bits().hash(state);
+        self.fill.hash(state);
+        self.outline.hash(state);
+        self.shadow.hash(state);
+        self.animated.hash(state);
+        self.animation.hash(state);
+        self.transform.hash(state);
+        self.billboard.hash(state);
+        self.palette.hash(state);
+        self.section.hash(state);
+    }
+}
+
+impl GlyphExtra {
+    /// Resolves the attributes of the text with the given index of a
+    /// section at the given origin, drawn with the color and depth of its
+    /// `Extra` unless it uses a style.
+    ///
+    /// Returns the resolved extra and the style of the text, if any.
+    pub(crate) fn resolve<'s>(
+        extra: &Extra,
+        attributes: &SectionAttributes,
+        index: usize,
+        origin: (f32, f32),
+        styles: &'s Styles,
+    ) -> (GlyphExtra, Option<&'s TextStyle>) {
+        let text_attributes = attributes.text(index);
+        let style = text_attributes
+            .style
+            .as_ref()
+            .and_then(|style| styles.resolve(style));
+
+        let slot = style.and_then(|(id, _)| Styles::slot(id));
+
+        // Styles with a slot are drawn with their palette entry, so their
+        // color is left out of the hash and changing it keeps the vertices
+        let color = match style {
+            Some((_, style)) if slot.is_none() => style.color,
+            _ => extra.color,
+        };
+
+        let glyph_extra = GlyphExtra {
+            color,
+            z: extra.z,
+            fill: text_attributes.fill,
+            outline: text_attributes.outline,
+            shadow: attributes.shadow,
+            animated: text_attributes.animated,
+            animation: None,
+            transform: attributes.transform,
+            billboard: attributes.billboard,
+            origin,
+            palette: Palette::entry(text_attributes.palette_index, slot),
+            section: None,
+        };
+
+        (glyph_extra, style.map(|(_, style)| style))
+    }
+
+    /// Applies the given transform, in screen space, before the transform
+    /// of the glyphs, e.g. to place them along a path.
+    pub(crate) fn pre_transform(&mut self, transform: Affine) {
+        let (x, y) = self.origin;
+
+        let relative = Affine::translation(x, y)
+            .then(transform)
+            .then(Affine::translation(-x, -y));
+
+        self.transform =
+            Some(relative.then(self.transform.unwrap_or(Affine::IDENTITY)));
+    }
+}
+
+/// Resolves the styles and attributes of the texts of a section into the
+/// section passed to `glyph_brush`.
+pub(crate) fn glyph_section<'a>(
+    section: &RichSection<'a>,
+    styles: &Styles,
+) -> GlyphSection<'a> {
+    let text = section
+        .section
+        .text
+        .iter()
+        .enumerate()
+        .map(|(index, text)| {
+            let (extra, style) = GlyphExtra::resolve(
+                &text.extra,
+                &section.attributes,
+                index,
+                section.section.screen_position,
+                styles,
+            );
+
+            glyph_brush::Text {
+                text: text.text,
+                scale: style.map_or(text.scale, |style| style.scale),
+                font_id: style.map_or(text.font_id, |style| style.font_id),
+                extra,
+            }
+        })
+        .collect();
+
+    glyph_brush::Section {
+        screen_position: section.section.screen_position,
+        bounds: section.section.bounds,
+        layout: section.section.layout,
+        text,
+    }
+}
+
+/// A stroke drawn around the glyphs of a [`Text`](struct.Text.html), under
+/// their fill.
+///
+/// It keeps text readable over busy backgrounds, like subtitles over video.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Outline {
+    /// The color of the outline.
+    pub color: [f32; 4],
+    /// The width of the outline around the edges of the glyphs, in pixels.
+    ///
+    /// The outline is found by sampling the coverage of the glyphs around
+    /// every pixel, so widths of more than a few pixels may look uneven.
+    pub width: f32,
+}
+
+impl Outline {
+    /// Creates a new `Outline`.
+    pub fn new(color: [f32; 4], width: f32) -> Self {
+        Outline { color, width }
+    }
+}
+
+impl Hash for Outline {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.color.map(f32::to_bits).hash(state);
+        self.width.to_bits().hash(state);
+    }
+}
+
+/// A blurred copy of the glyphs of a section, drawn under all the text of a
+/// frame.
+///
+/// A shadow without offset surrounds the glyphs, giving them a glow.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Shadow {
+    /// The offset of the shadow from the glyphs, in pixels.
+    pub offset: (f32, f32),
+    /// The distance the shadow is blurred over, in pixels.
+    pub blur_radius: f32,
+    /// The color of the shadow.
+    pub color: [f32; 4],
+}
+
+impl Shadow {
+    /// Creates a new `Shadow`.
+    pub fn new(offset: (f32, f32), blur_radius: f32, color: [f32; 4]) -> Self {
+        Shadow {
+            offset,
+            blur_radius,
+            color,
+        }
+    }
+
+    /// Creates a `Shadow` without offset, glowing around the glyphs.
+    pub fn glow(blur_radius: f32, color: [f32; 4]) -> Self {
+        Shadow::new((0.0, 0.0), blur_radius, color)
+    }
+}
+
+impl Hash for Shadow {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.offset.0.to_bits().hash(state);
+        self.offset.1.to_bits().hash(state);
+        self.blur_radius.to_bits().hash(state);
+        self.color.map(f32::to_bits).hash(state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Transforms a point like the vertex shader, around the origin.
+    fn draw(extra: &GlyphExtra, (x, y): (f32, f32)) -> (f32, f32) {
+        let (origin_x, origin_y) = extra.origin;
+        let transform = extra.transform.unwrap_or(Affine::IDENTITY);
+        let (x, y) = transform.transform_point((x - origin_x, y - origin_y));
+
+        (x + origin_x, y + origin_y)
+    }
+
+    fn assert_near((x, y): (f32, f32), (expected_x, expected_y): (f32, f32)) {
+        assert!(
+            (x - expected_x).abs() < 1e-4 && (y - expected_y).abs() < 1e-4,
+            "{:?} != {:?}",
+            (x, y),
+            (expected_x, expected_y)
+        );
+    }
+
+    #[test]
+    fn pre_transform_applies_before_the_transform_of_the_section() {
+        let rotation = Affine::rotation(std::f32::consts::FRAC_PI_2);
+        let placement = Affine::translation(5.0, -3.0);
+
+        let mut extra = GlyphExtra {
+            transform: Some(rotation),
+            origin: (100.0, 50.0),
+            ..GlyphExtra::default()
+        };
+
+        extra.pre_transform(placement);
+
+        let point = (110.0, 60.0);
+        let expected = rotation
+            .around(extra.origin)
+            .transform_point(placement.transform_point(point));
+
+        assert_near(draw(&extra, point), expected);
+        assert_near(expected, (93.0, 65.0));
+    }
+
+    fn hash(extra: &GlyphExtra) -> u64 {
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        extra.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn moving_a_section_keeps_the_hash_of_its_extras() {
+        let extra = GlyphExtra {
+            transform: Some(Affine::scale(2.0, 2.0)),
+            ..GlyphExtra::default()
+        };
+        let moved = GlyphExtra {
+            origin: (10.0, 20.0),
+            ..extra
+        };
+
+        assert_eq!(hash(&extra), hash(&moved));
+    }
+
+    #[test]
+    fn changing_the_color_of_a_style_keeps_the_hash_of_its_extras() {
+        use crate::{FontId, Section, Text, TextAttributes};
+
+        let mut styles = Styles::new();
+        let section = RichSection::new(
+            Section::default()
+                .add_text(Text::new("styled").with_color([0.0, 1.0, 0.0, 1.0])),
+            SectionAttributes::default()
+                .with_text(0, TextAttributes::default().with_style("title")),
+        );
+        let extra =
+            |styles: &Styles| glyph_section(&section, styles).text[0].extra;
+
+        let id = styles.define(
+            String::from("title"),
+            TextStyle::new(FontId(0), 20.0, [1.0, 0.0, 0.0, 1.0]),
+        );
+        let red = extra(&styles);
+
+        styles.set(id, TextStyle::new(FontId(0), 20.0, [0.0, 0.0, 1.0, 1.0]));
+        let blue = extra(&styles);
+
+        // The color is read from the palette entry of the style
+        assert_eq!(red.palette, Styles::slot(id).unwrap());
+        assert_eq!(red.color, [0.0, 1.0, 0.0, 1.0]);
+        assert_eq!(hash(&red), hash(&blue));
+    }
+
+    #[test]
+    fn styles_without_a_slot_are_drawn_with_their_color() {
+        use crate::{FontId, Section, Text, TextAttributes};
+
+        let mut styles = Styles::new();
+
+        for index in 0..=Styles::SLOTS {
+            let _ = styles.define(index.to_string(), TextStyle::default());
+        }
+
+        let last = Styles::SLOTS.to_string();
+        let color = [0.5, 0.5, 0.5, 1.0];
+        let _ =
+            styles.define(last.clone(), TextStyle::new(FontId(0), 16.0, color));
+
+        let section = RichSection::new(
+            Section::default().add_text(Text::new("styled")),
+            SectionAttributes::default()
+                .with_text(0, TextAttributes::default().with_style(last)),
+        );
+        let extra = glyph_section(&section, &styles).text[0].extra;
+
+        assert_eq!(extra.palette, 0);
+        assert_eq!(extra.color, color);
+    }
+}
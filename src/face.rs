@@ -0,0 +1,97 @@
+use glyph_brush::ab_glyph::Font;
+
+/// Parses the face of the given font with `ttf_parser`, picking the right
+/// face of a font collection.
+pub(crate) fn parse<F: Font>(font: &F) -> Option<ttf_parser::Face<'_>> {
+    let data = font.font_data();
+    let count = ttf_parser::fonts_in_collection(data).unwrap_or(1);
+
+    (0..count)
+        .filter_map(|index| ttf_parser::Face::parse(data, index).ok())
+        .find(|face| {
+            usize::from(face.number_of_glyphs()) == font.glyph_count()
+                && f32::from(face.ascender()) == font.ascent_unscaled()
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::INCONSOLATA;
+
+    use glyph_brush::ab_glyph::FontRef;
+
+    const DEJAVU: &[u8] = include_bytes!("../examples/DejaVuSans.ttf");
+
+    /// Builds a font collection of the given fonts, moving the tables of
+    /// each font by the offset of the font in the collection.
+    fn collection(fonts: &[&[u8]]) -> Vec<u8> {
+        let mut data = b"ttcf\0\x01\0\0".to_vec();
+        data.extend((fonts.len() as u32).to_be_bytes());
+
+        let mut base = data.len() + 4 * fonts.len();
+
+        for font in fonts {
+            data.extend((base as u32).to_be_bytes());
+            base += font.len().next_multiple_of(4);
+        }
+
+        for font in fonts {
+            let start = data.len();
+            data.extend_from_slice(font);
+            data.resize(start + font.len().next_multiple_of(4), 0);
+
+            let tables = u16::from_be_bytes([font[4], font[5]]) as usize;
+
+            for table in 0..tables {
+                let at = start + 12 + 16 * table + 8;
+                let offset =
+                    u32::from_be_bytes(data[at..at + 4].try_into().unwrap());
+
+                data[at..at + 4]
+                    .copy_from_slice(&(offset + start as u32).to_be_bytes());
+            }
+        }
+
+        data
+    }
+
+    #[test]
+    fn single_fonts() {
+        let font = FontRef::try_from_slice(DEJAVU).unwrap();
+        let face = parse(&font).unwrap();
+
+        assert_eq!(usize::from(face.number_of_glyphs()), font.glyph_count());
+        assert!(face.glyph_index('a').is_some());
+    }
+
+    #[test]
+    fn the_face_of_the_font_is_picked_in_a_collection() {
+        let data = collection(&[INCONSOLATA, DEJAVU]);
+
+        for (index, expected) in [INCONSOLATA, DEJAVU].into_iter().enumerate() {
+            let font =
+                FontRef::try_from_slice_and_index(&data, index as u32).unwrap();
+            let expected = ttf_parser::Face::parse(expected, 0).unwrap();
+            let face = parse(&font).unwrap();
+
+            assert_eq!(face.number_of_glyphs(), expected.number_of_glyphs());
+            assert_eq!(face.ascender(), expected.ascender());
+        }
+    }
+
+    #[test]
+    fn broken_faces_of_a_collection_are_skipped() {
+        let mut data = collection(&[DEJAVU, INCONSOLATA]);
+
+        // Point the first face at the garbage past the end of the header
+        data[12..16].copy_from_slice(&4u32.to_be_bytes());
+
+        assert!(ttf_parser::Face::parse(&data, 0).is_err());
+
+        let font = FontRef::try_from_slice_and_index(&data, 1).unwrap();
+        let face = parse(&font).unwrap();
+
+        assert_eq!(usize::from(face.number_of_glyphs()), font.glyph_count());
+    }
+}
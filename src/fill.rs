@@ -0,0 +1,275 @@
+use log::{log_enabled, warn};
+
+use std::hash::{Hash, Hasher};
+
+/// How the glyphs of a [`Text`](struct.Text.html) are painted.
+///
+/// Positions are relative to the `screen_position` of the section, so a
+/// gradient spans all the glyphs of a section continuously.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Fill {
+    /// The color of the text.
+    #[default]
+    Solid,
+    /// A gradient along the line from `start` to `end`.
+    LinearGradient {
+        /// The position of the first color stop.
+        start: (f32, f32),
+        /// The position of the last color stop.
+        end: (f32, f32),
+        /// The colors of the gradient.
+        gradient: GradientId,
+    },
+    /// A gradient from `center` out to `radius`.
+    RadialGradient {
+        /// The position of the first color stop.
+        center: (f32, f32),
+        /// The distance of the last color stop from the center.
+        radius: f32,
+        /// The colors of the gradient.
+        gradient: GradientId,
+    },
+    /// The fill texture of the brush, set with
+    /// [`set_fill_texture`](struct.GlyphBrush.html#method.set_fill_texture)
+    /// and repeated in every direction.
+    Texture {
+        /// The position of the top left corner of the texture.
+        origin: (f32, f32),
+        /// The size of the texture, in pixels.
+        size: (f32, f32),
+    },
+}
+
+impl Hash for Fill {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+
+        let bits = |(x, y): (f32, f32)| [x.to_bits(), y.to_bits()];
+
+        match self {
+            Fill::Solid => {}
+            Fill::LinearGradient {
+                start,
+                end,
+                gradient,
+            } => {
+                bits(*start).hash(state);
+                bits(*end).hash(state);
+                gradient.hash(state);
+            }
+            Fill::RadialGradient {
+                center,
+                radius,
+                gradient,
+            } => {
+                bits(*center).hash(state);
+                radius.to_bits().hash(state);
+                gradient.hash(state);
+            }
+            Fill::Texture { origin, size } => {
+                bits(*origin).hash(state);
+                bits(*size).hash(state);
+            }
+        }
+    }
+}
+
+/// A handle to the color stops of a gradient added to a brush with
+/// [`add_gradient`](struct.GlyphBrush.html#method.add_gradient).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GradientId(u32);
+
+/// A color at a position of a gradient.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ColorStop {
+    /// The position of the stop, from `0.0` at the start of the gradient to
+    /// `1.0` at its end.
+    pub offset: f32,
+    /// The color at the stop.
+    pub color: [f32; 4],
+}
+
+impl ColorStop {
+    /// Creates a new `ColorStop`.
+    pub fn new(offset: f32, color: [f32; 4]) -> Self {
+        ColorStop { offset, color }
+    }
+}
+
+impl Hash for ColorStop {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.offset.to_bits().hash(state);
+        self.color.map(f32::to_bits).hash(state);
+    }
+}
+
+/// The color stops of the gradients of a brush, stored in the slots of a
+/// uniform buffer.
+///
+/// Glyphs refer to the slot of their gradient, so replacing its stops does
+/// not invalidate the cached vertices of the sections using it.
+#[derive(Debug)]
+pub(crate) struct Gradients {
+    slots: Vec<Vec<ColorStop>>,
+    changed: bool,
+}
+
+impl Gradients {
+    /// The amount of gradients of a brush.
+    pub(crate) const SLOTS: usize = 64;
+
+    /// The amount of color stops of a gradient. Gradients with more stops
+    /// are truncated.
+    pub(crate) const STOPS: usize = 8;
+
+    pub(crate) fn new() -> Self {
+        Gradients {
+            slots: Vec::new(),
+            changed: true,
+        }
+    }
+
+    /// Adds a gradient with the given color stops.
+    ///
+    /// Gradients added once all the slots are taken are never drawn.
+    pub(crate) fn add(&mut self, stops: &[ColorStop]) -> GradientId {
+        let id = GradientId(self.slots.len() as u32);
+
+        if self.slots.len() == Self::SLOTS && log_enabled!(log::Level::Warn) {
+            warn!(
+                "Too many gradients added, drawing the texts using the rest \
+                 with their color"
+            );
+        }
+
+        self.changed |= self.slots.len() < Self::SLOTS;
+        self.slots.push(stops.to_vec());
+
+        id
+    }
+
+    /// Replaces the color stops of a gradient.
+    pub(crate) fn set(&mut self, id: GradientId, stops: &[ColorStop]) {
+        if let Some(slot) = self.slots.get_mut(id.0 as usize) {
+            *slot = stops.to_vec();
+            self.changed |= id.0 < Self::SLOTS as u32;
+        }
+    }
+
+    /// Returns the slot of a gradient, if it has one.
+    pub(crate) fn slot(&self, id: GradientId) -> Option<u32> {
+        (id.0 < Self::SLOTS as u32 && (id.0 as usize) < self.slots.len())
+            .then_some(id.0)
+    }
+
+    /// Returns the contents of the uniform buffer if the slots changed
+    /// since the last call.
+    ///
+    /// The buffer holds the colors of all the stops followed by their
+    /// offsets, packed four to a vector. Slots with fewer stops repeat
+    /// their last stop.
+    pub(crate) fn take_changes(&mut self) -> Option<Vec<f32>> {
+        if !std::mem::take(&mut self.changed) {
+            return None;
+        }
+
+        let mut colors = Vec::with_capacity(Self::SLOTS * Self::STOPS * 4);
+        let mut offsets = Vec::with_capacity(Self::SLOTS * Self::STOPS);
+
+        for index in 0..Self::SLOTS {
+            let mut stops: Vec<ColorStop> =
+                self.slots.get(index).cloned().unwrap_or_default();
+
+            stops.truncate(Self::STOPS);
+            stops.sort_by(|a, b| a.offset.total_cmp(&b.offset));
+
+            let last = stops
+                .last()
+                .copied()
+                .unwrap_or(ColorStop::new(0.0, [0.0; 4]));
+
+            for index in 0..Self::STOPS {
+                let stop = stops.get(index).copied().unwrap_or(last);
+
+                colors.extend_from_slice(&stop.color);
+                offsets.push(stop.offset);
+            }
+        }
+
+        colors.extend(offsets);
+
+        Some(colors)
+    }
+
+    /// The size of the uniform buffer, in bytes.
+    pub(crate) const fn size() -> u64 {
+        (Self::SLOTS * Self::STOPS * 5 * std::mem::size_of::<f32>()) as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED: [f32; 4] = [1.0, 0.0, 0.0, 1.0];
+    const BLUE: [f32; 4] = [0.0, 0.0, 1.0, 1.0];
+
+    #[test]
+    fn packs_sorted_stops_and_repeats_the_last_one() {
+        let mut gradients = Gradients::new();
+        let id = gradients
+            .add(&[ColorStop::new(1.0, BLUE), ColorStop::new(0.0, RED)]);
+
+        assert_eq!(gradients.slot(id), Some(0));
+
+        let buffer = gradients.take_changes().unwrap();
+        let offsets = Gradients::SLOTS * Gradients::STOPS * 4;
+
+        assert_eq!(buffer.len() * 4, Gradients::size() as usize);
+        assert_eq!(buffer[..4], RED);
+        assert_eq!(buffer[4..8], BLUE);
+        assert_eq!(buffer[28..32], BLUE);
+        assert_eq!(buffer[offsets..offsets + 3], [0.0, 1.0, 1.0]);
+        assert_eq!(gradients.take_changes(), None);
+    }
+
+    #[test]
+    fn replaces_stops_in_place() {
+        let mut gradients = Gradients::new();
+        let first = gradients.add(&[ColorStop::new(0.0, RED)]);
+        let second = gradients.add(&[ColorStop::new(0.0, RED)]);
+        let _ = gradients.take_changes();
+
+        gradients.set(second, &[ColorStop::new(0.0, BLUE)]);
+
+        let buffer = gradients.take_changes().unwrap();
+        let stride = Gradients::STOPS * 4;
+
+        assert_eq!(gradients.slot(first), Some(0));
+        assert_eq!(gradients.slot(second), Some(1));
+        assert_eq!(buffer[..4], RED);
+        assert_eq!(buffer[stride..stride + 4], BLUE);
+    }
+
+    #[test]
+    fn gradients_past_the_slots_have_none() {
+        let mut gradients = Gradients::new();
+        let ids: Vec<GradientId> = (0..=Gradients::SLOTS)
+            .map(|_| gradients.add(&[ColorStop::new(0.0, RED)]))
+            .collect();
+        let _ = gradients.take_changes();
+
+        assert_eq!(gradients.slot(ids[Gradients::SLOTS - 1]), Some(63));
+        assert_eq!(gradients.slot(ids[Gradients::SLOTS]), None);
+        assert_eq!(
+            gradients.slot(GradientId(Gradients::SLOTS as u32 + 5)),
+            None
+        );
+
+        gradients.set(ids[Gradients::SLOTS], &[ColorStop::new(0.0, BLUE)]);
+
+        assert_eq!(gradients.take_changes(), None);
+    }
+}
@@ -0,0 +1,707 @@
+use crate::extra::GlyphSection;
+use crate::pipeline::Quad;
+use crate::{SectionAttributes, SectionGlyph};
+
+use glyph_brush::ab_glyph::{Rect, point};
+use log::{log_enabled, warn};
+
+use std::hash::{Hash, Hasher};
+use std::ops::Range;
+
+/// A box laid out in the flow of a section in place of the text of a
+/// [`Text`](struct.Text.html), like an icon or an emote.
+///
+/// The box is placed like a word: lines wrap around it, and it sticks to
+/// the text next to it unless there is a break opportunity in between. Its
+/// rectangle is returned by
+/// [`inline_rects`](struct.GlyphBrush.html#method.inline_rects), and an
+/// image added with [`add_image`](struct.GlyphBrush.html#method.add_image)
+/// can be drawn in it.
+///
+/// The text of the `Text` is not drawn, and may be empty. Use
+/// [`InlineObject::TEXT`](#associatedconstant.TEXT), so cursors and hit
+/// testing see a single character.
+///
+/// ```
+/// use wgpu_glyph::{
+///     GlyphBrush, InlineObject, RichSection, Section, SectionAttributes,
+///     Text, TextAttributes,
+/// };
+///
+/// fn message(brush: &mut GlyphBrush<()>, smiley: &[u8]) {
+///     let image = brush.add_image(16, 16, smiley);
+///
+///     brush.queue(RichSection::new(
+///         Section::default()
+///             .add_text(Text::new("Nice work "))
+///             .add_text(Text::new(InlineObject::TEXT)),
+///         SectionAttributes::default().with_text(
+///             1,
+///             TextAttributes::default().with_inline_object(
+///                 InlineObject::new(16.0, 16.0)
+///                     .with_baseline_offset(3.0)
+///                     .with_image(image),
+///             ),
+///         ),
+///     ));
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InlineObject {
+    /// The width of the box, in pixels.
+    pub width: f32,
+    /// The height of the box, in pixels.
+    pub height: f32,
+    /// How far the bottom of the box is placed below the baseline, in
+    /// pixels. Defaults to zero, sitting the box on the baseline.
+    pub baseline_offset: f32,
+    /// The image drawn in the box, if any.
+    pub image: Option<ImageId>,
+    /// Whether the image is multiplied by the color of the text. Defaults
+    /// to `false`, drawing the image as is.
+    pub tinted: bool,
+}
+
+impl InlineObject {
+    /// The object replacement character, the text to give to the `Text` of
+    /// an inline object.
+    pub const TEXT: &'static str = "\u{FFFC}";
+
+    /// Creates an `InlineObject` of the given size, sitting on the baseline.
+    pub fn new(width: f32, height: f32) -> Self {
+        InlineObject {
+            width,
+            height,
+            baseline_offset: 0.0,
+            image: None,
+            tinted: false,
+        }
+    }
+
+    /// Moves the bottom of the box below the baseline by the given amount,
+    /// in pixels.
+    pub fn with_baseline_offset(mut self, baseline_offset: f32) -> Self {
+        self.baseline_offset = baseline_offset;
+        self
+    }
+
+    /// Draws the given image in the box, stretched to its size.
+    pub fn with_image(mut self, image: ImageId) -> Self {
+        self.image = Some(image);
+        self
+    }
+
+    /// Multiplies the image by the color of the text, like a glyph.
+    pub fn tinted(mut self) -> Self {
+        self.tinted = true;
+        self
+    }
+
+    /// Returns the rectangle of the object laid out with the given origin
+    /// on the baseline.
+    pub(crate) fn rect(&self, x: f32, baseline: f32) -> Rect {
+        let bottom = baseline + self.baseline_offset;
+
+        Rect {
+            min: point(x, bottom - self.height),
+            max: point(x + self.width, bottom),
+        }
+    }
+}
+
+impl Eq for InlineObject {}
+
+impl Hash for InlineObject {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.width.to_bits().hash(state);
+        self.height.to_bits().hash(state);
+        self.baseline_offset.to_bits().hash(state);
+        self.image.hash(state);
+        self.tinted.hash(state);
+    }
+}
+
+/// A handle to an image added to a brush with
+/// [`add_image`](struct.GlyphBrush.html#method.add_image).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ImageId(u32);
+
+/// Returns whether any `Text` of the section is an inline object.
+pub(crate) fn has_objects(attributes: &SectionAttributes) -> bool {
+    attributes
+        .texts
+        .iter()
+        .any(|text| text.inline_object.is_some())
+}
+
+/// Returns the text index and the rectangle of the inline objects of a
+/// section from its glyphs, as returned by
+/// [`GlyphCruncher::glyphs`](trait.GlyphCruncher.html#method.glyphs).
+pub(crate) fn rects(
+    attributes: &SectionAttributes,
+    glyphs: &[SectionGlyph],
+) -> Vec<(usize, Rect)> {
+    glyphs
+        .iter()
+        .filter_map(|sg| {
+            let object = attributes.text(sg.section_index).inline_object?;
+            let position = sg.glyph.position;
+
+            Some((sg.section_index, object.rect(position.x, position.y)))
+        })
+        .collect()
+}
+
+/// Grows the bounds of the glyphs of a section to contain its inline
+/// objects, capped to the bounds of its layout.
+pub(crate) fn bounds(
+    attributes: &SectionAttributes,
+    glyphs: &[SectionGlyph],
+    glyph_bounds: Option<Rect>,
+    layout_bounds: Rect,
+) -> Option<Rect> {
+    rects(attributes, glyphs)
+        .into_iter()
+        .map(|(_, rect)| rect)
+        .chain(glyph_bounds)
+        .reduce(|a, b| Rect {
+            min: point(a.min.x.min(b.min.x), a.min.y.min(b.min.y)),
+            max: point(a.max.x.max(b.max.x), a.max.y.max(b.max.y)),
+        })
+        .map(|rect| Rect {
+            min: point(
+                rect.min.x.max(layout_bounds.min.x),
+                rect.min.y.max(layout_bounds.min.y),
+            ),
+            max: point(
+                rect.max.x.min(layout_bounds.max.x),
+                rect.max.y.min(layout_bounds.max.y),
+            ),
+        })
+}
+
+/// Builds the quads of the images of the inline objects of a section,
+/// clipped to `bounds`.
+pub(crate) fn quads(
+    images: &Images,
+    section: &GlyphSection<'_>,
+    attributes: &SectionAttributes,
+    glyphs: &[SectionGlyph],
+    bounds: Rect,
+    out: &mut Vec<Quad>,
+) {
+    for (text_index, rect) in rects(attributes, glyphs) {
+        let extra = &section.text[text_index].extra;
+
+        let Some(object) = attributes.text(text_index).inline_object else {
+            continue;
+        };
+
+        let Some(tex_coords) = object.image.and_then(|id| images.get(id))
+        else {
+            continue;
+        };
+
+        let color = if object.tinted { extra.color } else { [1.0; 4] };
+
+        let instance =
+            Quad::image(rect, tex_coords, bounds, extra.z, color)
+                .map(|instance| instance.transformed(extra));
+
+        out.extend(instance.map(|instance| {
+            if object.tinted {
+                instance.paletted(extra)
+            } else {
+                instance
+            }
+        }));
+    }
+}
+
+/// The images of a brush, packed in rows of an atlas texture.
+///
+/// The atlas doubles in size when an image does not fit, up to a maximum
+/// size, and the room of removed images is reused.
+#[derive(Debug)]
+pub(crate) struct Images {
+    /// The area of every image in the atlas, or `None` if it did not fit or
+    /// was removed.
+    areas: Vec<Option<Area>>,
+    rows: Vec<Row>,
+    /// The width and height of the atlas, in pixels.
+    size: u32,
+    max_size: u32,
+    /// Whether the atlas grew since the last uploads.
+    resized: bool,
+    uploads: Vec<Upload>,
+}
+
+/// The area of an image in the atlas, in pixels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Area {
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+}
+
+/// A row of the atlas, with the free spans of its width.
+#[derive(Debug)]
+struct Row {
+    top: u32,
+    height: u32,
+    free: Vec<Range<u32>>,
+}
+
+/// The pixels of an image waiting to be copied to the atlas.
+#[derive(Debug)]
+pub(crate) struct Upload {
+    pub offset: [u16; 2],
+    pub size: [u16; 2],
+    pub data: Vec<u8>,
+}
+
+impl Images {
+    /// The width and height of a new atlas, in pixels.
+    pub(crate) const INITIAL_SIZE: u32 = 1024;
+
+    /// Creates an empty atlas, growing up to the given size.
+    pub(crate) fn new(max_size: u32) -> Self {
+        // Uploads are placed with 16-bit offsets
+        let max_size = max_size.min(u32::from(u16::MAX));
+
+        Images {
+            areas: Vec::new(),
+            rows: Vec::new(),
+            size: Self::INITIAL_SIZE.min(max_size),
+            max_size,
+            resized: false,
+            uploads: Vec::new(),
+        }
+    }
+
+    /// Adds an image with the given RGBA pixels to the atlas, or an image
+    /// drawing nothing if it cannot be stored.
+    pub(crate) fn add(
+        &mut self,
+        width: u32,
+        height: u32,
+        rgba: &[u8],
+    ) -> ImageId {
+        self.try_add(width, height, rgba).unwrap_or_else(|reason| {
+            if log_enabled!(log::Level::Warn) {
+                warn!("Skipping image: {reason}");
+            }
+
+            self.areas.push(None);
+
+            ImageId(self.areas.len() as u32 - 1)
+        })
+    }
+
+    /// Adds an image with the given RGBA pixels to the atlas, or returns
+    /// why it cannot be stored.
+    pub(crate) fn try_add(
+        &mut self,
+        width: u32,
+        height: u32,
+        rgba: &[u8],
+    ) -> Result<ImageId, String> {
+        let size = (width as usize) * (height as usize) * 4;
+
+        if rgba.len() < size {
+            return Err(format!(
+                "image of {width}x{height} pixels needs {size} bytes, got {}",
+                rgba.len()
+            ));
+        }
+
+        let area = self.allocate(width, height).ok_or_else(|| {
+            format!(
+                "image of {width}x{height} pixels does not fit in the image \
+                 atlas of at most {max}x{max} pixels",
+                max = self.max_size,
+            )
+        })?;
+
+        self.uploads.push(Upload {
+            offset: [area.x as u16, area.y as u16],
+            size: [width as u16, height as u16],
+            data: rgba[..size].to_vec(),
+        });
+        self.areas.push(Some(area));
+
+        Ok(ImageId(self.areas.len() as u32 - 1))
+    }
+
+    /// Removes an image from the atlas, freeing its room for the images
+    /// added after it.
+    pub(crate) fn remove(&mut self, id: ImageId) {
+        let Some(area) =
+            self.areas.get_mut(id.0 as usize).and_then(Option::take)
+        else {
+            return;
+        };
+
+        if let Some(row) = self.rows.iter_mut().find(|row| row.top == area.y) {
+            row.release(area.x..area.x + area.width);
+        }
+
+        // Trailing empty rows give their height back to the next rows
+        while let Some(row) = self.rows.last()
+            && row.free.first() == Some(&(0..self.size))
+        {
+            let _ = self.rows.pop();
+        }
+    }
+
+    /// Returns the texture coordinates of an image in the atlas, in pixels.
+    pub(crate) fn get(&self, id: ImageId) -> Option<Rect> {
+        let area = self.areas.get(id.0 as usize).copied().flatten()?;
+
+        Some(Rect {
+            min: point(area.x as f32, area.y as f32),
+            max: point(
+                (area.x + area.width) as f32,
+                (area.y + area.height) as f32,
+            ),
+        })
+    }
+
+    /// Returns the new size of the atlas, if it grew since the last call.
+    pub(crate) fn take_resize(&mut self) -> Option<u32> {
+        std::mem::take(&mut self.resized).then_some(self.size)
+    }
+
+    /// Returns the images added since the last call.
+    pub(crate) fn take_uploads(&mut self) -> Vec<Upload> {
+        std::mem::take(&mut self.uploads)
+    }
+
+    /// Finds room for an image, growing the atlas until it fits.
+    fn allocate(&mut self, width: u32, height: u32) -> Option<Area> {
+        if width == 0 || height == 0 {
+            return None;
+        }
+
+        loop {
+            if let Some(area) = self.place(width, height) {
+                return Some(area);
+            }
+
+            if self.size * 2 > self.max_size {
+                return None;
+            }
+
+            self.grow();
+        }
+    }
+
+    /// Finds room for an image in the shortest row tall enough, opening a
+    /// new row if needed.
+    fn place(&mut self, width: u32, height: u32) -> Option<Area> {
+        let row = self
+            .rows
+            .iter_mut()
+            .filter(|row| row.height >= height)
+            .filter_map(|row| Some((row.take(width)?, row)))
+            .min_by_key(|(_, row)| row.height);
+
+        if let Some((x, row)) = row {
+            return Some(Area {
+                x,
+                y: row.top,
+                width,
+                height,
+            });
+        }
+
+        let top = self.rows.last().map_or(0, |row| row.top + row.height);
+
+        if width > self.size || top + height > self.size {
+            return None;
+        }
+
+        let mut row = Row {
+            top,
+            height,
+            free: Vec::new(),
+        };
+
+        row.release(width..self.size);
+        self.rows.push(row);
+
+        Some(Area {
+            x: 0,
+            y: top,
+            width,
+            height,
+        })
+    }
+
+    /// Doubles the size of the atlas, widening its rows.
+    fn grow(&mut self) {
+        let size = self.size;
+
+        for row in &mut self.rows {
+            row.release(size..size * 2);
+        }
+
+        self.size *= 2;
+        self.resized = true;
+    }
+}
+
+impl Row {
+    /// Takes the first free span of the given width, returning its start.
+    fn take(&mut self, width: u32) -> Option<u32> {
+        let span = self
+            .free
+            .iter_mut()
+            .find(|span| span.len() >= width as usize)?;
+        let x = span.start;
+
+        span.start += width;
+        self.free.retain(|span| !span.is_empty());
+
+        Some(x)
+    }
+
+    /// Frees a span, merging it with the free spans next to it.
+    fn release(&mut self, span: Range<u32>) {
+        if span.is_empty() {
+            return;
+        }
+
+        let index = self.free.partition_point(|free| free.end < span.start);
+
+        match self.free.get_mut(index) {
+            Some(next) if next.start <= span.end => {
+                next.start = next.start.min(span.start);
+                next.end = next.end.max(span.end);
+            }
+            _ => self.free.insert(index, span),
+        }
+
+        // The span may now touch the next free span
+        if index + 1 < self.free.len()
+            && self.free[index].end == self.free[index + 1].start
+        {
+            let next = self.free.remove(index + 1);
+            self.free[index].end = next.end;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FontId, TextAttributes};
+
+    use glyph_brush::ab_glyph::{Glyph, GlyphId};
+
+    fn glyph(section_index: usize, x: f32, y: f32) -> SectionGlyph {
+        SectionGlyph {
+            section_index,
+            byte_index: 0,
+            glyph: Glyph {
+                id: GlyphId(0),
+                scale: 16.0.into(),
+                position: point(x, y),
+            },
+            font_id: FontId(0),
+        }
+    }
+
+    fn rect(min: (f32, f32), max: (f32, f32)) -> Rect {
+        Rect {
+            min: point(min.0, min.1),
+            max: point(max.0, max.1),
+        }
+    }
+
+    #[test]
+    fn objects_sit_on_the_baseline() {
+        let object = InlineObject::new(20.0, 10.0);
+
+        assert_eq!(object.rect(5.0, 30.0), rect((5.0, 20.0), (25.0, 30.0)));
+        assert_eq!(
+            object.with_baseline_offset(4.0).rect(5.0, 30.0),
+            rect((5.0, 24.0), (25.0, 34.0))
+        );
+    }
+
+    #[test]
+    fn rects_and_bounds_of_objects() {
+        let attributes = SectionAttributes::default().with_text(
+            1,
+            TextAttributes::default()
+                .with_inline_object(InlineObject::new(20.0, 30.0)),
+        );
+        let glyphs = [glyph(0, 0.0, 20.0), glyph(1, 10.0, 20.0)];
+
+        assert!(has_objects(&attributes));
+        assert!(!has_objects(&SectionAttributes::default()));
+        assert_eq!(
+            rects(&attributes, &glyphs),
+            [(1, rect((10.0, -10.0), (30.0, 20.0)))]
+        );
+
+        let glyph_bounds = rect((0.0, 8.0), (10.0, 24.0));
+        let unbounded = rect((-100.0, -100.0), (100.0, 100.0));
+
+        // The object is taller than the glyphs
+        assert_eq!(
+            bounds(&attributes, &glyphs, Some(glyph_bounds), unbounded),
+            Some(rect((0.0, -10.0), (30.0, 24.0)))
+        );
+
+        // It is clipped to the bounds of the layout
+        assert_eq!(
+            bounds(
+                &attributes,
+                &glyphs,
+                Some(glyph_bounds),
+                rect((0.0, 0.0), (25.0, 100.0)),
+            ),
+            Some(rect((0.0, 0.0), (25.0, 24.0)))
+        );
+
+        // A section made of an object has bounds
+        assert_eq!(
+            bounds(&attributes, &glyphs[1..], None, unbounded),
+            Some(rect((10.0, -10.0), (30.0, 20.0)))
+        );
+        assert_eq!(
+            bounds(&SectionAttributes::default(), &glyphs, None, unbounded),
+            None
+        );
+    }
+
+    #[test]
+    fn images_are_packed_in_rows() {
+        let mut images = Images::new(4096);
+
+        let a = images.add(100, 50, &[255; 100 * 50 * 4]);
+        let b = images.add(200, 20, &[255; 200 * 20 * 4]);
+        let c = images.add(10, 80, &[255; 10 * 80 * 4]);
+
+        // The second image fits in the row of the first one, and the third
+        // one is too tall for it
+        assert_eq!(images.get(a), Some(rect((0.0, 0.0), (100.0, 50.0))));
+        assert_eq!(images.get(b), Some(rect((100.0, 0.0), (300.0, 20.0))));
+        assert_eq!(images.get(c), Some(rect((0.0, 50.0), (10.0, 130.0))));
+
+        let uploads = images.take_uploads();
+
+        assert_eq!(uploads.len(), 3);
+        assert_eq!(uploads[1].offset, [100, 0]);
+        assert_eq!(uploads[1].size, [200, 20]);
+        assert_eq!(uploads[1].data.len(), 200 * 20 * 4);
+        assert!(images.take_uploads().is_empty());
+        assert_eq!(images.take_resize(), None);
+    }
+
+    #[test]
+    fn invalid_images_are_skipped() {
+        let mut images = Images::new(2048);
+
+        let short = images.add(10, 10, &[0; 10 * 10 * 4 - 1]);
+        let empty = images.add(0, 10, &[]);
+        let wide = images.add(2049, 1, &[0; 2049 * 4]);
+
+        assert_eq!(images.get(short), None);
+        assert_eq!(images.get(empty), None);
+        assert_eq!(images.get(wide), None);
+        assert_eq!(images.get(ImageId(100)), None);
+        assert!(images.take_uploads().is_empty());
+
+        // Images that cannot be stored are errors without an id
+        assert!(images.try_add(10, 10, &[0; 4]).is_err());
+        assert!(images.try_add(2049, 1, &[0; 2049 * 4]).is_err());
+        assert_eq!(images.add(1, 1, &[0; 4]), ImageId(3));
+        let _ = images.take_uploads();
+
+        // Extra bytes are ignored
+        let long = images.add(1, 1, &[0; 8]);
+
+        assert!(images.get(long).is_some());
+        assert_eq!(images.take_uploads()[0].data.len(), 4);
+    }
+
+    #[test]
+    fn atlases_grow_up_to_their_maximum_size() {
+        let mut images = Images::new(2048);
+        let pixels = vec![0; 1024 * 600 * 4];
+
+        let first = images.add(1024, 600, &pixels);
+        assert_eq!(images.take_resize(), None);
+
+        // The atlas doubles, and the row of the first image widens
+        let second = images.add(1024, 600, &pixels);
+        let third = images.add(1024, 600, &pixels);
+
+        assert_eq!(images.take_resize(), Some(2048));
+        assert_eq!(images.take_resize(), None);
+        assert_eq!(images.get(first), Some(rect((0.0, 0.0), (1024.0, 600.0))));
+        assert_eq!(
+            images.get(second),
+            Some(rect((1024.0, 0.0), (2048.0, 600.0)))
+        );
+        assert_eq!(
+            images.get(third),
+            Some(rect((0.0, 600.0), (1024.0, 1200.0)))
+        );
+
+        // It cannot grow further
+        let tall = vec![0; 2048 * 900 * 4];
+
+        let tall = images.add(2048, 900, &tall);
+
+        assert_eq!(images.get(tall), None);
+        assert_eq!(images.take_resize(), None);
+    }
+
+    #[test]
+    fn removed_images_free_their_room() {
+        let mut images = Images::new(1024);
+        let pixels = vec![0; 512 * 512 * 4];
+
+        let a = images.add(512, 512, &pixels);
+        let b = images.add(512, 512, &pixels);
+        let c = images.add(512, 512, &pixels);
+        let d = images.add(512, 512, &pixels);
+        let full = images.add(512, 512, &pixels);
+
+        assert_eq!(images.get(full), None);
+
+        // The room of a removed image is reused, and its id draws nothing
+        images.remove(a);
+        images.remove(a);
+
+        let e = images.add(512, 512, &pixels);
+
+        assert_eq!(images.get(a), None);
+        assert_eq!(images.get(e), Some(rect((0.0, 0.0), (512.0, 512.0))));
+
+        // Free spans merge, so a row emptied in the middle takes wider images
+        images.remove(e);
+        images.remove(b);
+
+        let wide = vec![0; 1024 * 100 * 4];
+        let f = images.add(1024, 100, &wide);
+
+        assert_eq!(images.get(f), Some(rect((0.0, 0.0), (1024.0, 100.0))));
+
+        // Emptied rows at the bottom give their height back
+        images.remove(f);
+        images.remove(c);
+        images.remove(d);
+
+        let pixels = vec![0; 1024 * 1024 * 4];
+        let g = images.add(1024, 1024, &pixels);
+
+        assert_eq!(images.get(g), Some(rect((0.0, 0.0), (1024.0, 1024.0))));
+    }
+}
@@ -0,0 +1,130 @@
+/// How a [`TextLayout`](struct.TextLayout.html) stretches lines to fill the
+/// width of the bounds of a section.
+///
+/// Every line is justified except the last line of each paragraph, which
+/// keeps the `HorizontalAlign` of the layout. Sections without a width bound
+/// are not justified.
+///
+/// ```
+/// use wgpu_glyph::{Justification, Layout, TextLayout};
+///
+/// let layout = TextLayout::new(Layout::default_wrap())
+///     .with_justification(Justification::InterWordOrCharacter);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Justification {
+    /// The extra space is spread across the gaps between words. Lines
+    /// without gaps are left as they are.
+    #[default]
+    InterWord,
+    /// Like `InterWord`, but the extra space of lines without gaps, like a
+    /// single long word, is spread between their characters.
+    InterWordOrCharacter,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{advance, assert_near, glyphs};
+    use crate::{Layout, Section, Text, TextLayout};
+
+    /// Lays out the text justified in bounds of the given width and returns
+    /// the left edge of the glyphs of every line.
+    fn lines(
+        justification: Justification,
+        width: f32,
+        text: &str,
+    ) -> Vec<Vec<f32>> {
+        let layout = TextLayout::new(Layout::default_wrap())
+            .with_justification(justification);
+        let section = Section::default()
+            .with_bounds((width, f32::INFINITY))
+            .add_text(Text::new(text));
+
+        let mut lines: Vec<(f32, Vec<f32>)> = Vec::new();
+
+        for sg in glyphs(&layout, &section) {
+            if text[sg.byte_index..].starts_with(char::is_whitespace) {
+                continue;
+            }
+
+            let position = sg.glyph.position;
+
+            match lines.last_mut() {
+                Some((y, line)) if *y == position.y => line.push(position.x),
+                _ => lines.push((position.y, vec![position.x])),
+            }
+        }
+
+        lines.into_iter().map(|(_, line)| line).collect()
+    }
+
+    #[test]
+    fn extra_space_goes_to_the_gaps_between_words() {
+        let w = advance();
+        let width = 9.5 * w;
+        let lines = lines(Justification::InterWord, width, "aa bb cc dd");
+        let first = &lines[0];
+
+        // The two gaps of "aa bb cc" share the space left by its characters
+        let gap = (width - 6.0 * w) / 2.0;
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(first.len(), 6);
+        assert_near(first[2], 2.0 * w + gap);
+        assert_near(first[4], 4.0 * w + 2.0 * gap);
+        assert_near(first[5] + w, width);
+
+        // The last line keeps its alignment
+        assert_eq!(lines[1], [0.0, w]);
+    }
+
+    #[test]
+    fn the_last_line_of_every_paragraph_is_not_justified() {
+        let w = advance();
+        let lines = lines(Justification::InterWord, 20.0 * w, "aa bb\ncc dd");
+
+        assert_eq!(lines, [[0.0, w, 3.0 * w, 4.0 * w]; 2]);
+    }
+
+    #[test]
+    fn lines_without_gaps() {
+        let w = advance();
+        let width = 4.5 * w;
+
+        // Every line holds a single word, so its only gap is the trailing
+        // whitespace, which is not stretched
+        let words = lines(Justification::InterWord, width, "abcd efgh ij");
+
+        assert_eq!(words.len(), 3);
+        assert_eq!(words[0], [0.0, w, 2.0 * w, 3.0 * w]);
+
+        let characters =
+            lines(Justification::InterWordOrCharacter, width, "abcd efgh ij");
+        let spread = 0.5 * w / 3.0;
+
+        assert_eq!(characters.len(), 3);
+
+        for (i, x) in characters[0].iter().enumerate() {
+            assert_near(*x, i as f32 * (w + spread));
+        }
+
+        assert_near(characters[1][3] + w, width);
+        assert_eq!(characters[2], [0.0, w]);
+    }
+
+    #[test]
+    fn unbounded_sections_are_not_justified() {
+        let w = advance();
+
+        for justification in [
+            Justification::InterWord,
+            Justification::InterWordOrCharacter,
+        ] {
+            assert_eq!(
+                lines(justification, f32::INFINITY, "aa bb"),
+                [[0.0, w, 3.0 * w, 4.0 * w]]
+            );
+        }
+    }
+}
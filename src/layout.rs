@@ -0,0 +1,1804 @@
+use crate::bidi::{self, Level};
+use crate::spacing::Spacing;
+use crate::vertical;
+use crate::{
+    BuiltInLineBreaker, Ellipsis, EllipsisPosition, FontId, GlyphPositioner,
+    HorizontalAlign, InlineObject, Justification, Layout, LineBreak,
+    LineBreaker, LineHeight, RichSection, SectionGeometry, SectionGlyph,
+    SectionText, TabAlignment, TabStops, VerticalAlign, WritingMode,
+};
+
+#[cfg(feature = "shaping")]
+use crate::shaping::{self, Feature};
+
+use glyph_brush::GlyphChange;
+use glyph_brush::ToSectionText;
+use glyph_brush::ab_glyph::{
+    Font, Glyph, GlyphId, PxScale, Rect, ScaleFont, point,
+};
+
+/// A [`GlyphPositioner`](trait.GlyphPositioner.html) that extends a
+/// [`Layout`](enum.Layout.html) with text shaping.
+///
+/// When the `shaping` feature is enabled, every `SectionText` is shaped with
+/// the OpenType tables of its font before being broken into lines, which
+/// renders ligatures and complex scripts correctly. Otherwise, glyphs are
+/// positioned like the built-in `Layout` does.
+///
+/// Queue sections with it using
+/// [`queue_custom_layout`](struct.GlyphBrush.html#method.queue_custom_layout)
+/// and measure them with the `_custom_layout` methods of
+/// [`GlyphCruncher`](trait.GlyphCruncher.html).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TextLayout<L: LineBreaker = BuiltInLineBreaker> {
+    layout: Layout<L>,
+    ellipsis: Option<Ellipsis>,
+    justification: Option<Justification>,
+    spacing: Spacing,
+    tab_stops: Option<TabStops>,
+    writing_mode: WritingMode,
+    inline_objects: Vec<(usize, InlineObject)>,
+    #[cfg(feature = "shaping")]
+    features: Vec<Feature>,
+    #[cfg(feature = "shaping")]
+    text_features: Vec<(usize, Vec<Feature>)>,
+}
+
+impl Default for TextLayout<BuiltInLineBreaker> {
+    fn default() -> Self {
+        TextLayout::new(Layout::default())
+    }
+}
+
+impl<L: LineBreaker> From<Layout<L>> for TextLayout<L> {
+    fn from(layout: Layout<L>) -> Self {
+        TextLayout::new(layout)
+    }
+}
+
+impl<L: LineBreaker> TextLayout<L> {
+    /// Creates a `TextLayout` that wraps, aligns and breaks lines like the
+    /// given [`Layout`](enum.Layout.html).
+    pub fn new(layout: Layout<L>) -> Self {
+        TextLayout {
+            layout,
+            ellipsis: None,
+            justification: None,
+            spacing: Spacing::default(),
+            tab_stops: None,
+            writing_mode: WritingMode::default(),
+            inline_objects: Vec::new(),
+            #[cfg(feature = "shaping")]
+            features: Vec::new(),
+            #[cfg(feature = "shaping")]
+            text_features: Vec::new(),
+        }
+    }
+
+    /// Returns the underlying [`Layout`](enum.Layout.html).
+    pub fn layout(&self) -> Layout<L> {
+        self.layout
+    }
+
+    /// Truncates the text that overflows the bounds of a section with the
+    /// given [`Ellipsis`](struct.Ellipsis.html), instead of clipping it.
+    pub fn with_ellipsis(mut self, ellipsis: Ellipsis) -> Self {
+        self.ellipsis = Some(ellipsis);
+        self
+    }
+
+    /// Justifies the lines of a section to fill the width of its bounds,
+    /// except the last line of each paragraph.
+    pub fn with_justification(mut self, justification: Justification) -> Self {
+        self.justification = Some(justification);
+        self
+    }
+
+    /// Moves the text after tab characters to the given
+    /// [`TabStops`](struct.TabStops.html). Without them, tabs have no width.
+    pub fn with_tab_stops(mut self, tab_stops: TabStops) -> Self {
+        self.tab_stops = Some(tab_stops);
+        self
+    }
+
+    /// Sets the direction the lines of a section flow in.
+    pub fn with_writing_mode(mut self, writing_mode: WritingMode) -> Self {
+        self.writing_mode = writing_mode;
+        self
+    }
+
+    /// Sets the height of the lines of a section.
+    pub fn with_line_height(mut self, line_height: LineHeight) -> Self {
+        self.spacing.line_height = Some(line_height);
+        self
+    }
+
+    /// Adds space between the paragraphs of a section, in pixels.
+    pub fn with_paragraph_spacing(mut self, spacing: f32) -> Self {
+        self.spacing.paragraph_spacing = spacing;
+        self
+    }
+
+    /// Adds space after every character of every `Text` of a section, in
+    /// pixels.
+    pub fn with_letter_spacing(mut self, spacing: f32) -> Self {
+        self.spacing.letter_spacing = spacing;
+        self
+    }
+
+    /// Adds space after every character of the `Text` at the given index of
+    /// a section, in pixels, instead of the one set with
+    /// [`with_letter_spacing`](#method.with_letter_spacing).
+    pub fn with_text_letter_spacing(
+        mut self,
+        text_index: usize,
+        spacing: f32,
+    ) -> Self {
+        self.spacing
+            .text_letter_spacing
+            .retain(|(index, _)| *index != text_index);
+        self.spacing.text_letter_spacing.push((text_index, spacing));
+        self
+    }
+
+    /// Lays out the given [`InlineObject`](struct.InlineObject.html) in
+    /// place of the text of the `Text` at the given index of a section.
+    pub fn with_inline_object(
+        mut self,
+        text_index: usize,
+        object: InlineObject,
+    ) -> Self {
+        self.inline_objects
+            .retain(|(index, _)| *index != text_index);
+        self.inline_objects.push((text_index, object));
+        self
+    }
+
+    /// Sets the OpenType features toggled for every `Text` of a section.
+    #[cfg(feature = "shaping")]
+    pub fn with_features(mut self, features: impl Into<Vec<Feature>>) -> Self {
+        self.features = features.into();
+        self
+    }
+
+    /// Sets the OpenType features toggled for the `Text` at the given index
+    /// of a section, on top of the ones set with
+    /// [`with_features`](#method.with_features).
+    #[cfg(feature = "shaping")]
+    pub fn with_text_features(
+        mut self,
+        text_index: usize,
+        features: impl Into<Vec<Feature>>,
+    ) -> Self {
+        self.text_features.retain(|(index, _)| *index != text_index);
+        self.text_features.push((text_index, features.into()));
+        self
+    }
+
+    fn alignment(&self) -> (HorizontalAlign, VerticalAlign, bool) {
+        match self.layout {
+            Layout::SingleLine {
+                h_align, v_align, ..
+            } => (h_align, v_align, true),
+            Layout::Wrap {
+                h_align, v_align, ..
+            } => (h_align, v_align, false),
+        }
+    }
+
+    fn line_breaker(&self) -> L {
+        match self.layout {
+            Layout::SingleLine { line_breaker, .. }
+            | Layout::Wrap { line_breaker, .. } => line_breaker,
+        }
+    }
+
+    /// Breaks the sections into clusters, the smallest units of text that
+    /// can be laid out.
+    fn clusters<F: Font>(
+        &self,
+        fonts: &[F],
+        sections: &[SectionText<'_>],
+    ) -> Vec<Cluster> {
+        let line_breaker = self.line_breaker();
+        let mut clusters: Vec<Cluster> = Vec::new();
+
+        // Bidi levels are resolved over the text of all the sections
+        let text: String =
+            sections.iter().map(|section| section.text).collect();
+        let levels = bidi::levels(&text);
+        let mut offset = 0;
+
+        for (section_index, section) in sections.iter().enumerate() {
+            let PxScale { x, y } = section.scale;
+
+            let start = offset;
+            let section_levels = levels
+                .as_ref()
+                .map(|levels| &levels[offset..offset + section.text.len()]);
+
+            offset += section.text.len();
+
+            let object = self
+                .inline_objects
+                .iter()
+                .find(|(index, _)| *index == section_index);
+
+            // Empty texts are only laid out for their inline object
+            if x <= 0.0
+                || y <= 0.0
+                || (section.text.is_empty() && object.is_none())
+            {
+                continue;
+            }
+
+            let font = &fonts[section.font_id.0];
+            let scaled = font.as_scaled(section.scale);
+            let v_metrics = VMetrics::from(&scaled);
+            let first = clusters.len();
+
+            if let Some((_, object)) = object {
+                // A single cluster spanning the text, with an invisible
+                // glyph marking its position
+                let c = section.text.chars().next().unwrap_or('\u{FFFC}');
+
+                // An empty text takes the level of the text after it
+                let level = levels
+                    .as_ref()
+                    .and_then(|levels| levels.get(start).or(levels.last()))
+                    .copied()
+                    .unwrap_or_default();
+
+                clusters.push(Cluster {
+                    section_index,
+                    byte_index: 0,
+                    font_id: section.font_id,
+                    scale: section.scale,
+                    glyphs: vec![(scaled.glyph_id(' '), point(0.0, 0.0))],
+                    advance: object.width,
+                    v_metrics: VMetrics {
+                        ascent: v_metrics
+                            .ascent
+                            .max(object.height - object.baseline_offset),
+                        descent: v_metrics.descent.min(-object.baseline_offset),
+                        line_gap: v_metrics.line_gap,
+                    },
+                    character: c,
+                    whitespace: false,
+                    control: false,
+                    line_break: None,
+                    level,
+                    class: bidi::class(c),
+                });
+            }
+
+            let runs = match object {
+                Some(_) => Vec::new(),
+                None => level_runs(section.text, section_levels),
+            };
+
+            for (range, level) in runs {
+                let text = &section.text[range.clone()];
+
+                let right_to_left = level.level % 2 == 1;
+
+                // Right-to-left runs display mirrored characters, which the
+                // shaper picks by itself
+                let mirrored: String;
+                let text = if right_to_left && !cfg!(feature = "shaping") {
+                    mirrored = text.chars().map(bidi::mirror).collect();
+                    &mirrored
+                } else {
+                    text
+                };
+
+                for run in self.run(
+                    font,
+                    section.scale,
+                    text,
+                    right_to_left,
+                    section_index,
+                ) {
+                    let byte_index = range.start + run.cluster;
+                    let c = section.text[byte_index..].chars().next().unwrap();
+                    let control = c.is_control();
+
+                    match clusters.last_mut() {
+                        Some(cluster)
+                            if cluster.section_index == section_index
+                                && cluster.byte_index == byte_index =>
+                        {
+                            if !control {
+                                cluster.glyphs.push((
+                                    run.id,
+                                    point(
+                                        cluster.advance + run.x_offset,
+                                        -run.y_offset,
+                                    ),
+                                ));
+                                cluster.advance += run.x_advance;
+                            }
+                        }
+                        _ => clusters.push(Cluster {
+                            section_index,
+                            byte_index,
+                            font_id: section.font_id,
+                            scale: section.scale,
+                            glyphs: if control {
+                                Vec::new()
+                            } else {
+                                vec![(
+                                    run.id,
+                                    point(run.x_offset, -run.y_offset),
+                                )]
+                            },
+                            advance: if c == '\t' && self.tab_stops.is_some() {
+                                // The width of a space, until the tab is
+                                // placed on a line
+                                scaled.h_advance(scaled.glyph_id(' '))
+                            } else if control {
+                                0.0
+                            } else {
+                                run.x_advance
+                            },
+                            v_metrics,
+                            character: c,
+                            whitespace: c.is_whitespace(),
+                            control,
+                            line_break: None,
+                            level,
+                            class: bidi::class(c),
+                        }),
+                    }
+                }
+            }
+
+            // Clusters can be out of logical order after shaping
+            clusters[first..].sort_by_key(|cluster| cluster.byte_index);
+
+            let letter_spacing = self.spacing.letter_spacing(section_index);
+
+            for cluster in &mut clusters[first..] {
+                if !cluster.control {
+                    cluster.advance += letter_spacing;
+                }
+            }
+
+            let mut breaks = line_breaker.line_breaks(section.text).peekable();
+            let count = clusters.len() - first;
+
+            for index in first..clusters.len() {
+                let end = clusters
+                    .get(index + 1)
+                    .filter(|_| index + 1 - first < count)
+                    .map_or(section.text.len(), |next| next.byte_index);
+
+                while breaks.next_if(|b| b.offset() < end).is_some() {}
+
+                clusters[index].line_break =
+                    breaks.next_if(|b| b.offset() == end);
+            }
+
+            // Only break at the end of a section when its last character
+            // breaks on its own, like `\n`
+            if let Some(last) = clusters[first..].last_mut() {
+                let c = section.text[last.byte_index..].chars().last();
+
+                if last.line_break.is_some() {
+                    last.line_break =
+                        c.and_then(|c| eol_line_break(c, &line_breaker));
+                }
+            }
+        }
+
+        clusters
+    }
+
+    /// Lays out the sections in columns flowing from top to bottom, stacked
+    /// from right to left.
+    fn vertical_glyphs<F: Font>(
+        &self,
+        fonts: &[F],
+        geometry: &SectionGeometry,
+        sections: &[SectionText<'_>],
+    ) -> Vec<SectionGlyph> {
+        let SectionGeometry {
+            screen_position: (screen_x, screen_y),
+            bounds: (bound_w, bound_h),
+        } = *geometry;
+
+        let (h_align, v_align, single_line) = self.alignment();
+        let mut clusters = self.clusters(fonts, sections);
+
+        // Upright clusters advance by the vertical advance of their glyphs,
+        // or the height of their font
+        for cluster in &mut clusters {
+            if cluster.control || !vertical::is_upright(cluster.character) {
+                continue;
+            }
+
+            let scaled = fonts[cluster.font_id.0].as_scaled(cluster.scale);
+
+            let advance = cluster
+                .glyphs
+                .first()
+                .map(|(id, _)| scaled.v_advance(*id))
+                .filter(|advance| *advance > 0.0)
+                .unwrap_or_else(|| scaled.height());
+
+            cluster.advance =
+                advance + self.spacing.letter_spacing(cluster.section_index);
+        }
+
+        let words = words(&clusters);
+
+        let wrap_h = if single_line && self.ellipsis.is_some() {
+            f32::INFINITY
+        } else {
+            bound_h
+        };
+
+        let mut columns =
+            lines(&mut clusters, &words, wrap_h, self.tab_stops.as_ref());
+
+        self.space(&mut columns);
+
+        let visible = if single_line {
+            1
+        } else if self.ellipsis.is_some() {
+            // Keep the columns that fit in the bounds
+            let mut width = 0.0;
+
+            columns
+                .iter()
+                .take_while(|column| {
+                    width += column.v_metrics.height();
+                    width <= bound_w
+                })
+                .count()
+                .max(1)
+        } else {
+            columns.len()
+        };
+
+        let truncated = columns.len() > visible;
+
+        columns.truncate(visible);
+        self.ellipsize(fonts, &mut clusters, &mut columns, bound_h, truncated);
+
+        let width: f32 =
+            columns.iter().map(|column| column.v_metrics.height()).sum();
+
+        let mut right = match h_align {
+            HorizontalAlign::Left => screen_x + width,
+            HorizontalAlign::Center => screen_x + width / 2.0,
+            HorizontalAlign::Right => screen_x,
+        };
+
+        let mut out = Vec::new();
+
+        for mut column in columns {
+            if let Some(justification) = self.justification {
+                column.justify(&clusters, justification, bound_h);
+            }
+
+            let thickness = column.v_metrics.height();
+            let center = right - thickness / 2.0;
+
+            let top = match v_align {
+                VerticalAlign::Top => screen_y,
+                VerticalAlign::Center => screen_y - column.rightmost / 2.0,
+                VerticalAlign::Bottom => screen_y - column.rightmost,
+            };
+
+            for (index, y) in column.clusters {
+                let cluster = &clusters[index];
+                let font = &fonts[cluster.font_id.0];
+                let scaled = font.as_scaled(cluster.scale);
+                let upright = vertical::is_upright(cluster.character);
+                let pen = top + y;
+
+                out.extend(cluster.glyphs.iter().map(|(id, offset)| {
+                    let position = if upright {
+                        // The top of the glyph is placed by its vertical
+                        // side bearing, when the font has one
+                        let baseline = if scaled.v_advance(*id) > 0.0 {
+                            let outline_top = font.outline(*id).map_or(
+                                scaled.ascent(),
+                                |outline| {
+                                    outline.bounds.max.y
+                                        * scaled.v_scale_factor()
+                                },
+                            );
+
+                            pen + scaled.v_side_bearing(*id) + outline_top
+                        } else {
+                            pen + scaled.ascent()
+                        };
+
+                        point(
+                            center - scaled.h_advance(*id) / 2.0 + offset.x,
+                            baseline + offset.y,
+                        )
+                    } else {
+                        // Sideways glyphs are placed so a quarter turn around
+                        // the middle of their em box, on their left edge,
+                        // puts them in the column
+                        let middle = (scaled.ascent() + scaled.descent()) / 2.0;
+
+                        point(center - offset.y, pen + offset.x + middle)
+                    };
+
+                    SectionGlyph {
+                        section_index: cluster.section_index,
+                        byte_index: cluster.byte_index,
+                        glyph: Glyph {
+                            id: *id,
+                            scale: cluster.scale,
+                            position,
+                        },
+                        font_id: cluster.font_id,
+                    }
+                }));
+            }
+
+            right -= thickness;
+        }
+
+        out
+    }
+
+    /// Applies the line height and the paragraph spacing to the lines.
+    fn space(&self, lines: &mut [Line]) {
+        let last = lines.len().saturating_sub(1);
+
+        for (index, line) in lines.iter_mut().enumerate() {
+            if let Some(line_height) = self.spacing.line_height {
+                line.v_metrics = line.v_metrics.with_line_height(line_height);
+            }
+
+            if line.paragraph_end && index != last {
+                line.v_metrics.line_gap += self.spacing.paragraph_spacing;
+            }
+        }
+    }
+
+    /// Replaces the text that overflows the width bound in every line with
+    /// the ellipsis of the layout, if any, and ends the last line with it
+    /// when the lines after it were truncated.
+    fn ellipsize<F: Font>(
+        &self,
+        fonts: &[F],
+        clusters: &mut Vec<Cluster>,
+        lines: &mut [Line],
+        width_bound: f32,
+        truncated: bool,
+    ) {
+        let Some(ellipsis) = &self.ellipsis else {
+            return;
+        };
+
+        let last = lines.len().saturating_sub(1);
+
+        for (index, line) in lines.iter_mut().enumerate() {
+            let position = if line.rightmost > width_bound {
+                ellipsis.position
+            } else if truncated && index == last {
+                EllipsisPosition::End
+            } else {
+                continue;
+            };
+
+            // Clusters in reading order
+            let rtl = line.paragraph_level % 2 == 1;
+            let mut order: Vec<usize> =
+                line.clusters.iter().map(|(index, _)| *index).collect();
+
+            if rtl {
+                order.reverse();
+            }
+
+            // The first dropped cluster, or the last cluster if none is dropped
+            let dropped = |(prefix, suffix): (usize, usize)| {
+                let index = match position {
+                    EllipsisPosition::Start => {
+                        order.len().checked_sub(suffix + 1)
+                    }
+                    _ => Some(prefix),
+                };
+
+                index.and_then(|i| order.get(i)).or(order.last()).copied()
+            };
+
+            // The ellipsis takes the style of the first dropped cluster,
+            // which depends on the width of the ellipsis itself
+            let Some(mut style) =
+                dropped(cut(clusters, &order, position, width_bound))
+            else {
+                continue;
+            };
+
+            let mut mark =
+                self.ellipsis_cluster(fonts, &clusters[style], ellipsis);
+            let mut kept =
+                cut(clusters, &order, position, width_bound - mark.advance);
+
+            if let Some(first) = dropped(kept)
+                && clusters[first].section_index
+                    != clusters[style].section_index
+            {
+                style = first;
+                mark = self.ellipsis_cluster(fonts, &clusters[style], ellipsis);
+                kept =
+                    cut(clusters, &order, position, width_bound - mark.advance);
+            }
+
+            mark.byte_index = dropped(kept)
+                .map_or(mark.byte_index, |index| clusters[index].byte_index);
+
+            let (prefix, suffix) = kept;
+
+            let mut visual: Vec<usize> = order[..prefix]
+                .iter()
+                .copied()
+                .chain(Some(clusters.len()))
+                .chain(order[order.len() - suffix..].iter().copied())
+                .collect();
+
+            if rtl {
+                visual.reverse();
+            }
+
+            clusters.push(mark);
+
+            let mut x = 0.0;
+
+            line.clusters = visual
+                .into_iter()
+                .map(|index| {
+                    let left = x;
+
+                    x += clusters[index].advance;
+
+                    (index, left)
+                })
+                .collect();
+
+            line.rightmost = x;
+        }
+    }
+
+    /// Shapes the text of an ellipsis in the style of the given cluster.
+    fn ellipsis_cluster<F: Font>(
+        &self,
+        fonts: &[F],
+        style: &Cluster,
+        ellipsis: &Ellipsis,
+    ) -> Cluster {
+        let font = &fonts[style.font_id.0];
+        let mut glyphs = Vec::new();
+        let mut advance = 0.0;
+
+        for run in self.run(
+            font,
+            style.scale,
+            &ellipsis.text,
+            false,
+            style.section_index,
+        ) {
+            glyphs.push((run.id, point(advance + run.x_offset, -run.y_offset)));
+            advance += run.x_advance;
+        }
+
+        Cluster {
+            glyphs,
+            advance,
+            whitespace: false,
+            control: false,
+            line_break: None,
+            ..style.clone()
+        }
+    }
+
+    #[cfg(feature = "shaping")]
+    fn run<F: Font>(
+        &self,
+        font: &F,
+        scale: PxScale,
+        text: &str,
+        right_to_left: bool,
+        section_index: usize,
+    ) -> Vec<shaping::ShapedGlyph> {
+        let mut features = self.features.clone();
+
+        if let Some((_, text_features)) = self
+            .text_features
+            .iter()
+            .find(|(index, _)| *index == section_index)
+        {
+            features.retain(|feature| {
+                text_features.iter().all(|other| other.tag != feature.tag)
+            });
+            features.extend_from_slice(text_features);
+        }
+
+        shaping::shape(font, scale, text, right_to_left, &features)
+    }
+
+    #[cfg(not(feature = "shaping"))]
+    fn run<F: Font>(
+        &self,
+        font: &F,
+        scale: PxScale,
+        text: &str,
+        _right_to_left: bool,
+        _section_index: usize,
+    ) -> Vec<RunGlyph> {
+        let scaled = font.as_scaled(scale);
+        let mut glyphs: Vec<RunGlyph> = Vec::new();
+
+        for (index, c) in text.char_indices() {
+            let id = scaled.glyph_id(c);
+            let mut cluster = index;
+
+            if let Some(last) = glyphs.last_mut() {
+                last.x_advance += scaled.kern(last.id, id);
+
+                // Keep combining marks with their base
+                if bidi::class(c) == bidi::Class::NSM {
+                    cluster = last.cluster;
+                }
+            }
+
+            glyphs.push(RunGlyph {
+                id,
+                cluster,
+                x_advance: scaled.h_advance(id),
+                x_offset: 0.0,
+                y_offset: 0.0,
+            });
+        }
+
+        glyphs
+    }
+}
+
+impl<L: LineBreaker> GlyphPositioner for TextLayout<L> {
+    fn calculate_glyphs<F, S>(
+        &self,
+        fonts: &[F],
+        geometry: &SectionGeometry,
+        sections: &[S],
+    ) -> Vec<SectionGlyph>
+    where
+        F: Font,
+        S: ToSectionText,
+    {
+        let SectionGeometry {
+            screen_position,
+            bounds: (bound_w, bound_h),
+        } = *geometry;
+
+        let (h_align, v_align, single_line) = self.alignment();
+
+        let sections: Vec<SectionText<'_>> =
+            sections.iter().map(|s| s.to_section_text()).collect();
+
+        if self.writing_mode == WritingMode::VerticalRightToLeft {
+            return self.vertical_glyphs(fonts, geometry, &sections);
+        }
+
+        let mut clusters = self.clusters(fonts, &sections);
+        let words = words(&clusters);
+
+        // Single lines with an ellipsis are truncated instead of wrapped
+        let wrap_w = if single_line && self.ellipsis.is_some() {
+            f32::INFINITY
+        } else {
+            bound_w
+        };
+
+        let mut lines =
+            lines(&mut clusters, &words, wrap_w, self.tab_stops.as_ref());
+
+        self.space(&mut lines);
+
+        if single_line {
+            let truncated = lines.len() > 1;
+
+            lines.truncate(1);
+            self.ellipsize(
+                fonts,
+                &mut clusters,
+                &mut lines,
+                bound_w,
+                truncated,
+            );
+
+            return lines
+                .into_iter()
+                .flat_map(|line| {
+                    line.aligned_on_screen(
+                        &clusters,
+                        screen_position,
+                        h_align,
+                        v_align,
+                        bound_w,
+                        self.justification,
+                    )
+                })
+                .collect();
+        }
+
+        if self.ellipsis.is_some() {
+            // Keep the lines that fit under the bounds
+            let mut height = 0.0;
+
+            let visible = lines
+                .iter()
+                .take_while(|line| {
+                    height += line.v_metrics.height();
+                    height <= bound_h
+                })
+                .count()
+                .max(1);
+
+            let truncated = lines.len() > visible;
+
+            lines.truncate(visible);
+            self.ellipsize(
+                fonts,
+                &mut clusters,
+                &mut lines,
+                bound_w,
+                truncated,
+            );
+        }
+
+        let mut out = Vec::new();
+        let mut caret = screen_position;
+
+        for line in lines {
+            // Top alignment can bound check & exit early
+            if v_align == VerticalAlign::Top
+                && caret.1 >= screen_position.1 + bound_h
+            {
+                break;
+            }
+
+            let line_height = line.v_metrics.height();
+
+            out.extend(line.aligned_on_screen(
+                &clusters,
+                caret,
+                h_align,
+                VerticalAlign::Top,
+                bound_w,
+                self.justification,
+            ));
+
+            caret.1 += line_height;
+        }
+
+        if out.is_empty() || v_align == VerticalAlign::Top {
+            return out;
+        }
+
+        let shift_up = if v_align == VerticalAlign::Center {
+            (caret.1 - screen_position.1) / 2.0
+        } else {
+            caret.1 - screen_position.1
+        };
+
+        let bounds = self.bounds_rect(geometry);
+
+        out.into_iter()
+            .filter_map(|mut sg| {
+                sg.glyph.position.y -= shift_up;
+
+                // Filter away out-of-bounds glyphs
+                let scaled = fonts[sg.font_id.0].as_scaled(sg.glyph.scale);
+                let h_advance = scaled.h_advance(sg.glyph.id);
+                let h_side_bearing = scaled.h_side_bearing(sg.glyph.id);
+                let height = scaled.height();
+
+                Some(sg).filter(|sg| {
+                    sg.glyph.position.x - h_side_bearing <= bounds.max.x
+                        && sg.glyph.position.x + h_advance >= bounds.min.x
+                        && sg.glyph.position.y - height <= bounds.max.y
+                        && sg.glyph.position.y + height >= bounds.min.y
+                })
+            })
+            .collect()
+    }
+
+    fn bounds_rect(&self, geometry: &SectionGeometry) -> Rect {
+        self.layout.bounds_rect(geometry)
+    }
+
+    fn recalculate_glyphs<F, S, P>(
+        &self,
+        previous: P,
+        change: GlyphChange,
+        fonts: &[F],
+        geometry: &SectionGeometry,
+        sections: &[S],
+    ) -> Vec<SectionGlyph>
+    where
+        F: Font,
+        S: ToSectionText,
+        P: IntoIterator<Item = SectionGlyph>,
+    {
+        match change {
+            GlyphChange::Geometry(old) if old.bounds == geometry.bounds => {
+                // Position change
+                let adjustment = point(
+                    geometry.screen_position.0 - old.screen_position.0,
+                    geometry.screen_position.1 - old.screen_position.1,
+                );
+
+                previous
+                    .into_iter()
+                    .map(|mut sg| {
+                        sg.glyph.position += adjustment;
+                        sg
+                    })
+                    .collect()
+            }
+            _ => self.calculate_glyphs(fonts, geometry, sections),
+        }
+    }
+}
+
+/// The layout of a section queued or measured without a custom layout: its
+/// own [`Layout`](enum.Layout.html), or a `TextLayout` applying the spacing,
+/// the writing mode, the justification, the ellipsis, the tab stops and the
+/// inline objects set in its attributes, or reordering its right-to-left
+/// text.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) enum SectionLayout {
+    Layout(Layout<BuiltInLineBreaker>),
+    Spaced(TextLayout),
+}
+
+impl SectionLayout {
+    pub fn of(section: &RichSection<'_>) -> Self {
+        let layout = section.section.layout;
+        let attributes = &*section.attributes;
+
+        // Right-to-left text is reordered and mirrored by `TextLayout`
+        let right_to_left = section
+            .section
+            .text
+            .iter()
+            .any(|text| bidi::has_right_to_left(text.text));
+
+        if !attributes.changes_layout() && !right_to_left {
+            return SectionLayout::Layout(layout);
+        }
+
+        let mut layout = TextLayout::new(layout);
+
+        layout.spacing.line_height = attributes.line_height;
+        layout.spacing.paragraph_spacing = attributes.paragraph_spacing;
+        layout.writing_mode = attributes.writing_mode;
+        layout.justification = attributes.justification;
+        layout.ellipsis = attributes.ellipsis.clone();
+        layout.tab_stops = attributes.tab_stops.clone();
+
+        layout.spacing.text_letter_spacing = attributes
+            .texts
+            .iter()
+            .enumerate()
+            .filter(|(_, text)| text.letter_spacing != 0.0)
+            .map(|(index, text)| (index, text.letter_spacing))
+            .collect();
+
+        layout.inline_objects = attributes
+            .texts
+            .iter()
+            .enumerate()
+            .filter_map(|(index, text)| Some((index, text.inline_object?)))
+            .collect();
+
+        SectionLayout::Spaced(layout)
+    }
+}
+
+impl GlyphPositioner for SectionLayout {
+    fn calculate_glyphs<F, S>(
+        &self,
+        fonts: &[F],
+        geometry: &SectionGeometry,
+        sections: &[S],
+    ) -> Vec<SectionGlyph>
+    where
+        F: Font,
+        S: ToSectionText,
+    {
+        match self {
+            SectionLayout::Layout(layout) => {
+                layout.calculate_glyphs(fonts, geometry, sections)
+            }
+            SectionLayout::Spaced(layout) => {
+                layout.calculate_glyphs(fonts, geometry, sections)
+            }
+        }
+    }
+
+    fn bounds_rect(&self, geometry: &SectionGeometry) -> Rect {
+        match self {
+            SectionLayout::Layout(layout) => layout.bounds_rect(geometry),
+            SectionLayout::Spaced(layout) => layout.bounds_rect(geometry),
+        }
+    }
+
+    fn recalculate_glyphs<F, S, P>(
+        &self,
+        previous: P,
+        change: GlyphChange,
+        fonts: &[F],
+        geometry: &SectionGeometry,
+        sections: &[S],
+    ) -> Vec<SectionGlyph>
+    where
+        F: Font,
+        S: ToSectionText,
+        P: IntoIterator<Item = SectionGlyph>,
+    {
+        match self {
+            SectionLayout::Layout(layout) => layout.recalculate_glyphs(
+                previous, change, fonts, geometry, sections,
+            ),
+            SectionLayout::Spaced(layout) => layout.recalculate_glyphs(
+                previous, change, fonts, geometry, sections,
+            ),
+        }
+    }
+}
+
+#[cfg(not(feature = "shaping"))]
+struct RunGlyph {
+    id: GlyphId,
+    cluster: usize,
+    x_advance: f32,
+    x_offset: f32,
+    y_offset: f32,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct VMetrics {
+    ascent: f32,
+    descent: f32,
+    line_gap: f32,
+}
+
+impl VMetrics {
+    fn height(&self) -> f32 {
+        self.ascent - self.descent + self.line_gap
+    }
+
+    /// Returns the metrics of a line of the given height, with the extra
+    /// space split above and below the glyphs.
+    fn with_line_height(self, line_height: LineHeight) -> Self {
+        let content = self.ascent - self.descent;
+
+        let height = match line_height {
+            LineHeight::Relative(factor) => content * factor,
+            LineHeight::Absolute(height) => height,
+        };
+
+        let leading = (height - content) / 2.0;
+
+        VMetrics {
+            ascent: self.ascent + leading,
+            descent: self.descent - leading,
+            line_gap: 0.0,
+        }
+    }
+
+    fn max(self, other: Self) -> Self {
+        if other.height() > self.height() {
+            other
+        } else {
+            self
+        }
+    }
+}
+
+impl<F: Font> From<&glyph_brush::ab_glyph::PxScaleFont<&F>> for VMetrics {
+    fn from(scaled: &glyph_brush::ab_glyph::PxScaleFont<&F>) -> Self {
+        VMetrics {
+            ascent: scaled.ascent(),
+            descent: scaled.descent(),
+            line_gap: scaled.line_gap(),
+        }
+    }
+}
+
+/// A group of glyphs that represent the text between two character
+/// boundaries, e.g. a single character or a ligature.
+#[derive(Debug, Clone)]
+struct Cluster {
+    section_index: usize,
+    byte_index: usize,
+    font_id: FontId,
+    scale: PxScale,
+    /// The glyphs of the cluster with their offsets from its origin, `y`
+    /// pointing down.
+    glyphs: Vec<(GlyphId, glyph_brush::ab_glyph::Point)>,
+    advance: f32,
+    v_metrics: VMetrics,
+    /// The first character of the cluster.
+    character: char,
+    whitespace: bool,
+    control: bool,
+    line_break: Option<LineBreak>,
+    level: Level,
+    class: bidi::Class,
+}
+
+/// A range of clusters that ends in a line break opportunity.
+#[derive(Debug, Clone)]
+struct Word {
+    clusters: std::ops::Range<usize>,
+    width: f32,
+    width_no_trail: f32,
+    v_metrics: VMetrics,
+    has_glyphs: bool,
+    hard_break: bool,
+}
+
+fn words(clusters: &[Cluster]) -> Vec<Word> {
+    let mut words = Vec::new();
+    let mut start = 0;
+
+    while start < clusters.len() {
+        let mut word = Word {
+            clusters: start..start,
+            width: 0.0,
+            width_no_trail: 0.0,
+            v_metrics: VMetrics::default(),
+            has_glyphs: false,
+            hard_break: false,
+        };
+
+        for (index, cluster) in clusters.iter().enumerate().skip(start) {
+            word.clusters.end = index + 1;
+            word.v_metrics = word.v_metrics.max(cluster.v_metrics);
+            word.width += cluster.advance;
+            word.has_glyphs |= !cluster.control;
+
+            if !cluster.whitespace {
+                word.width_no_trail = word.width;
+            }
+
+            if let Some(line_break) = cluster.line_break {
+                // Simulate a hard break at the end of all sections
+                word.hard_break = matches!(line_break, LineBreak::Hard(_))
+                    || index + 1 == clusters.len();
+                break;
+            }
+        }
+
+        if word.clusters.end == clusters.len() {
+            word.hard_break = true;
+        }
+
+        start = word.clusters.end;
+        words.push(word);
+    }
+
+    words
+}
+
+/// A laid out line, with the horizontal position of each of its clusters.
+#[derive(Debug, Clone, Default)]
+struct Line {
+    clusters: Vec<(usize, f32)>,
+    v_metrics: VMetrics,
+    rightmost: f32,
+    paragraph_level: u8,
+    /// Whether the line is the last line of its paragraph.
+    paragraph_end: bool,
+}
+
+fn lines(
+    clusters: &mut [Cluster],
+    words: &[Word],
+    width_bound: f32,
+    tab_stops: Option<&TabStops>,
+) -> Vec<Line> {
+    let mut lines = Vec::new();
+    let mut words = words.iter().peekable();
+
+    while words.peek().is_some() {
+        let mut line = Line::default();
+        let mut caret = 0.0;
+        let mut progressed = false;
+
+        while let Some(word) = words.peek() {
+            // Drop trailing spaces when bounds-wrapping, unless the word
+            // ends in a hard break
+            let word_right = caret
+                + if word.hard_break {
+                    word.width
+                } else {
+                    word.width_no_trail
+                };
+
+            // Reduce float errors by using a relative "<= width bound" check
+            let in_bounds = word_right < width_bound
+                || (word_right - width_bound).abs()
+                    <= f32::EPSILON * word_right.abs().max(width_bound.abs());
+
+            // Only the first word of a line can overlap the bounds
+            if !in_bounds && progressed {
+                break;
+            }
+
+            let word = words.next().unwrap();
+            progressed = true;
+            line.rightmost = word_right;
+
+            if (line.clusters.is_empty() || word.has_glyphs)
+                && word.v_metrics.height() > line.v_metrics.height()
+            {
+                line.v_metrics = word.v_metrics;
+            }
+
+            for index in word.clusters.clone() {
+                if let Some(tab_stops) = tab_stops
+                    && clusters[index].character == '\t'
+                {
+                    clusters[index].advance =
+                        tab_advance(clusters, index, caret, tab_stops);
+                }
+
+                line.clusters.push((index, caret));
+                caret += clusters[index].advance;
+            }
+
+            if word.hard_break {
+                // Tabs may have widened the word
+                line.rightmost = caret;
+                line.paragraph_end = true;
+                break;
+            }
+        }
+
+        line.reorder(clusters);
+        lines.push(line);
+    }
+
+    lines
+}
+
+impl Line {
+    /// Moves the clusters of the line to their visual order.
+    fn reorder(&mut self, clusters: &[Cluster]) {
+        if self
+            .clusters
+            .iter()
+            .all(|(index, _)| clusters[*index].level == Level::default())
+        {
+            return;
+        }
+
+        let paragraph = clusters[self.clusters[0].0].level.paragraph;
+
+        let mut levels: Vec<u8> = self
+            .clusters
+            .iter()
+            .map(|(index, _)| clusters[*index].level.level)
+            .collect();
+
+        // Separators and the whitespace before them or at the end of the
+        // line take the paragraph level
+        let mut trailing = true;
+
+        for (position, (index, _)) in self.clusters.iter().enumerate().rev() {
+            let class = clusters[*index].class;
+
+            if bidi::is_separator(class) {
+                levels[position] = paragraph;
+                trailing = true;
+            } else if trailing && bidi::is_whitespace(class) {
+                levels[position] = paragraph;
+            } else {
+                trailing = false;
+            }
+        }
+
+        let width: f32 = self
+            .clusters
+            .iter()
+            .map(|(index, _)| clusters[*index].advance)
+            .sum();
+
+        // Trailing whitespace of right-to-left lines is placed before the
+        // start of the line
+        let mut x = if paragraph % 2 == 1 {
+            self.rightmost - width
+        } else {
+            0.0
+        };
+
+        self.clusters = bidi::reorder(&levels)
+            .into_iter()
+            .map(|position| {
+                let index = self.clusters[position].0;
+                let left = x;
+
+                x += clusters[index].advance;
+
+                (index, left)
+            })
+            .collect();
+
+        self.paragraph_level = paragraph;
+    }
+
+    /// Spreads the space left in the width bound across the gaps between
+    /// the words of the line, or between its characters.
+    fn justify(
+        &mut self,
+        clusters: &[Cluster],
+        justification: Justification,
+        width_bound: f32,
+    ) {
+        let extra = width_bound - self.rightmost;
+
+        if self.paragraph_end || !width_bound.is_finite() || extra <= 0.0 {
+            return;
+        }
+
+        // Leading and trailing whitespace is not stretched
+        let blank = |(index, _): &(usize, f32)| {
+            clusters[*index].whitespace || clusters[*index].control
+        };
+
+        let (Some(first), Some(last)) = (
+            self.clusters.iter().position(|cluster| !blank(cluster)),
+            self.clusters.iter().rposition(|cluster| !blank(cluster)),
+        ) else {
+            return;
+        };
+
+        let gaps = self.clusters[first..last]
+            .iter()
+            .filter(|(index, _)| clusters[*index].whitespace)
+            .count();
+
+        // The clusters followed by extra space
+        let stretched: Vec<bool> = self
+            .clusters
+            .iter()
+            .enumerate()
+            .map(|(position, (index, _))| {
+                let inner = (first..last).contains(&position);
+
+                if gaps > 0 {
+                    inner && clusters[*index].whitespace
+                } else {
+                    inner
+                        && justification == Justification::InterWordOrCharacter
+                }
+            })
+            .collect();
+
+        let count = stretched.iter().filter(|stretched| **stretched).count();
+
+        if count == 0 {
+            return;
+        }
+
+        let space = extra / count as f32;
+        let mut shift = 0.0;
+
+        for ((_, x), stretched) in self.clusters.iter_mut().zip(stretched) {
+            *x += shift;
+
+            if stretched {
+                shift += space;
+            }
+        }
+
+        self.rightmost = width_bound;
+    }
+
+    fn aligned_on_screen(
+        mut self,
+        clusters: &[Cluster],
+        (screen_x, screen_y): (f32, f32),
+        h_align: HorizontalAlign,
+        v_align: VerticalAlign,
+        width_bound: f32,
+        justification: Option<Justification>,
+    ) -> Vec<SectionGlyph> {
+        if let Some(justification) = justification {
+            self.justify(clusters, justification, width_bound);
+        }
+
+        // Right-to-left paragraphs start at the right edge of the bounds
+        let rtl = self.paragraph_level % 2 == 1 && width_bound.is_finite();
+
+        let left = match h_align {
+            HorizontalAlign::Left if rtl => {
+                screen_x + width_bound - self.rightmost
+            }
+            HorizontalAlign::Left => screen_x,
+            HorizontalAlign::Center => screen_x - self.rightmost / 2.0,
+            HorizontalAlign::Right if rtl => screen_x - width_bound,
+            HorizontalAlign::Right => screen_x - self.rightmost,
+        };
+
+        let top = match v_align {
+            VerticalAlign::Top => screen_y,
+            VerticalAlign::Center => screen_y - self.v_metrics.height() / 2.0,
+            VerticalAlign::Bottom => screen_y - self.v_metrics.height(),
+        };
+
+        let baseline = top + self.v_metrics.ascent;
+
+        self.clusters
+            .into_iter()
+            .flat_map(|(index, x)| {
+                let cluster = &clusters[index];
+
+                cluster.glyphs.iter().map(move |(id, offset)| SectionGlyph {
+                    section_index: cluster.section_index,
+                    byte_index: cluster.byte_index,
+                    glyph: Glyph {
+                        id: *id,
+                        scale: cluster.scale,
+                        position: point(
+                            left + x + offset.x,
+                            baseline + offset.y,
+                        ),
+                    },
+                    font_id: cluster.font_id,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Returns how many clusters at the start and at the end of a line, in
+/// reading order, fit in the given width when characters are dropped at the
+/// given position.
+///
+/// Whitespace next to the dropped characters is dropped too.
+fn cut(
+    clusters: &[Cluster],
+    order: &[usize],
+    position: EllipsisPosition,
+    width: f32,
+) -> (usize, usize) {
+    let fitting = |order: &mut dyn Iterator<Item = &usize>, width: f32| {
+        let mut total = 0.0;
+        let mut count = 0;
+
+        for index in order {
+            if total + clusters[*index].advance > width {
+                break;
+            }
+
+            total += clusters[*index].advance;
+            count += 1;
+        }
+
+        (count, total)
+    };
+
+    let (mut prefix, mut suffix) = match position {
+        EllipsisPosition::Start => {
+            (0, fitting(&mut order.iter().rev(), width).0)
+        }
+        EllipsisPosition::Middle => {
+            let (prefix, start) = fitting(&mut order.iter(), width / 2.0);
+            let rest = &mut order[prefix..].iter().rev();
+
+            (prefix, fitting(rest, width - start).0)
+        }
+        EllipsisPosition::End => (fitting(&mut order.iter(), width).0, 0),
+    };
+
+    let blank =
+        |index: usize| clusters[index].whitespace || clusters[index].control;
+
+    while prefix > 0 && blank(order[prefix - 1]) {
+        prefix -= 1;
+    }
+
+    while suffix > 0 && blank(order[order.len() - suffix]) {
+        suffix -= 1;
+    }
+
+    (prefix, suffix)
+}
+
+/// Returns the advance of the tab at the given index of the clusters, which
+/// moves the text after it to the next tab stop past the caret.
+fn tab_advance(
+    clusters: &[Cluster],
+    index: usize,
+    caret: f32,
+    tab_stops: &TabStops,
+) -> f32 {
+    // The text after the tab, up to the next tab or the end of the line
+    let text = clusters[index + 1..]
+        .iter()
+        .take_while(|cluster| cluster.character != '\t')
+        .scan(false, |ended, cluster| {
+            let end = *ended;
+            *ended = matches!(cluster.line_break, Some(LineBreak::Hard(_)));
+
+            (!end).then_some(cluster)
+        });
+
+    let offset = |alignment: TabAlignment| {
+        let text = text.clone();
+
+        match alignment {
+            TabAlignment::Left => 0.0,
+            TabAlignment::Right => text.map(|cluster| cluster.advance).sum(),
+            TabAlignment::Center => {
+                text.map(|cluster| cluster.advance).sum::<f32>() / 2.0
+            }
+            TabAlignment::Decimal(point) => text
+                .take_while(|cluster| cluster.character != point)
+                .map(|cluster| cluster.advance)
+                .sum(),
+        }
+    };
+
+    let stop = tab_stops
+        .stops
+        .iter()
+        .map(|stop| stop.position - offset(stop.alignment))
+        .filter(|start| *start > caret)
+        .reduce(f32::min);
+
+    // Past the explicit stops, every interval of spaces
+    let space = clusters[index].advance;
+    let interval = space * tab_stops.interval as f32;
+
+    match stop {
+        Some(start) => start - caret,
+        None if interval > 0.0 => {
+            ((caret / interval).floor() + 1.0) * interval - caret
+        }
+        None => 0.0,
+    }
+}
+
+/// Splits a section into runs of characters with the same bidi level.
+fn level_runs(
+    text: &str,
+    levels: Option<&[Level]>,
+) -> Vec<(std::ops::Range<usize>, Level)> {
+    let Some(levels) = levels else {
+        return vec![(0..text.len(), Level::default())];
+    };
+
+    let mut runs: Vec<(std::ops::Range<usize>, Level)> = Vec::new();
+
+    for (index, c) in text.char_indices() {
+        let end = index + c.len_utf8();
+
+        match runs.last_mut() {
+            Some((range, level)) if *level == levels[index] => {
+                range.end = end;
+            }
+            _ => runs.push((index..end, levels[index])),
+        }
+    }
+
+    runs
+}
+
+/// Returns the line break of a character at the end of a section, if the
+/// character breaks lines on its own.
+fn eol_line_break<L: LineBreaker>(
+    c: char,
+    line_breaker: &L,
+) -> Option<LineBreak> {
+    // To check if the end char (say '$') should hard break, construct a
+    // str "$ " and check if the line break logic flags a hard break at
+    // index 1. Then check for soft breaks using "$a".
+    for padding in [' ', 'a'] {
+        let padded: String = [c, padding].iter().collect();
+        let len = c.len_utf8();
+
+        match line_breaker.line_breaks(&padded).next() {
+            Some(LineBreak::Soft(offset)) if offset == len => {
+                return Some(LineBreak::Soft(1));
+            }
+            Some(LineBreak::Hard(offset)) if offset == len => {
+                return Some(LineBreak::Hard(1));
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{self, advance, assert_near, font};
+    use crate::{SectionAttributes, Text};
+
+    fn glyphs(
+        layout: &impl GlyphPositioner,
+        bounds: (f32, f32),
+        texts: &[&str],
+    ) -> Vec<SectionGlyph> {
+        let texts: Vec<Text<'_>> =
+            texts.iter().map(|text| Text::new(text)).collect();
+        let section = crate::Section::default()
+            .with_bounds(bounds)
+            .with_text(texts);
+
+        test_util::glyphs(layout, &section)
+    }
+
+    /// Returns the right edge of the glyphs of every line, by line, without
+    /// trailing whitespace.
+    fn line_widths(glyphs: &[SectionGlyph], text: &str) -> Vec<f32> {
+        let font = font();
+        let mut lines: Vec<(f32, f32)> = Vec::new();
+
+        for sg in glyphs {
+            if text[sg.byte_index..].starts_with(char::is_whitespace) {
+                continue;
+            }
+
+            let right = sg.glyph.position.x
+                + font.as_scaled(sg.glyph.scale).h_advance(sg.glyph.id);
+
+            match lines.last_mut() {
+                Some((y, width)) if *y == sg.glyph.position.y => {
+                    *width = width.max(right);
+                }
+                _ => lines.push((sg.glyph.position.y, right)),
+            }
+        }
+
+        lines.into_iter().map(|(_, width)| width).collect()
+    }
+
+    #[test]
+    fn section_justification_stretches_lines_to_the_bounds() {
+        let attributes = SectionAttributes::default()
+            .with_justification(Justification::InterWord);
+        let layout = SectionLayout::of(&RichSection::new(
+            crate::Section::default().with_layout(Layout::default_wrap()),
+            attributes,
+        ));
+
+        let text =
+            "The quick brown fox jumps over the lazy dog again and again";
+
+        let glyphs = glyphs(&layout, (200.0, f32::INFINITY), &[text]);
+        let widths = line_widths(&glyphs, text);
+
+        assert!(widths.len() > 2, "{widths:?}");
+
+        let (last, justified) = widths.split_last().unwrap();
+
+        for width in justified {
+            assert!((width - 200.0).abs() < 1e-3, "{widths:?}");
+        }
+
+        assert!(*last < 200.0);
+    }
+
+    #[test]
+    fn section_ellipsis_truncates_the_text() {
+        let ellipsis = Ellipsis::new(EllipsisPosition::End);
+        let layout = SectionLayout::of(&RichSection::new(
+            crate::Section::default()
+                .with_layout(Layout::default_single_line()),
+            SectionAttributes::default().with_ellipsis(ellipsis.clone()),
+        ));
+
+        assert_eq!(
+            layout,
+            SectionLayout::Spaced(
+                TextLayout::new(Layout::default_single_line())
+                    .with_ellipsis(ellipsis)
+            )
+        );
+
+        let font = font();
+        let text = "abcdefghijkl";
+        let glyphs = glyphs(&layout, (8.0 * advance(), f32::INFINITY), &[text]);
+
+        assert!(
+            glyphs
+                .iter()
+                .any(|sg| sg.glyph.id == font.glyph_id('\u{2026}'))
+        );
+        assert!(glyphs.iter().all(|sg| sg.byte_index < text.len() - 1));
+    }
+
+    #[test]
+    fn section_tab_stops_move_the_text_after_tabs() {
+        let tab_stops = TabStops::every(4);
+        let layout = SectionLayout::of(&RichSection::new(
+            crate::Section::default(),
+            SectionAttributes::default().with_tab_stops(tab_stops.clone()),
+        ));
+
+        assert_eq!(
+            layout,
+            SectionLayout::Spaced(
+                TextLayout::new(Layout::default()).with_tab_stops(tab_stops)
+            )
+        );
+
+        let glyphs = glyphs(&layout, (f32::INFINITY, f32::INFINITY), &["a\tb"]);
+        let b = glyphs.iter().find(|sg| sg.byte_index == 2).unwrap();
+
+        assert_near(b.glyph.position.x, 4.0 * advance());
+    }
+
+    #[test]
+    fn right_to_left_sections_are_reordered() {
+        let layout_of = |text| {
+            SectionLayout::of(&RichSection::from(
+                crate::Section::default().add_text(Text::new(text)),
+            ))
+        };
+
+        assert!(matches!(layout_of("abc"), SectionLayout::Layout(_)));
+
+        // Hebrew letters without any attribute are laid out right-to-left
+        let text = "a \u{5D0}\u{5D1}";
+        let layout = layout_of(text);
+        let glyphs = glyphs(&layout, (f32::INFINITY, f32::INFINITY), &[text]);
+        let x = |byte: usize| {
+            glyphs
+                .iter()
+                .find(|sg| sg.byte_index == byte)
+                .unwrap()
+                .glyph
+                .position
+                .x
+        };
+
+        assert!(matches!(layout, SectionLayout::Spaced(_)));
+        assert!(x(0) < x(4));
+        assert!(x(4) < x(2));
+    }
+
+    #[test]
+    fn inline_objects_of_empty_texts_are_laid_out() {
+        let layout = TextLayout::new(Layout::default_single_line())
+            .with_inline_object(1, InlineObject::new(30.0, 10.0));
+
+        let glyphs =
+            glyphs(&layout, (f32::INFINITY, f32::INFINITY), &["ab", "", "cd"]);
+
+        let x = |text_index: usize, byte_index: usize| {
+            glyphs
+                .iter()
+                .find(|sg| {
+                    sg.section_index == text_index
+                        && sg.byte_index == byte_index
+                })
+                .map(|sg| sg.glyph.position.x)
+        };
+
+        let (b, object, c) = (x(0, 1).unwrap(), x(1, 0).unwrap(), x(2, 0));
+        let advance = font()
+            .as_scaled(PxScale::from(16.0))
+            .h_advance(font().glyph_id('b'));
+
+        assert_eq!(object, b + advance);
+        assert_eq!(c, Some(object + 30.0));
+    }
+}
@@ -3,13 +3,77 @@
 //! [`wgpu`]: https://github.com/gfx-rs/wgpu
 //! [`glyph_brush`]: https://github.com/alexheretic/glyph-brush/tree/master/glyph-brush
 #![deny(unused_results)]
+mod animation;
+mod attributes;
+mod bidi;
+mod billboard;
+mod bitmap;
+mod bmfont;
 mod builder;
+mod cursor;
+mod database;
+mod decoration;
+mod ellipsis;
+mod extra;
+mod face;
+mod fill;
+mod inline;
+mod justification;
+mod layout;
+mod palette;
+mod path;
 mod pipeline;
+mod quads;
+mod rasterizer;
 mod region;
+mod spacing;
+mod style;
+mod tab;
+#[cfg(test)]
+mod test_util;
+mod text_position;
+mod transform;
+mod vertical;
 
+#[cfg(feature = "markup")]
+pub mod markup;
+#[cfg(feature = "shaping")]
+pub mod shaping;
+
+pub use animation::AnimatedGlyph;
+pub use attributes::{
+    OwnedRichSection, RichSection, SectionAttributes, TextAttributes,
+};
+pub use billboard::{Billboard, BillboardMode};
+pub use bitmap::{BitmapFont, InvalidBitmapFont, Strike};
+pub use bmfont::BmFont;
+pub use cursor::{Caret, CaretStyle, Cursor, Selection};
+pub use database::{FaceInfo, FontDatabase, Query, Stretch, Style, Weight};
+pub use decoration::{Decoration, DecorationStyle};
+pub use ellipsis::{Ellipsis, EllipsisPosition};
+pub use extra::{Outline, Shadow};
+pub use fill::{ColorStop, Fill, GradientId};
+pub use inline::{ImageId, InlineObject};
+pub use justification::Justification;
+pub use layout::TextLayout;
+pub use path::TextPath;
+pub use rasterizer::{
+    Coverage, GlyphRasterizer, OutlineRasterizer, RasterKey, RasterizedFont,
+};
 pub use region::Region;
+pub use spacing::LineHeight;
+pub use style::{StyleId, StyleRef, TextStyle};
+pub use tab::{TabAlignment, TabStop, TabStops};
+pub use text_position::{Affinity, TextPosition};
+pub use transform::Affine;
+pub use vertical::WritingMode;
 
-use pipeline::{Instance, Pipeline};
+use animation::{AnimatedSection, AnimatedVertex, Animation};
+use extra::{GlyphExtra, GlyphSection};
+use layout::SectionLayout;
+use pipeline::{Glyph, Pipeline, Quad};
+use quads::{QuadGlyphs, Quads, Runs, SectionQuads};
+use text_position::Lines;
 
 pub use builder::GlyphBrushBuilder;
 pub use glyph_brush::ab_glyph;
@@ -20,9 +84,10 @@ pub use glyph_brush::{
     Text, VerticalAlign,
 };
 
-use ab_glyph::{Font, Rect};
+use ab_glyph::{Font, Rect, ScaleFont};
 use core::hash::BuildHasher;
 use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
 
 use glyph_brush::{BrushAction, BrushError, DefaultSectionHasher};
 use log::{log_enabled, warn};
@@ -33,7 +98,26 @@ use log::{log_enabled, warn};
 /// Build using a [`GlyphBrushBuilder`](struct.GlyphBrushBuilder.html).
 pub struct GlyphBrush<Depth, F = ab_glyph::FontArc, H = DefaultSectionHasher> {
     pipeline: Pipeline<Depth>,
-    glyph_brush: glyph_brush::GlyphBrush<Instance, Extra, F, H>,
+    glyph_brush: glyph_brush::GlyphBrush<Glyph, GlyphExtra, F, H>,
+    font_database: FontDatabase,
+    loaded_faces: HashMap<usize, FontId>,
+    decoration_metrics: HashMap<FontId, decoration::Metrics>,
+    gradients: fill::Gradients,
+    styles: style::Styles,
+    palette: palette::Palette,
+    images: inline::Images,
+    bm_fonts: bmfont::BmFonts,
+    animation: Option<Animation>,
+    queued_sections: usize,
+    animated: Vec<AnimatedSection>,
+    drawn_animated: Vec<AnimatedSection>,
+    animated_vertices: Vec<AnimatedVertex>,
+    instances: Vec<Quad>,
+    shadows: Vec<Quad>,
+    section_runs: Runs,
+    section_keys: HashSet<u64>,
+    quads: Vec<SectionQuads>,
+    drawn_quads: Vec<SectionQuads>,
 }
 
 impl<Depth, F: Font, H: BuildHasher> GlyphBrush<Depth, F, H> {
@@ -45,9 +129,20 @@ impl<Depth, F: Font, H: BuildHasher> GlyphBrush<Depth, F, H> {
     #[inline]
     pub fn queue<'a, S>(&mut self, section: S)
     where
-        S: Into<Cow<'a, Section<'a>>>,
+        S: Into<RichSection<'a>>,
     {
-        self.glyph_brush.queue(section)
+        let section = section.into();
+        let attributes = &section.attributes;
+
+        let glyph_section = self.prepare(&section);
+
+        let layout = SectionLayout::of(&section);
+
+        if vertical::is_vertical(attributes) {
+            return self.queue_vertical(glyph_section, attributes, &layout);
+        }
+
+        self.queue_section(glyph_section, attributes, &layout, None)
     }
 
     /// Queues a section/layout to be drawn by the next call of
@@ -66,9 +161,131 @@ impl<Depth, F: Font, H: BuildHasher> GlyphBrush<Depth, F, H> {
         custom_layout: &G,
     ) where
         G: GlyphPositioner,
-        S: Into<Cow<'a, Section<'a>>>,
+        S: Into<RichSection<'a>>,
     {
-        self.glyph_brush.queue_custom_layout(section, custom_layout)
+        let section = section.into();
+        let attributes = &section.attributes;
+
+        let glyph_section = self.prepare(&section);
+
+        self.queue_section(glyph_section, attributes, custom_layout, None)
+    }
+
+    /// Queues a section/layout to be drawn by the next call of
+    /// [`draw_queued`](struct.GlyphBrush.html#method.draw_queued), together
+    /// with the selection and caret of the given
+    /// [`Cursor`](struct.Cursor.html).
+    ///
+    /// Benefits from caching, see [caching behaviour](#caching-behaviour).
+    pub fn queue_with_cursor<'a, S>(&mut self, section: S, cursor: &Cursor)
+    where
+        S: Into<RichSection<'a>>,
+    {
+        let section = section.into();
+        let layout = SectionLayout::of(&section);
+
+        self.queue_custom_layout_with_cursor(section, &layout, cursor)
+    }
+
+    /// Queues a section/layout to be drawn by the next call of
+    /// [`draw_queued`](struct.GlyphBrush.html#method.draw_queued), together
+    /// with the selection and caret of the given
+    /// [`Cursor`](struct.Cursor.html), using a custom layout.
+    ///
+    /// See [`queue_with_cursor`](#method.queue_with_cursor).
+    pub fn queue_custom_layout_with_cursor<'a, S, G>(
+        &mut self,
+        section: S,
+        custom_layout: &G,
+        cursor: &Cursor,
+    ) where
+        G: GlyphPositioner + std::hash::Hash,
+        S: Into<RichSection<'a>>,
+    {
+        let section = section.into();
+        let glyph_section = self.prepare(&section);
+
+        self.queue_section(
+            glyph_section,
+            &section.attributes,
+            custom_layout,
+            Some(cursor),
+        )
+    }
+
+    /// Queues a section to be drawn by the next call of
+    /// [`draw_queued`](struct.GlyphBrush.html#method.draw_queued), with its
+    /// glyphs following the given path, rotated to its direction.
+    ///
+    /// The section is laid out as usual, then its `screen_position` is moved
+    /// along the path according to the horizontal alignment of its layout:
+    /// to the start of the path for `Left`, its middle for `Center` and its
+    /// end for `Right`. The vertical alignment places the lines across the
+    /// path. Glyphs that fall outside the path are not drawn.
+    ///
+    /// The transform of the section is applied after placing its glyphs on
+    /// the path. Decorations are not drawn and, like pre-positioned glyphs,
+    /// the glyphs skip the vertex cache.
+    pub fn queue_on_path<'a, S>(&mut self, section: S, path: &TextPath)
+    where
+        S: Into<RichSection<'a>>,
+    {
+        let section = section.into();
+        let section_layout = SectionLayout::of(&section);
+
+        let section = self.prepare(&section);
+
+        let layout = section.layout;
+        let glyphs = self.section_glyphs(&section, &section_layout);
+        let bounds = layout.bounds_rect(&SectionGeometry::from(&section));
+        let (x, y) = section.screen_position;
+
+        let start = match layout {
+            Layout::SingleLine { h_align, .. }
+            | Layout::Wrap { h_align, .. } => match h_align {
+                HorizontalAlign::Left => 0.0,
+                HorizontalAlign::Center => path.length() / 2.0,
+                HorizontalAlign::Right => path.length(),
+            },
+        };
+
+        let mut placed = Vec::with_capacity(glyphs.len());
+        let mut extras = Vec::with_capacity(glyphs.len());
+
+        for mut sg in glyphs {
+            let position = sg.glyph.position;
+            let advance = self.glyph_brush.fonts()[sg.font_id.0]
+                .as_scaled(sg.glyph.scale)
+                .h_advance(sg.glyph.id);
+
+            // The middle of the glyph on the baseline follows the path
+            let center = position.x + advance / 2.0;
+            let offset = position.y - y;
+
+            let Some((point, angle)) = path.sample(start + center - x) else {
+                continue;
+            };
+
+            let (sin, cos) = angle.sin_cos();
+
+            let placement = Affine::translation(-center, -position.y)
+                .then(Affine::rotation(angle))
+                .then(Affine::translation(
+                    point.0 - sin * offset,
+                    point.1 + cos * offset,
+                ));
+
+            let mut extra = section.text[sg.section_index].extra;
+            extra.pre_transform(placement);
+
+            sg.section_index = extras.len();
+            placed.push(sg);
+            extras.push(extra);
+        }
+
+        self.queued_sections += 1;
+        self.glyph_brush
+            .queue_pre_positioned(placed, extras, bounds)
     }
 
     /// Queues pre-positioned glyphs to be processed by the next call of
@@ -81,6 +298,47 @@ impl<Depth, F: Font, H: BuildHasher> GlyphBrush<Depth, F, H> {
         extra: Vec<Extra>,
         bounds: Rect,
     ) {
+        self.glyph_brush.queue_pre_positioned(
+            glyphs,
+            extra.into_iter().map(GlyphExtra::from).collect(),
+            bounds,
+        )
+    }
+
+    /// Queues pre-positioned glyphs like
+    /// [`queue_pre_positioned`](#method.queue_pre_positioned), drawn with
+    /// the given attributes.
+    ///
+    /// The glyphs of the `Extra` with index `i` use the `TextAttributes`
+    /// with index `i`. Fills and transforms are relative to
+    /// `screen_position`, like the ones of a section at this position.
+    /// Decorations, inline objects and animations depend on the layout of
+    /// the text and are ignored.
+    pub fn queue_pre_positioned_with_attributes(
+        &mut self,
+        glyphs: Vec<SectionGlyph>,
+        extra: Vec<Extra>,
+        attributes: &SectionAttributes,
+        screen_position: (f32, f32),
+        bounds: Rect,
+    ) {
+        let extra = extra
+            .iter()
+            .enumerate()
+            .map(|(index, extra)| {
+                let (mut extra, _) = GlyphExtra::resolve(
+                    extra,
+                    attributes,
+                    index,
+                    screen_position,
+                    &self.styles,
+                );
+
+                extra.animated = false;
+                extra
+            })
+            .collect();
+
         self.glyph_brush.queue_pre_positioned(glyphs, extra, bounds)
     }
 
@@ -95,11 +353,15 @@ impl<Depth, F: Font, H: BuildHasher> GlyphBrush<Depth, F, H> {
         section: S,
         custom_layout: &G,
     ) where
-        S: Into<Cow<'a, Section<'a>>>,
+        S: Into<RichSection<'a>>,
         G: GlyphPositioner,
     {
+        let section = section.into();
+
+        let glyph_section = self.prepare(&section);
+
         self.glyph_brush
-            .keep_cached_custom_layout(section, custom_layout)
+            .keep_cached_custom_layout(glyph_section, custom_layout)
     }
 
     /// Retains the section in the cache as if it had been used in the last
@@ -110,9 +372,12 @@ impl<Depth, F: Font, H: BuildHasher> GlyphBrush<Depth, F, H> {
     #[inline]
     pub fn keep_cached<'a, S>(&mut self, section: S)
     where
-        S: Into<Cow<'a, Section<'a>>>,
+        S: Into<RichSection<'a>>,
     {
-        self.glyph_brush.keep_cached(section)
+        let section = section.into();
+        let layout = SectionLayout::of(&section);
+
+        self.keep_cached_custom_layout(section, &layout)
     }
 
     /// Returns the available fonts.
@@ -129,6 +394,835 @@ impl<Depth, F: Font, H: BuildHasher> GlyphBrush<Depth, F, H> {
     pub fn add_font(&mut self, font: F) -> FontId {
         self.glyph_brush.add_font(font)
     }
+
+    /// Returns the position in the text of the section that is closest to
+    /// the given point, e.g. to place the caret under the mouse cursor.
+    ///
+    /// The point is on screen: the
+    /// [`transform`](struct.SectionAttributes.html#method.with_transform) of
+    /// the section is undone before hit testing its layout. Sections with a
+    /// [`Billboard`](struct.Billboard.html) are placed by the camera on the
+    /// GPU and cannot be hit tested.
+    ///
+    /// Returns `None` if the section has no glyphs, or if its transform
+    /// flattens it.
+    ///
+    /// # Panics
+    /// Panics in debug builds if the section has a billboard.
+    ///
+    /// Benefits from caching, see [caching behaviour](#caching-behaviour).
+    pub fn hit_test<'a, S>(
+        &mut self,
+        section: S,
+        point: (f32, f32),
+    ) -> Option<TextPosition>
+    where
+        S: Into<RichSection<'a>>,
+    {
+        let section = section.into();
+        let layout = SectionLayout::of(&section);
+
+        self.hit_test_custom_layout(section, &layout, point)
+    }
+
+    /// Returns the position in the text of the section that is closest to
+    /// the given point, using a custom layout.
+    ///
+    /// See [`hit_test`](#method.hit_test).
+    pub fn hit_test_custom_layout<'a, S, L>(
+        &mut self,
+        section: S,
+        custom_layout: &L,
+        point: (f32, f32),
+    ) -> Option<TextPosition>
+    where
+        S: Into<RichSection<'a>>,
+        L: GlyphPositioner + std::hash::Hash,
+    {
+        let section = section.into();
+        let attributes = &section.attributes;
+
+        debug_assert!(
+            attributes.billboard.is_none(),
+            "billboards cannot be hit tested"
+        );
+
+        let point = match attributes.transform {
+            Some(transform) => transform
+                .around(section.section.screen_position)
+                .inverse()?
+                .transform_point(point),
+            None => point,
+        };
+
+        let section = self.prepare(&section);
+
+        self.lines(&section, custom_layout)
+            .hit_test(&section, point)
+    }
+
+    /// Returns the rectangle of the caret at the given position of the
+    /// section. The rectangle spans the height of the line of the position
+    /// and has no width.
+    ///
+    /// Like the layout, the rectangle is before the
+    /// [`transform`](struct.SectionAttributes.html#method.with_transform) of
+    /// the section, which may turn it into any quad: transform its corners
+    /// with [`Affine::around`](struct.Affine.html#method.around) the
+    /// `screen_position` of the section to place it on screen.
+    ///
+    /// Returns `None` if the section has no text.
+    ///
+    /// Benefits from caching, see [caching behaviour](#caching-behaviour).
+    pub fn caret_rect<'a, S>(
+        &mut self,
+        section: S,
+        position: TextPosition,
+    ) -> Option<Rect>
+    where
+        S: Into<RichSection<'a>>,
+    {
+        let section = section.into();
+        let layout = SectionLayout::of(&section);
+
+        self.caret_rect_custom_layout(section, &layout, position)
+    }
+
+    /// Returns the rectangle of the caret at the given position of the
+    /// section, using a custom layout.
+    ///
+    /// See [`caret_rect`](#method.caret_rect).
+    pub fn caret_rect_custom_layout<'a, S, L>(
+        &mut self,
+        section: S,
+        custom_layout: &L,
+        position: TextPosition,
+    ) -> Option<Rect>
+    where
+        S: Into<RichSection<'a>>,
+        L: GlyphPositioner + std::hash::Hash,
+    {
+        let section = self.prepare(&section.into());
+
+        self.lines(&section, custom_layout)
+            .caret_rect(&section, position)
+    }
+
+    /// Returns the rectangles covering the text of the section between the
+    /// given positions, one for every contiguous run of selected glyphs in a
+    /// line.
+    ///
+    /// Like the ones of
+    /// [`caret_rect`](#method.caret_rect), the rectangles are before the
+    /// transform of the section.
+    ///
+    /// Benefits from caching, see [caching behaviour](#caching-behaviour).
+    pub fn selection_rects<'a, S>(
+        &mut self,
+        section: S,
+        range: std::ops::Range<TextPosition>,
+    ) -> Vec<Rect>
+    where
+        S: Into<RichSection<'a>>,
+    {
+        let section = section.into();
+        let layout = SectionLayout::of(&section);
+
+        self.selection_rects_custom_layout(section, &layout, range)
+    }
+
+    /// Returns the rectangles covering the text of the section between the
+    /// given positions, using a custom layout.
+    ///
+    /// See [`selection_rects`](#method.selection_rects).
+    pub fn selection_rects_custom_layout<'a, S, L>(
+        &mut self,
+        section: S,
+        custom_layout: &L,
+        range: std::ops::Range<TextPosition>,
+    ) -> Vec<Rect>
+    where
+        S: Into<RichSection<'a>>,
+        L: GlyphPositioner + std::hash::Hash,
+    {
+        let section = self.prepare(&section.into());
+
+        self.lines(&section, custom_layout)
+            .selection_rects(&section, range)
+    }
+
+    /// Returns the text index and the rectangle of every inline object of
+    /// the section, as placed by its layout.
+    ///
+    /// Benefits from caching, see [caching behaviour](#caching-behaviour).
+    pub fn inline_rects<'a, S>(&mut self, section: S) -> Vec<(usize, Rect)>
+    where
+        S: Into<RichSection<'a>>,
+    {
+        let section = section.into();
+        let layout = SectionLayout::of(&section);
+
+        self.inline_rects_custom_layout(section, &layout)
+    }
+
+    /// Returns the text index and the rectangle of every inline object of
+    /// the section, using a custom layout.
+    ///
+    /// See [`inline_rects`](#method.inline_rects).
+    pub fn inline_rects_custom_layout<'a, S, L>(
+        &mut self,
+        section: S,
+        custom_layout: &L,
+    ) -> Vec<(usize, Rect)>
+    where
+        S: Into<RichSection<'a>>,
+        L: GlyphPositioner + std::hash::Hash,
+    {
+        let section = section.into();
+        let glyph_section = extra::glyph_section(&section, &self.styles);
+        let glyphs = self.section_glyphs(&glyph_section, custom_layout);
+
+        inline::rects(&section.attributes, &glyphs)
+    }
+
+    fn lines<L>(
+        &mut self,
+        section: &GlyphSection<'_>,
+        custom_layout: &L,
+    ) -> Lines
+    where
+        L: GlyphPositioner + std::hash::Hash,
+    {
+        let glyphs = self.section_glyphs(section, custom_layout);
+
+        Lines::new(self.glyph_brush.fonts(), section, &glyphs)
+    }
+
+    fn section_glyphs<L>(
+        &mut self,
+        section: &GlyphSection<'_>,
+        custom_layout: &L,
+    ) -> Vec<SectionGlyph>
+    where
+        L: GlyphPositioner + std::hash::Hash,
+    {
+        self.glyph_brush
+            .glyphs_custom_layout(section, custom_layout)
+            .cloned()
+            .collect()
+    }
+
+    /// Resolves the styles and attributes of the section.
+    fn prepare<'a>(&self, section: &RichSection<'a>) -> GlyphSection<'a> {
+        extra::glyph_section(section, &self.styles)
+    }
+
+    /// Queues a prepared section, with the quads of its decorations, inline
+    /// objects and cursor drawn around its glyphs.
+    fn queue_section<L: GlyphPositioner>(
+        &mut self,
+        mut section: GlyphSection<'_>,
+        attributes: &SectionAttributes,
+        custom_layout: &L,
+        cursor: Option<&Cursor>,
+    ) {
+        let animated = self.is_animated(&section);
+
+        if cursor.is_none()
+            && !decoration::is_decorated(attributes)
+            && !inline::has_objects(attributes)
+            && !self.bm_fonts.is_used(&section)
+            && !animated
+        {
+            self.queued_sections += 1;
+            return self.glyph_brush.queue_custom_layout(section, custom_layout);
+        }
+
+        let has_quads = cursor.is_some()
+            || decoration::is_decorated(attributes)
+            || inline::has_objects(attributes)
+            || self.bm_fonts.is_used(&section);
+
+        // Animated glyphs are queued again when processed, with their ids
+        let key = (!animated && has_quads)
+            .then(|| self.tag(&mut section, custom_layout));
+
+        let glyphs = self.section_glyphs(&section, custom_layout);
+        let bounds =
+            custom_layout.bounds_rect(&SectionGeometry::from(&section));
+        let mut quads = Quads::default();
+
+        // Images and bitmap glyphs are drawn like glyphs, under the caret
+        // and strikethroughs
+        self.queue_images(&section, attributes, &glyphs, bounds, &mut quads);
+
+        if let Some(cursor) = cursor {
+            self.queue_cursor(&section, cursor, &glyphs, bounds, &mut quads);
+        }
+
+        self.queue_decorations(
+            &section, attributes, &glyphs, bounds, &mut quads,
+        );
+
+        if animated {
+            self.quads.push(SectionQuads {
+                glyphs: QuadGlyphs::Animated(self.animated.len()),
+                quads,
+            });
+
+            return self.queue_animated(&section, glyphs, bounds);
+        }
+
+        if let Some(key) = key
+            && !quads.is_empty()
+        {
+            self.quads.push(SectionQuads {
+                glyphs: QuadGlyphs::Tagged(key),
+                quads,
+            });
+        }
+
+        self.queued_sections += 1;
+        self.glyph_brush.queue_custom_layout(section, custom_layout)
+    }
+
+    /// Tags the glyphs of a section with a key unique in the frame, to draw
+    /// its quads around them.
+    ///
+    /// The key only depends on the section, its layout and the sections
+    /// equal to it queued before it, so it keeps the vertices of the section
+    /// cached across frames.
+    fn tag<L: GlyphPositioner>(
+        &mut self,
+        section: &mut GlyphSection<'_>,
+        custom_layout: &L,
+    ) -> u64 {
+        let hasher = DefaultSectionHasher::default();
+        let hash = hasher.hash_one((&*section, custom_layout));
+
+        let key = (0_u32..)
+            .map(|occurrence| hasher.hash_one((hash, occurrence)))
+            .find(|key| self.section_keys.insert(*key))
+            .expect("a free key");
+
+        for text in &mut section.text {
+            text.extra.section = Some(key);
+        }
+
+        key
+    }
+
+    fn queue_cursor(
+        &mut self,
+        section: &GlyphSection<'_>,
+        cursor: &Cursor,
+        glyphs: &[SectionGlyph],
+        bounds: Rect,
+        quads: &mut Quads,
+    ) {
+        let lines = Lines::new(self.glyph_brush.fonts(), section, glyphs);
+
+        let quad = |text_index: usize, rect: Rect, color: [f32; 4]| {
+            let extra = section.text.get(text_index).map(|text| &text.extra);
+
+            Quad::solid(
+                rect,
+                bounds,
+                extra.map_or(0.0, |extra| extra.z),
+                color,
+            )
+            .map(|instance| match extra {
+                Some(extra) => instance.transformed(extra),
+                None => instance,
+            })
+        };
+
+        if let Some(selection) = &cursor.selection {
+            let text_index = selection.range.start.text_index;
+
+            quads.behind.extend(
+                lines
+                    .selection_rects(section, selection.range.clone())
+                    .into_iter()
+                    .filter_map(|rect| quad(text_index, rect, selection.color)),
+            );
+        }
+
+        if let Some(caret) = cursor.caret.filter(Caret::is_visible) {
+            let rect = caret.rect(&lines, section);
+
+            quads.front.extend(rect.and_then(|rect| {
+                quad(caret.position.text_index, rect, caret.color)
+            }));
+        }
+    }
+
+    fn queue_decorations(
+        &mut self,
+        section: &GlyphSection<'_>,
+        attributes: &SectionAttributes,
+        glyphs: &[SectionGlyph],
+        bounds: Rect,
+        quads: &mut Quads,
+    ) {
+        if !decoration::is_decorated(attributes) {
+            return;
+        }
+
+        decoration::quads(
+            self.glyph_brush.fonts(),
+            &mut self.decoration_metrics,
+            section,
+            attributes,
+            glyphs,
+            bounds,
+            quads,
+        );
+    }
+
+    fn queue_images(
+        &mut self,
+        section: &GlyphSection<'_>,
+        attributes: &SectionAttributes,
+        glyphs: &[SectionGlyph],
+        bounds: Rect,
+        quads: &mut Quads,
+    ) {
+        if !inline::has_objects(attributes) && !self.bm_fonts.is_used(section)
+        {
+            return;
+        }
+
+        inline::quads(
+            &self.images,
+            section,
+            attributes,
+            glyphs,
+            bounds,
+            &mut quads.front,
+        );
+
+        self.bm_fonts.quads(
+            &self.images,
+            section,
+            glyphs,
+            bounds,
+            &mut quads.front,
+        );
+    }
+
+    fn is_animated(&self, section: &GlyphSection<'_>) -> bool {
+        self.animation.is_some() && animation::is_animated(section)
+    }
+
+    /// Keeps the glyphs of a section with animated texts, to be queued as
+    /// pre-positioned glyphs and animated when processed.
+    fn queue_animated(
+        &mut self,
+        section: &GlyphSection<'_>,
+        glyphs: Vec<SectionGlyph>,
+        bounds: Rect,
+    ) {
+        self.animated.push(AnimatedSection::new(
+            self.queued_sections,
+            section,
+            glyphs,
+            bounds,
+        ));
+
+        self.queued_sections += 1;
+    }
+
+    /// Queues a section in a vertical writing mode, turning its sideways
+    /// glyphs a quarter turn around the middle of their em box.
+    fn queue_vertical(
+        &mut self,
+        section: GlyphSection<'_>,
+        attributes: &SectionAttributes,
+        layout: &SectionLayout,
+    ) {
+        if (decoration::is_decorated(attributes)
+            || inline::has_objects(attributes)
+            || self.is_animated(&section))
+            && log_enabled!(log::Level::Warn)
+        {
+            warn!(
+                "Decorations, inline objects and animations of vertical \
+                 sections are not supported, drawing the section without them"
+            );
+        }
+
+        let glyphs = self.section_glyphs(&section, layout);
+        let fonts = self.glyph_brush.fonts();
+
+        let (upright, sideways): (Vec<_>, Vec<_>) =
+            glyphs.into_iter().partition(|sg| {
+                section.text[sg.section_index].text[sg.byte_index..]
+                    .chars()
+                    .next()
+                    .is_none_or(vertical::is_upright)
+            });
+
+        self.queued_sections += 1;
+
+        if sideways.is_empty() {
+            return self.glyph_brush.queue_custom_layout(section, layout);
+        }
+
+        let origin = section.screen_position;
+
+        let turned = sideways
+            .into_iter()
+            .map(|mut sg| {
+                let scaled = fonts[sg.font_id.0].as_scaled(sg.glyph.scale);
+                let middle = (scaled.ascent() + scaled.descent()) / 2.0;
+                let position = sg.glyph.position;
+
+                sg.glyph.position = vertical::sideways_position(
+                    position,
+                    (position.x, position.y - middle),
+                    origin,
+                );
+                sg
+            })
+            .collect();
+
+        let bounds = layout.bounds_rect(&SectionGeometry::from(&section));
+        let mut extras: Vec<_> =
+            section.text.iter().map(|text| text.extra).collect();
+
+        self.glyph_brush
+            .queue_pre_positioned(upright, extras.clone(), bounds);
+
+        let turn = vertical::quarter_turn(origin);
+
+        for extra in &mut extras {
+            extra.pre_transform(turn);
+        }
+
+        // Glyphs are clipped before being turned
+        self.glyph_brush.queue_pre_positioned(
+            turned,
+            extras,
+            vertical::sideways_bounds(bounds, origin),
+        );
+    }
+
+    /// Returns the [`FontDatabase`](struct.FontDatabase.html) used to resolve
+    /// fonts by family name.
+    pub fn font_database(&self) -> &FontDatabase {
+        &self.font_database
+    }
+
+    /// Returns a mutable reference to the
+    /// [`FontDatabase`](struct.FontDatabase.html) used to resolve fonts by
+    /// family name, e.g. to index additional directories.
+    pub fn font_database_mut(&mut self) -> &mut FontDatabase {
+        &mut self.font_database
+    }
+
+    /// Adds a gradient with the given color stops, to paint texts with a
+    /// [`Fill::LinearGradient`](enum.Fill.html#variant.LinearGradient) or a
+    /// [`Fill::RadialGradient`](enum.Fill.html#variant.RadialGradient).
+    ///
+    /// A brush holds 64 gradients of up to 8 stops. Extra stops are
+    /// ignored, and the texts using a gradient added past the limit are
+    /// drawn with their color, logging a warning.
+    pub fn add_gradient(&mut self, stops: &[ColorStop]) -> GradientId {
+        self.gradients.add(stops)
+    }
+
+    /// Replaces the color stops of a gradient.
+    ///
+    /// The next draw uses them for all the texts painted with the gradient,
+    /// including the ones already queued, without laying them out again.
+    pub fn set_gradient(&mut self, id: GradientId, stops: &[ColorStop]) {
+        self.gradients.set(id, stops);
+    }
+
+    /// Sets the texture used to paint the texts with a
+    /// [`Fill::Texture`](enum.Fill.html#variant.Texture).
+    ///
+    /// The view must be of a 2D texture with a filterable float format. It
+    /// is repeated in every direction. Until a texture is set, texture fills
+    /// are transparent.
+    pub fn set_fill_texture(
+        &mut self,
+        device: &wgpu::Device,
+        view: &wgpu::TextureView,
+    ) {
+        self.pipeline.set_fill_texture(device, view);
+    }
+
+    /// Sets the view-projection matrix of the camera, in column-major order,
+    /// used to project the sections with a
+    /// [`Billboard`](struct.Billboard.html). Defaults to the identity.
+    ///
+    /// The camera is assumed to have a symmetric projection, so its right
+    /// and up axes can be read from the matrix. It is used by the next
+    /// draws, including the billboards already queued, without laying them
+    /// out again.
+    pub fn set_camera(&mut self, view_projection: [f32; 16]) {
+        self.pipeline.set_camera(view_projection);
+    }
+
+    /// Sets the function called on every glyph of the
+    /// [`animated`](struct.TextAttributes.html#method.animated) texts of a
+    /// frame, before it is drawn. It can move, recolor or hide the glyph.
+    ///
+    /// Sections with animated texts skip the vertex cache of the brush and
+    /// are drawn after the other sections of the frame. Their decorations
+    /// are not animated.
+    ///
+    /// ```
+    /// use std::time::Instant;
+    /// use wgpu_glyph::{AnimatedGlyph, GlyphBrush};
+    ///
+    /// fn wave(brush: &mut GlyphBrush<()>) {
+    ///     let start = Instant::now();
+    ///
+    ///     brush.set_animation(move |glyph: &mut AnimatedGlyph<'_>| {
+    ///         let phase = start.elapsed().as_secs_f32() * 4.0
+    ///             - glyph.char_index as f32 * 0.5;
+    ///
+    ///         glyph.vertex.pixel_coords.min.y += phase.sin() * 3.0;
+    ///         glyph.vertex.pixel_coords.max.y += phase.sin() * 3.0;
+    ///     });
+    /// }
+    /// ```
+    pub fn set_animation<A>(&mut self, animation: A)
+    where
+        A: FnMut(&mut AnimatedGlyph<'_>) + Send + Sync + 'static,
+    {
+        self.animation = Some(Box::new(animation));
+    }
+
+    /// Removes the animation of the brush, drawing animated texts as laid
+    /// out.
+    pub fn remove_animation(&mut self) {
+        self.animation = None;
+    }
+
+    /// Defines a [`TextStyle`](struct.TextStyle.html) that texts can refer
+    /// to by name or by the returned [`StyleId`](struct.StyleId.html).
+    ///
+    /// Defining a style with the name of an existing one replaces it, like
+    /// [`set_style`](#method.set_style), and returns the same handle.
+    pub fn define_style(
+        &mut self,
+        name: impl Into<String>,
+        style: TextStyle,
+    ) -> StyleId {
+        self.styles.define(name.into(), style)
+    }
+
+    /// Returns the handle of the style with the given name, if defined.
+    pub fn style_id(&self, name: &str) -> Option<StyleId> {
+        self.styles.id(name)
+    }
+
+    /// Returns the style with the given handle.
+    pub fn style(&self, id: StyleId) -> Option<&TextStyle> {
+        self.styles.get(id)
+    }
+
+    /// Replaces the style with the given handle.
+    ///
+    /// A new color is used by all the texts with the style in the next
+    /// draw, including the ones already queued, without laying them out
+    /// again. A new font or scale is used by the texts queued afterwards.
+    pub fn set_style(&mut self, id: StyleId, style: TextStyle) {
+        self.styles.set(id, style);
+    }
+
+    /// Replaces the color of the style with the given handle, see
+    /// [`set_style`](#method.set_style).
+    pub fn set_style_color(&mut self, id: StyleId, color: [f32; 4]) {
+        if let Some(style) = self.styles.get(id) {
+            self.styles.set(id, TextStyle { color, ..*style });
+        }
+    }
+
+    /// Replaces the colors of the palette of the brush, used by the texts
+    /// with a palette color, set with
+    /// [`TextAttributes::with_palette_color`](struct.TextAttributes.html#method.with_palette_color).
+    ///
+    /// The palette lives in a small uniform buffer, so the next draw
+    /// recolors all these texts, including the ones already queued, with a
+    /// single write and without laying them out again. Only the first 256
+    /// colors are kept. Texts indexing past the end of the palette are
+    /// transparent, and past 256 colors they keep their own color.
+    ///
+    /// ```
+    /// use wgpu_glyph::{
+    ///     GlyphBrush, RichSection, Section, SectionAttributes, Text,
+    ///     TextAttributes,
+    /// };
+    ///
+    /// const FOREGROUND: u32 = 0;
+    /// const ACCENT: u32 = 1;
+    ///
+    /// fn theme(brush: &mut GlyphBrush<()>, dark: bool) {
+    ///     if dark {
+    ///         brush.set_palette(&[[0.9, 0.9, 0.9, 1.0], [0.4, 0.6, 1.0, 1.0]]);
+    ///     } else {
+    ///         brush.set_palette(&[[0.1, 0.1, 0.1, 1.0], [0.0, 0.3, 0.8, 1.0]]);
+    ///     }
+    /// }
+    ///
+    /// fn label(brush: &mut GlyphBrush<()>) {
+    ///     let color = |index| {
+    ///         SectionAttributes::default().with_text(
+    ///             0,
+    ///             TextAttributes::default().with_palette_color(index),
+    ///         )
+    ///     };
+    ///
+    ///     brush.queue(RichSection::new(
+    ///         Section::default().add_text(Text::new("Theme: ")),
+    ///         color(FOREGROUND),
+    ///     ));
+    ///     brush.queue(RichSection::new(
+    ///         Section::default().add_text(Text::new("dark")),
+    ///         color(ACCENT),
+    ///     ));
+    /// }
+    /// ```
+    pub fn set_palette(&mut self, colors: &[[f32; 4]]) {
+        self.palette.set(colors);
+    }
+
+    /// Adds an image with the given pixels, in rows of 8-bit sRGB RGBA
+    /// values, to be drawn in an [`InlineObject`](struct.InlineObject.html).
+    ///
+    /// Images are packed in an atlas shared by the brush, of 1024x1024
+    /// pixels at first, which doubles in size when an image does not fit,
+    /// up to the largest texture of the device. An image that does not fit
+    /// even then, or with fewer bytes than its pixels need, is never drawn,
+    /// and a warning is logged.
+    ///
+    /// Remove the images you no longer draw with
+    /// [`remove_image`](#method.remove_image) to reuse their room.
+    pub fn add_image(
+        &mut self,
+        width: u32,
+        height: u32,
+        rgba: &[u8],
+    ) -> ImageId {
+        self.images.add(width, height, rgba)
+    }
+
+    /// Removes an image added with [`add_image`](#method.add_image),
+    /// freeing its room in the atlas for the images added after it.
+    ///
+    /// Inline objects queued afterwards draw nothing in place of the image.
+    /// Its room is reused by the next images added, so remove it after
+    /// drawing the sections queued with it.
+    pub fn remove_image(&mut self, id: ImageId) {
+        self.images.remove(id)
+    }
+}
+
+impl<D, F, H> GlyphBrush<D, F, H>
+where
+    F: Font + From<ab_glyph::FontVec>,
+    H: BuildHasher,
+{
+    /// Returns the [`FontId`](struct.FontId.html) of the normal face of the
+    /// given family that best matches the given weight.
+    ///
+    /// The face is looked up in the [`FontDatabase`](struct.FontDatabase.html)
+    /// of the brush and loaded the first time it is requested.
+    ///
+    /// Returns `None` if no face of the family is indexed or if it fails to
+    /// load.
+    pub fn font_id(&mut self, family: &str, weight: Weight) -> Option<FontId> {
+        self.query_font_id(&Query::new(family).with_weight(weight))
+    }
+
+    /// Returns the [`FontId`](struct.FontId.html) of the face that best
+    /// matches the given [`Query`](struct.Query.html), loading it if needed.
+    ///
+    /// See [`font_id`](#method.font_id).
+    pub fn query_font_id(&mut self, query: &Query<'_>) -> Option<FontId> {
+        let face = self.font_database.query(query)?;
+
+        if let Some(font_id) = self.loaded_faces.get(&face) {
+            return Some(*font_id);
+        }
+
+        match self.font_database.load(face) {
+            Ok(font) => {
+                let font_id = self.glyph_brush.add_font(font);
+                let _ = self.loaded_faces.insert(face, font_id);
+
+                Some(font_id)
+            }
+            Err(error) => {
+                warn!(
+                    "Failed to load font {}: {error}",
+                    self.font_database.faces()[face].path.display()
+                );
+
+                None
+            }
+        }
+    }
+}
+
+impl<D, F, H> GlyphBrush<D, F, H>
+where
+    F: Font + From<BmFont>,
+    H: BuildHasher,
+{
+    /// Adds a [`BmFont`](struct.BmFont.html) to the brush, with the pixels
+    /// of its pages, in rows of 8-bit sRGB RGBA values and in the order of
+    /// their IDs, and returns its [`FontId`](struct.FontId.html).
+    ///
+    /// The pages are added to the atlas of
+    /// [`add_image`](#method.add_image). Returns an error, without adding
+    /// the font or any of its pages, if a page has fewer bytes than its
+    /// pixels need or does not fit in the atlas.
+    pub fn add_bm_font(
+        &mut self,
+        font: BmFont,
+        pages: &[&[u8]],
+    ) -> Result<FontId, InvalidBitmapFont> {
+        let (width, height) = font.page_size();
+
+        if pages.len() < font.pages().len() && log_enabled!(log::Level::Warn) {
+            warn!(
+                "BMFont has {} pages, got {}, the glyphs of the missing \
+                 pages are not drawn",
+                font.pages().len(),
+                pages.len()
+            );
+        }
+
+        let mut images = Vec::with_capacity(pages.len());
+
+        for (index, rgba) in pages.iter().enumerate() {
+            match self.images.try_add(width, height, rgba) {
+                Ok(image) => images.push(image),
+                Err(reason) => {
+                    for image in images {
+                        self.images.remove(image);
+                    }
+
+                    return Err(InvalidBitmapFont::new(format!(
+                        "page {index} cannot be stored: {reason}"
+                    )));
+                }
+            }
+        }
+
+        let atlas = bmfont::Atlas::new(&font, images);
+        let font_id = self.glyph_brush.add_font(font);
+
+        self.bm_fonts.insert(font_id, atlas);
+
+        Ok(font_id)
+    }
 }
 
 impl<D, F, H> GlyphBrush<D, F, H>
@@ -142,7 +1236,22 @@ where
         staging_belt: &mut wgpu::util::StagingBelt,
         encoder: &mut wgpu::CommandEncoder,
     ) {
+        let animated = std::mem::take(&mut self.animated);
+        self.queued_sections = 0;
+        self.section_keys.clear();
+
+        for (index, section) in animated.iter().enumerate() {
+            let (glyphs, extras) = section.queued(index);
+
+            self.glyph_brush.queue_pre_positioned(
+                glyphs,
+                extras,
+                AnimatedSection::unbounded(),
+            );
+        }
+
         let pipeline = &mut self.pipeline;
+        let gradients = &self.gradients;
 
         let mut brush_action;
 
@@ -161,7 +1270,7 @@ where
                         tex_data,
                     );
                 },
-                Instance::from_vertex,
+                |vertex| Glyph::from_vertex(vertex, gradients),
             );
 
             match brush_action {
@@ -200,36 +1309,235 @@ where
             }
         }
 
+        if let Some(gradients) = self.gradients.take_changes() {
+            self.pipeline
+                .update_gradients(staging_belt, encoder, &gradients);
+        }
+
+        if let Some(colors) = self.styles.take_changes() {
+            self.pipeline
+                .update_palette(staging_belt, encoder, 0, &colors);
+        }
+
+        if let Some(colors) = self.palette.take_changes() {
+            self.pipeline.update_palette(
+                staging_belt,
+                encoder,
+                palette::Palette::OFFSET,
+                &colors,
+            );
+        }
+
+        if let Some(size) = self.images.take_resize() {
+            self.pipeline.increase_images_size(device, encoder, size);
+        }
+
+        for upload in self.images.take_uploads() {
+            self.pipeline.update_images(
+                device,
+                staging_belt,
+                encoder,
+                upload.offset,
+                upload.size,
+                &upload.data,
+            );
+        }
+
+        let quads = std::mem::take(&mut self.quads);
+
         match brush_action.unwrap() {
             BrushAction::Draw(verts) => {
-                self.pipeline.upload(device, staging_belt, encoder, &verts);
+                let (animated_verts, verts): (Vec<_>, Vec<_>) = verts
+                    .into_iter()
+                    .partition(|glyph| glyph.animation.is_some());
+
+                self.animated_vertices = animated_verts
+                    .into_iter()
+                    .filter_map(|glyph| {
+                        Some(AnimatedVertex {
+                            id: glyph.animation?,
+                            tex_coords: glyph.tex_coords,
+                            pixel_coords: glyph.pixel_coords,
+                        })
+                    })
+                    .collect();
+
+                self.section_runs = Runs::new(&verts);
+                self.shadows =
+                    verts.iter().filter_map(|glyph| glyph.shadow).collect();
+                self.instances =
+                    verts.into_iter().map(|glyph| glyph.instance).collect();
+            }
+            BrushAction::ReDraw
+                if quads == self.drawn_quads && animated.is_empty() =>
+            {
+                self.drawn_animated = animated;
+                return;
             }
             BrushAction::ReDraw => {}
         };
+
+        self.drawn_animated = animated;
+
+        let (animated_shadows, animated_instances) = self.animate();
+
+        if self.shadows.is_empty()
+            && animated_shadows.is_empty()
+            && quads.is_empty()
+        {
+            self.pipeline.upload(
+                device,
+                staging_belt,
+                encoder,
+                &self.instances,
+            );
+        } else {
+            let mut instances: Vec<Quad> = self
+                .shadows
+                .iter()
+                .chain(&animated_shadows)
+                .copied()
+                .collect();
+
+            self.section_runs.order(
+                &self.instances,
+                &quads,
+                &animated_instances,
+                &mut instances,
+            );
+
+            self.pipeline
+                .upload(device, staging_belt, encoder, &instances);
+        }
+
+        self.drawn_quads = quads;
+    }
+
+    /// Runs the animation on the glyphs of the animated sections, returning
+    /// their shadows and the instances of every section.
+    fn animate(&mut self) -> (Vec<Quad>, Vec<Vec<Quad>>) {
+        let mut shadows = Vec::new();
+        let mut instances = vec![Vec::new(); self.drawn_animated.len()];
+
+        for vertex in &self.animated_vertices {
+            let section = &self.drawn_animated[vertex.id.section];
+            let sg = &section.glyphs[vertex.id.glyph];
+            let extra = &section.extras[sg.section_index];
+            let text_extra = Extra {
+                color: extra.color,
+                z: extra.z,
+            };
+
+            let mut glyph = AnimatedGlyph {
+                vertex: glyph_brush::GlyphVertex {
+                    tex_coords: vertex.tex_coords,
+                    pixel_coords: vertex.pixel_coords,
+                    bounds: section.bounds,
+                    extra: &text_extra,
+                },
+                section_index: section.index,
+                text_index: sg.section_index,
+                byte_offset: sg.byte_index,
+                char_index: section.char_indices[vertex.id.glyph],
+                color: extra.color,
+                visible: true,
+            };
+
+            if extra.animated
+                && let Some(animation) = &mut self.animation
+            {
+                animation(&mut glyph);
+            }
+
+            let pixel_coords = glyph.vertex.pixel_coords;
+            let bounds = glyph.vertex.bounds;
+
+            let outside = pixel_coords.min.x > bounds.max.x
+                || pixel_coords.min.y > bounds.max.y
+                || bounds.min.x > pixel_coords.max.x
+                || bounds.min.y > pixel_coords.max.y;
+
+            if !glyph.visible || outside {
+                continue;
+            }
+
+            let color = glyph.color;
+            let glyph = Glyph::from_vertex(
+                glyph_brush::GlyphVertex {
+                    tex_coords: glyph.vertex.tex_coords,
+                    pixel_coords,
+                    bounds,
+                    extra,
+                },
+                &self.gradients,
+            );
+
+            shadows.extend(glyph.shadow);
+
+            // Keep the color of the style unless the animation replaced it
+            instances[vertex.id.section].push(if color == extra.color {
+                glyph.instance
+            } else {
+                glyph.instance.with_color(color)
+            });
+        }
+
+        (shadows, instances)
+    }
+}
+
+impl<D, F: Font, H: BuildHasher> GlyphBrush<D, F, H> {
+    fn with_pipeline(
+        pipeline: Pipeline<D>,
+        glyph_brush: glyph_brush::GlyphBrush<Glyph, GlyphExtra, F, H>,
+        font_database: FontDatabase,
+    ) -> Self {
+        let images = inline::Images::new(pipeline.max_texture_size());
+
+        GlyphBrush {
+            pipeline,
+            glyph_brush,
+            font_database,
+            loaded_faces: HashMap::new(),
+            decoration_metrics: HashMap::new(),
+            gradients: fill::Gradients::new(),
+            styles: style::Styles::new(),
+            palette: palette::Palette::new(),
+            images,
+            bm_fonts: bmfont::BmFonts::default(),
+            animation: None,
+            queued_sections: 0,
+            animated: Vec::new(),
+            drawn_animated: Vec::new(),
+            animated_vertices: Vec::new(),
+            instances: Vec::new(),
+            shadows: Vec::new(),
+            section_runs: Runs::default(),
+            section_keys: HashSet::new(),
+            quads: Vec::new(),
+            drawn_quads: Vec::new(),
+        }
     }
 }
 
 impl<F: Font + Sync, H: BuildHasher> GlyphBrush<(), F, H> {
     fn new(
         device: &wgpu::Device,
-        filter_mode: wgpu::FilterMode,
-        multisample: wgpu::MultisampleState,
         render_format: wgpu::TextureFormat,
-        raw_builder: glyph_brush::GlyphBrushBuilder<F, H>,
+        builder: GlyphBrushBuilder<(), F, H>,
     ) -> Self {
-        let glyph_brush = raw_builder.build();
+        let glyph_brush = builder.inner.build();
         let (cache_width, cache_height) = glyph_brush.texture_dimensions();
-        GlyphBrush {
-            pipeline: Pipeline::<()>::new(
-                device,
-                filter_mode,
-                multisample,
-                render_format,
-                cache_width,
-                cache_height,
-            ),
-            glyph_brush,
-        }
+        let pipeline = Pipeline::<()>::new(
+            device,
+            builder.texture_filter_method,
+            builder.multisample_state,
+            render_format,
+            cache_width,
+            cache_height,
+        );
+
+        GlyphBrush::with_pipeline(pipeline, glyph_brush, builder.font_database)
     }
 
     /// Draws all queued sections onto a render target.
@@ -330,26 +1638,22 @@ impl<F: Font + Sync, H: BuildHasher> GlyphBrush<(), F, H> {
 impl<F: Font + Sync, H: BuildHasher> GlyphBrush<wgpu::DepthStencilState, F, H> {
     fn new(
         device: &wgpu::Device,
-        filter_mode: wgpu::FilterMode,
-        multisample: wgpu::MultisampleState,
         render_format: wgpu::TextureFormat,
-        depth_stencil_state: wgpu::DepthStencilState,
-        raw_builder: glyph_brush::GlyphBrushBuilder<F, H>,
+        builder: GlyphBrushBuilder<wgpu::DepthStencilState, F, H>,
     ) -> Self {
-        let glyph_brush = raw_builder.build();
+        let glyph_brush = builder.inner.build();
         let (cache_width, cache_height) = glyph_brush.texture_dimensions();
-        GlyphBrush {
-            pipeline: Pipeline::<wgpu::DepthStencilState>::new(
-                device,
-                filter_mode,
-                multisample,
-                render_format,
-                depth_stencil_state,
-                cache_width,
-                cache_height,
-            ),
-            glyph_brush,
-        }
+        let pipeline = Pipeline::<wgpu::DepthStencilState>::new(
+            device,
+            builder.texture_filter_method,
+            builder.multisample_state,
+            render_format,
+            builder.depth,
+            cache_width,
+            cache_height,
+        );
+
+        GlyphBrush::with_pipeline(pipeline, glyph_brush, builder.font_database)
     }
 
     /// Draws all queued sections onto a render target.
@@ -363,6 +1667,7 @@ impl<F: Font + Sync, H: BuildHasher> GlyphBrush<wgpu::DepthStencilState, F, H> {
     /// Panics if the provided `target` has a texture format that does not match
     /// the `render_format` provided on creation of the `GlyphBrush`.
     #[inline]
+    #[allow(clippy::too_many_arguments)]
     pub fn draw_queued(
         &mut self,
         device: &wgpu::Device,
@@ -429,6 +1734,7 @@ impl<F: Font + Sync, H: BuildHasher> GlyphBrush<wgpu::DepthStencilState, F, H> {
     /// Panics if the provided `target` has a texture format that does not match
     /// the `render_format` provided on creation of the `GlyphBrush`.
     #[inline]
+    #[allow(clippy::too_many_arguments)]
     pub fn draw_queued_with_transform_and_scissoring(
         &mut self,
         device: &wgpu::Device,
@@ -465,6 +1771,96 @@ pub fn orthographic_projection(width: u32, height: u32) -> [f32; 16] {
     ]
 }
 
+impl<D, F: Font, H: BuildHasher> GlyphBrush<D, F, H> {
+    /// Returns the glyphs of the section, like
+    /// [`GlyphCruncher::glyphs`](trait.GlyphCruncher.html#tymethod.glyphs),
+    /// honouring the attributes of a
+    /// [`RichSection`](struct.RichSection.html).
+    ///
+    /// Benefits from caching, see [caching behaviour](#caching-behaviour).
+    #[inline]
+    pub fn glyphs<'a, 'b, S>(&'b mut self, section: S) -> SectionGlyphIter<'b>
+    where
+        S: Into<RichSection<'a>>,
+    {
+        let section = section.into();
+        let layout = SectionLayout::of(&section);
+
+        self.glyphs_custom_layout(section, &layout)
+    }
+
+    /// Returns the glyphs of the section laid out with a custom layout.
+    ///
+    /// See [`glyphs`](#method.glyphs).
+    #[inline]
+    pub fn glyphs_custom_layout<'a, 'b, S, L>(
+        &'b mut self,
+        section: S,
+        custom_layout: &L,
+    ) -> SectionGlyphIter<'b>
+    where
+        L: GlyphPositioner + std::hash::Hash,
+        S: Into<RichSection<'a>>,
+    {
+        let section = extra::glyph_section(&section.into(), &self.styles);
+
+        self.glyph_brush
+            .glyphs_custom_layout(section, custom_layout)
+    }
+
+    /// Returns the bounds of the glyphs and inline objects of the section,
+    /// like
+    /// [`GlyphCruncher::glyph_bounds`](trait.GlyphCruncher.html#method.glyph_bounds),
+    /// honouring the attributes of a
+    /// [`RichSection`](struct.RichSection.html).
+    ///
+    /// Benefits from caching, see [caching behaviour](#caching-behaviour).
+    #[inline]
+    pub fn glyph_bounds<'a, S>(&mut self, section: S) -> Option<Rect>
+    where
+        S: Into<RichSection<'a>>,
+    {
+        let section = section.into();
+        let layout = SectionLayout::of(&section);
+
+        self.glyph_bounds_custom_layout(section, &layout)
+    }
+
+    /// Returns the bounds of the glyphs and inline objects of the section
+    /// laid out with a custom layout.
+    ///
+    /// See [`glyph_bounds`](#method.glyph_bounds).
+    pub fn glyph_bounds_custom_layout<'a, S, L>(
+        &mut self,
+        section: S,
+        custom_layout: &L,
+    ) -> Option<Rect>
+    where
+        L: GlyphPositioner + std::hash::Hash,
+        S: Into<RichSection<'a>>,
+    {
+        let section = section.into();
+        let attributes = &section.attributes;
+        let glyph_section = extra::glyph_section(&section, &self.styles);
+
+        if !inline::has_objects(attributes) {
+            return self
+                .glyph_brush
+                .glyph_bounds_custom_layout(glyph_section, custom_layout);
+        }
+
+        let glyph_bounds = self
+            .glyph_brush
+            .glyph_bounds_custom_layout(&glyph_section, custom_layout);
+
+        let glyphs = self.section_glyphs(&glyph_section, custom_layout);
+        let bounds =
+            custom_layout.bounds_rect(&SectionGeometry::from(&glyph_section));
+
+        inline::bounds(attributes, &glyphs, glyph_bounds, bounds)
+    }
+}
+
 impl<D, F: Font, H: BuildHasher> GlyphCruncher<F> for GlyphBrush<D, F, H> {
     #[inline]
     fn glyphs_custom_layout<'a, 'b, S, L>(
@@ -476,8 +1872,15 @@ impl<D, F: Font, H: BuildHasher> GlyphCruncher<F> for GlyphBrush<D, F, H> {
         L: GlyphPositioner + std::hash::Hash,
         S: Into<Cow<'a, Section<'a>>>,
     {
-        self.glyph_brush
-            .glyphs_custom_layout(section, custom_layout)
+        GlyphBrush::glyphs_custom_layout(self, section.into(), custom_layout)
+    }
+
+    #[inline]
+    fn glyphs<'a, 'b, S>(&'b mut self, section: S) -> SectionGlyphIter<'b>
+    where
+        S: Into<Cow<'a, Section<'a>>>,
+    {
+        GlyphBrush::glyphs(self, section.into())
     }
 
     #[inline]
@@ -485,6 +1888,14 @@ impl<D, F: Font, H: BuildHasher> GlyphCruncher<F> for GlyphBrush<D, F, H> {
         self.glyph_brush.fonts()
     }
 
+    #[inline]
+    fn glyph_bounds<'a, S>(&mut self, section: S) -> Option<Rect>
+    where
+        S: Into<Cow<'a, Section<'a>>>,
+    {
+        GlyphBrush::glyph_bounds(self, section.into())
+    }
+
     #[inline]
     fn glyph_bounds_custom_layout<'a, S, L>(
         &mut self,
@@ -495,8 +1906,11 @@ impl<D, F: Font, H: BuildHasher> GlyphCruncher<F> for GlyphBrush<D, F, H> {
         L: GlyphPositioner + std::hash::Hash,
         S: Into<Cow<'a, Section<'a>>>,
     {
-        self.glyph_brush
-            .glyph_bounds_custom_layout(section, custom_layout)
+        GlyphBrush::glyph_bounds_custom_layout(
+            self,
+            section.into(),
+            custom_layout,
+        )
     }
 }
 
@@ -0,0 +1,563 @@
+//! Rich text markup.
+//!
+//! Parses text with inline style tags into an
+//! [`OwnedRichSection`](../struct.OwnedRichSection.html), so styling can
+//! live inside translated strings:
+//!
+//! ```
+//! use wgpu_glyph::FontId;
+//! use wgpu_glyph::markup::{self, StyleMap};
+//!
+//! let styles = StyleMap::new(FontId(0), 18.0, [0.0, 0.0, 0.0, 1.0])
+//!     .with_bold(FontId(1))
+//!     .with_font("Mono", FontId(2));
+//!
+//! let section = markup::parse(
+//!     "Press <b>Enter</b> or type <font=Mono><color=#2060ff>help</color></font>",
+//!     &styles,
+//! )
+//! .unwrap();
+//!
+//! assert_eq!(section.section.text.len(), 4);
+//! ```
+//!
+//! The supported tags are:
+//!
+//! - `<b>` and `<i>`, using the bold, italic and bold italic fonts of the
+//!   [`StyleMap`](struct.StyleMap.html).
+//! - `<u>` and `<s>`, to underline and strike through the text.
+//! - `<color=#rrggbb>` or `<color=#rrggbbaa>`, and the short `#rgb` and
+//!   `#rgba` forms.
+//! - `<size=20>`, the scale of the text in pixels, finite and positive.
+//! - `<font=Name>`, a font registered in the style map.
+//!
+//! Every tag is closed with `</name>`, in the reverse order it was opened.
+//! The characters `<`, `>` and `&` are written as `&lt;`, `&gt;` and `&amp;`.
+use crate::{
+    Decoration, DecorationStyle, FontId, OwnedRichSection, OwnedText,
+    TextAttributes,
+};
+
+use glyph_brush::ab_glyph::PxScale;
+
+use std::collections::HashMap;
+
+/// The fonts and default style of the text parsed by
+/// [`parse`](fn.parse.html).
+#[derive(Debug, Clone, PartialEq)]
+pub struct StyleMap {
+    font_id: FontId,
+    scale: f32,
+    color: [f32; 4],
+    bold: Option<FontId>,
+    italic: Option<FontId>,
+    bold_italic: Option<FontId>,
+    fonts: HashMap<String, FontId>,
+}
+
+impl StyleMap {
+    /// Creates a `StyleMap` with the style of the text outside of tags.
+    pub fn new(font_id: FontId, scale: f32, color: [f32; 4]) -> Self {
+        StyleMap {
+            font_id,
+            scale,
+            color,
+            bold: None,
+            italic: None,
+            bold_italic: None,
+            fonts: HashMap::new(),
+        }
+    }
+
+    /// Sets the font of the text in `<b>` tags.
+    pub fn with_bold(mut self, font_id: FontId) -> Self {
+        self.bold = Some(font_id);
+        self
+    }
+
+    /// Sets the font of the text in `<i>` tags.
+    pub fn with_italic(mut self, font_id: FontId) -> Self {
+        self.italic = Some(font_id);
+        self
+    }
+
+    /// Sets the font of the text in both `<b>` and `<i>` tags. Defaults to
+    /// the bold font.
+    pub fn with_bold_italic(mut self, font_id: FontId) -> Self {
+        self.bold_italic = Some(font_id);
+        self
+    }
+
+    /// Registers a font for `<font=name>` tags.
+    pub fn with_font(
+        mut self,
+        name: impl Into<String>,
+        font_id: FontId,
+    ) -> Self {
+        let _ = self.fonts.insert(name.into(), font_id);
+        self
+    }
+
+    fn font(&self, style: &Style) -> FontId {
+        let variant = match (style.bold, style.italic) {
+            (true, true) => self.bold_italic.or(self.bold),
+            (true, false) => self.bold,
+            (false, true) => self.italic,
+            (false, false) => None,
+        };
+
+        style.font.or(variant).unwrap_or(self.font_id)
+    }
+}
+
+/// An error found by [`parse`](fn.parse.html), at a byte position of the
+/// markup.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// The byte position of the start of the tag or entity that failed to
+    /// parse.
+    pub position: usize,
+    /// What went wrong.
+    pub kind: ParseErrorKind,
+}
+
+/// The reason of a [`ParseError`](struct.ParseError.html).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    /// A `<` without a matching `>`.
+    UnterminatedTag,
+    /// A tag that is not supported.
+    UnknownTag(String),
+    /// A tag that needs a value, like `<size>`, without one.
+    MissingValue(String),
+    /// A tag that takes no value, like `<b>`, with one.
+    UnexpectedValue(String),
+    /// A value that cannot be parsed for its tag.
+    InvalidValue {
+        /// The name of the tag.
+        tag: String,
+        /// The value of the tag.
+        value: String,
+    },
+    /// A `<font>` tag naming a font missing from the style map.
+    UnknownFont(String),
+    /// A closing tag that does not match the last opened tag, if any.
+    UnexpectedClose {
+        /// The name of the last opened tag, if any.
+        expected: Option<String>,
+        /// The name of the closing tag.
+        found: String,
+    },
+    /// A tag that is never closed.
+    Unclosed(String),
+    /// An `&` that does not start `&lt;`, `&gt;` or `&amp;`.
+    UnknownEntity,
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.kind {
+            ParseErrorKind::UnterminatedTag => write!(f, "unterminated tag"),
+            ParseErrorKind::UnknownTag(tag) => write!(f, "unknown tag <{tag}>"),
+            ParseErrorKind::MissingValue(tag) => {
+                write!(f, "tag <{tag}> needs a value")
+            }
+            ParseErrorKind::UnexpectedValue(tag) => {
+                write!(f, "tag <{tag}> takes no value")
+            }
+            ParseErrorKind::InvalidValue { tag, value } => {
+                write!(f, "invalid value `{value}` for tag <{tag}>")
+            }
+            ParseErrorKind::UnknownFont(name) => {
+                write!(f, "unknown font `{name}`")
+            }
+            ParseErrorKind::UnexpectedClose {
+                expected: Some(expected),
+                found,
+            } => write!(f, "expected </{expected}>, found </{found}>"),
+            ParseErrorKind::UnexpectedClose {
+                expected: None,
+                found,
+            } => write!(f, "unexpected </{found}>"),
+            ParseErrorKind::Unclosed(tag) => write!(f, "unclosed tag <{tag}>"),
+            ParseErrorKind::UnknownEntity => write!(f, "unknown entity"),
+        }?;
+
+        write!(f, " at byte {}", self.position)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// The style of the text at a point of the markup.
+#[derive(Debug, Clone, Default)]
+struct Style {
+    font: Option<FontId>,
+    bold: bool,
+    italic: bool,
+    underline: bool,
+    strikethrough: bool,
+    scale: Option<f32>,
+    color: Option<[f32; 4]>,
+}
+
+/// Parses the markup into an
+/// [`OwnedRichSection`](../struct.OwnedRichSection.html) with a `Text` for
+/// every run of text with the same style.
+pub fn parse(
+    markup: &str,
+    styles: &StyleMap,
+) -> Result<OwnedRichSection, ParseError> {
+    let mut section = OwnedRichSection::default();
+    let mut open: Vec<(String, usize, Style)> = Vec::new();
+    let mut style = Style::default();
+    let mut run = String::new();
+    let mut rest = markup;
+
+    let error =
+        |position: usize, kind: ParseErrorKind| ParseError { position, kind };
+
+    while let Some(start) = rest.find(['<', '&']) {
+        let position = markup.len() - rest.len() + start;
+
+        run.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        if rest.starts_with('&') {
+            let (entity, c) = [("&lt;", '<'), ("&gt;", '>'), ("&amp;", '&')]
+                .into_iter()
+                .find(|(entity, _)| rest.starts_with(entity))
+                .ok_or_else(|| {
+                    error(position, ParseErrorKind::UnknownEntity)
+                })?;
+
+            run.push(c);
+            rest = &rest[entity.len()..];
+            continue;
+        }
+
+        let end = rest
+            .find('>')
+            .ok_or_else(|| error(position, ParseErrorKind::UnterminatedTag))?;
+
+        let tag = rest[1..end].trim();
+        rest = &rest[end + 1..];
+
+        flush(&mut section, &mut run, &style, styles);
+
+        if let Some(name) = tag.strip_prefix('/') {
+            let name = name.trim();
+
+            match open.pop() {
+                Some((open_name, _, previous)) if open_name == name => {
+                    style = previous;
+                }
+                last => {
+                    return Err(error(
+                        position,
+                        ParseErrorKind::UnexpectedClose {
+                            expected: last.map(|(name, _, _)| name),
+                            found: name.to_owned(),
+                        },
+                    ));
+                }
+            }
+
+            continue;
+        }
+
+        let (name, value) = match tag.split_once('=') {
+            Some((name, value)) => (name.trim(), Some(value.trim())),
+            None => (tag, None),
+        };
+
+        let flag = || {
+            if value.is_some() {
+                return Err(error(
+                    position,
+                    ParseErrorKind::UnexpectedValue(name.to_owned()),
+                ));
+            }
+
+            Ok(true)
+        };
+
+        let value = || {
+            value.ok_or_else(|| {
+                error(position, ParseErrorKind::MissingValue(name.to_owned()))
+            })
+        };
+
+        let invalid = |value: &str| {
+            error(
+                position,
+                ParseErrorKind::InvalidValue {
+                    tag: name.to_owned(),
+                    value: value.to_owned(),
+                },
+            )
+        };
+
+        let previous = style.clone();
+
+        match name {
+            "b" => style.bold = flag()?,
+            "i" => style.italic = flag()?,
+            "u" => style.underline = flag()?,
+            "s" => style.strikethrough = flag()?,
+            "color" => {
+                let value = value()?;
+
+                style.color =
+                    Some(parse_color(value).ok_or_else(|| invalid(value))?);
+            }
+            "size" => {
+                let value = value()?;
+
+                style.scale = Some(
+                    value
+                        .parse::<f32>()
+                        .ok()
+                        .filter(|size| size.is_finite() && *size > 0.0)
+                        .ok_or_else(|| invalid(value))?,
+                );
+            }
+            "font" => {
+                let value = value()?;
+
+                style.font =
+                    Some(*styles.fonts.get(value).ok_or_else(|| {
+                        error(
+                            position,
+                            ParseErrorKind::UnknownFont(value.to_owned()),
+                        )
+                    })?);
+            }
+            _ => {
+                return Err(error(
+                    position,
+                    ParseErrorKind::UnknownTag(name.to_owned()),
+                ));
+            }
+        }
+
+        open.push((name.to_owned(), position, previous));
+    }
+
+    if let Some((name, position, _)) = open.pop() {
+        return Err(error(position, ParseErrorKind::Unclosed(name)));
+    }
+
+    run.push_str(rest);
+    flush(&mut section, &mut run, &style, styles);
+
+    Ok(section)
+}
+
+/// Adds the text parsed since the last tag to the section.
+fn flush(
+    section: &mut OwnedRichSection,
+    run: &mut String,
+    style: &Style,
+    styles: &StyleMap,
+) {
+    if run.is_empty() {
+        return;
+    }
+
+    let decoration = Decoration::new(DecorationStyle::Solid);
+    let mut attributes = TextAttributes::default();
+
+    if style.underline {
+        attributes = attributes.with_underline(decoration);
+    }
+
+    if style.strikethrough {
+        attributes = attributes.with_strikethrough(decoration);
+    }
+
+    if attributes != TextAttributes::default() {
+        section.attributes = std::mem::take(&mut section.attributes)
+            .with_text(section.section.text.len(), attributes);
+    }
+
+    section.section.text.push(
+        OwnedText::default()
+            .with_text(std::mem::take(run))
+            .with_font_id(styles.font(style))
+            .with_scale(PxScale::from(style.scale.unwrap_or(styles.scale)))
+            .with_color(style.color.unwrap_or(styles.color)),
+    );
+}
+
+/// Parses a `#rgb`, `#rgba`, `#rrggbb` or `#rrggbbaa` color.
+fn parse_color(value: &str) -> Option<[f32; 4]> {
+    let hex = value.strip_prefix('#')?;
+
+    if !hex.is_ascii() {
+        return None;
+    }
+
+    let digits: Vec<u8> = match hex.len() {
+        3 | 4 => hex
+            .chars()
+            .map(|c| c.to_digit(16).map(|digit| digit as u8 * 17))
+            .collect::<Option<_>>()?,
+        6 | 8 if hex.chars().all(|c| c.is_ascii_hexdigit()) => (0..hex.len())
+            .step_by(2)
+            .map(|index| u8::from_str_radix(&hex[index..index + 2], 16).ok())
+            .collect::<Option<_>>()?,
+        _ => return None,
+    };
+
+    let channel = |index: usize| {
+        digits
+            .get(index)
+            .map_or(1.0, |digit| f32::from(*digit) / 255.0)
+    };
+
+    Some([channel(0), channel(1), channel(2), channel(3)])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn styles() -> StyleMap {
+        StyleMap::new(FontId(0), 16.0, [0.0, 0.0, 0.0, 1.0])
+            .with_bold(FontId(1))
+            .with_italic(FontId(2))
+            .with_font("Mono", FontId(3))
+    }
+
+    fn error(markup: &str) -> (usize, ParseErrorKind) {
+        let error = parse(markup, &styles()).unwrap_err();
+
+        (error.position, error.kind)
+    }
+
+    #[test]
+    fn splits_runs_by_style() {
+        let section = parse(
+            "a <b>b<i>c</i></b> <size=24><color=#f00>d</color></size>\
+             <font=Mono><u>e</u></font> &lt;&amp;&gt;",
+            &styles(),
+        )
+        .unwrap();
+
+        let runs: Vec<(&str, FontId, f32)> = section
+            .section
+            .text
+            .iter()
+            .map(|text| (text.text.as_str(), text.font_id, text.scale.y))
+            .collect();
+
+        assert_eq!(
+            runs,
+            [
+                ("a ", FontId(0), 16.0),
+                ("b", FontId(1), 16.0),
+                // Bold italic falls back to the bold font
+                ("c", FontId(1), 16.0),
+                (" ", FontId(0), 16.0),
+                ("d", FontId(0), 24.0),
+                ("e", FontId(3), 16.0),
+                (" <&>", FontId(0), 16.0),
+            ]
+        );
+        assert_eq!(section.section.text[4].extra.color, [1.0, 0.0, 0.0, 1.0]);
+        assert!(section.attributes.text(5).underline.is_some());
+        assert!(section.attributes.text(6).underline.is_none());
+        assert_eq!(section.attributes.texts.len(), 6);
+    }
+
+    #[test]
+    fn parses_colors() {
+        assert_eq!(parse_color("#fff"), Some([1.0; 4]));
+        assert_eq!(
+            parse_color("#00000080"),
+            Some([0.0, 0.0, 0.0, 128.0 / 255.0])
+        );
+        assert_eq!(parse_color("#12345"), None);
+        assert_eq!(parse_color("fff"), None);
+        assert_eq!(parse_color("#ggg"), None);
+        assert_eq!(parse_color("#ééé"), None);
+        assert_eq!(parse_color("#+1+2+3"), None);
+        assert_eq!(parse_color("#+f+f+f+f"), None);
+    }
+
+    #[test]
+    fn reports_errors_at_their_tag() {
+        let invalid = |tag: &str, value: &str| ParseErrorKind::InvalidValue {
+            tag: tag.to_owned(),
+            value: value.to_owned(),
+        };
+
+        assert_eq!(error("ab <b"), (3, ParseErrorKind::UnterminatedTag));
+        assert_eq!(
+            error("<b><q>"),
+            (3, ParseErrorKind::UnknownTag("q".to_owned()))
+        );
+        assert_eq!(
+            error("<color>"),
+            (0, ParseErrorKind::MissingValue("color".to_owned()))
+        );
+        assert_eq!(error("x<color=red>"), (1, invalid("color", "red")));
+        assert_eq!(
+            error("<b=3>x</b>"),
+            (0, ParseErrorKind::UnexpectedValue("b".to_owned()))
+        );
+        assert_eq!(
+            error("x<s = >y</s>"),
+            (1, ParseErrorKind::UnexpectedValue("s".to_owned()))
+        );
+        assert_eq!(
+            error("<font=Serif>"),
+            (0, ParseErrorKind::UnknownFont("Serif".to_owned()))
+        );
+        assert_eq!(
+            error("<b><i>x</b>"),
+            (
+                7,
+                ParseErrorKind::UnexpectedClose {
+                    expected: Some("i".to_owned()),
+                    found: "b".to_owned(),
+                }
+            )
+        );
+        assert_eq!(
+            error("x</b>"),
+            (
+                1,
+                ParseErrorKind::UnexpectedClose {
+                    expected: None,
+                    found: "b".to_owned(),
+                }
+            )
+        );
+        assert_eq!(
+            error("<b>x<u>y</u>"),
+            (0, ParseErrorKind::Unclosed("b".to_owned()))
+        );
+        assert_eq!(error("a && b"), (2, ParseErrorKind::UnknownEntity));
+    }
+
+    #[test]
+    fn rejects_sizes_that_are_not_finite_and_positive() {
+        for size in ["inf", "-inf", "NaN", "0", "-4", "1e39", "big"] {
+            assert_eq!(
+                error(&format!("<size={size}>x</size>")),
+                (
+                    0,
+                    ParseErrorKind::InvalidValue {
+                        tag: "size".to_owned(),
+                        value: size.to_owned(),
+                    }
+                ),
+                "{size}"
+            );
+        }
+
+        assert!(parse("<size=0.5>x</size>", &styles()).is_ok());
+    }
+}
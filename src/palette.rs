@@ -0,0 +1,134 @@
+use crate::style::Styles;
+
+/// The colors set with
+/// [`set_palette`](struct.GlyphBrush.html#method.set_palette), stored after
+/// the colors of the styles in the palette uniform buffer.
+#[derive(Debug)]
+pub(crate) struct Palette {
+    colors: Vec<[f32; 4]>,
+    changed: bool,
+}
+
+impl Palette {
+    /// The amount of colors of the palette.
+    pub(crate) const SLOTS: usize = 256;
+
+    /// The entry of the first color of the palette in the uniform buffer.
+    pub(crate) const OFFSET: usize = Styles::SLOTS;
+
+    /// The amount of entries of the palette uniform buffer.
+    pub(crate) const ENTRIES: usize = Self::OFFSET + Self::SLOTS;
+
+    pub(crate) fn new() -> Self {
+        Palette {
+            colors: Vec::new(),
+            changed: false,
+        }
+    }
+
+    /// Replaces the colors of the palette, keeping the first
+    /// [`SLOTS`](#associatedconstant.SLOTS).
+    pub(crate) fn set(&mut self, colors: &[[f32; 4]]) {
+        let colors = &colors[..colors.len().min(Self::SLOTS)];
+
+        if self.colors != colors {
+            self.colors = colors.to_vec();
+            self.changed = true;
+        }
+    }
+
+    /// Returns all the entries of the palette if they changed since the last
+    /// call, transparent after its colors.
+    pub(crate) fn take_changes(&mut self) -> Option<Vec<f32>> {
+        if !std::mem::take(&mut self.changed) {
+            return None;
+        }
+
+        let mut entries: Vec<f32> =
+            self.colors.iter().flatten().copied().collect();
+
+        entries.resize(Self::SLOTS * 4, 0.0);
+
+        Some(entries)
+    }
+
+    /// Returns the entry of the palette uniform buffer holding the color of
+    /// a text plus one, or zero if it is drawn with its own color, given the
+    /// index of its palette color and the entry of its style, if any.
+    ///
+    /// A palette color takes precedence over the color of a style.
+    pub(crate) fn entry(index: Option<u32>, style: Option<u32>) -> u32 {
+        match index {
+            Some(index) if (index as usize) < Self::SLOTS => {
+                (Self::OFFSET + index as usize) as u32 + 1
+            }
+            _ => style.unwrap_or(0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED: [f32; 4] = [1.0, 0.0, 0.0, 1.0];
+    const BLUE: [f32; 4] = [0.0, 0.0, 1.0, 1.0];
+
+    #[test]
+    fn changes_are_padded_with_transparent_entries() {
+        let mut palette = Palette::new();
+
+        assert_eq!(palette.take_changes(), None);
+
+        palette.set(&[RED, BLUE]);
+
+        let entries = palette.take_changes().unwrap();
+
+        assert_eq!(entries.len(), 4 * Palette::SLOTS);
+        assert_eq!(entries[..8], [RED, BLUE].concat());
+        assert!(entries[8..].iter().all(|entry| *entry == 0.0));
+        assert_eq!(palette.take_changes(), None);
+    }
+
+    #[test]
+    fn setting_the_same_colors_changes_nothing() {
+        let mut palette = Palette::new();
+
+        palette.set(&[RED]);
+        let _ = palette.take_changes();
+
+        palette.set(&[RED]);
+        assert_eq!(palette.take_changes(), None);
+
+        palette.set(&[]);
+        assert!(palette.take_changes().is_some());
+    }
+
+    #[test]
+    fn extra_colors_are_dropped() {
+        let mut palette = Palette::new();
+
+        palette.set(&vec![RED; Palette::SLOTS + 10]);
+
+        let entries = palette.take_changes().unwrap();
+
+        assert_eq!(entries.len(), 4 * Palette::SLOTS);
+        assert!(entries.chunks(4).all(|color| color == RED));
+    }
+
+    #[test]
+    fn entries_prefer_palette_colors_over_styles() {
+        let first = Palette::OFFSET as u32 + 1;
+
+        assert_eq!(Palette::entry(None, None), 0);
+        assert_eq!(Palette::entry(None, Some(3)), 3);
+        assert_eq!(Palette::entry(Some(0), None), first);
+        assert_eq!(Palette::entry(Some(2), Some(3)), first + 2);
+
+        // Out of range palette colors fall back to the style
+        assert_eq!(Palette::entry(Some(Palette::SLOTS as u32), Some(3)), 3);
+        assert_eq!(Palette::entry(Some(u32::MAX), None), 0);
+
+        assert!(first + Palette::SLOTS as u32 - 1 <= Palette::ENTRIES as u32);
+    }
+}
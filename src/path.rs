@@ -0,0 +1,189 @@
+/// A curve for the glyphs of a section to follow, drawn with
+/// [`queue_on_path`](struct.GlyphBrush.html#method.queue_on_path).
+///
+/// Curves are flattened into line segments when they are added.
+///
+/// ```
+/// use wgpu_glyph::TextPath;
+///
+/// let arc = TextPath::new((0.0, 100.0))
+///     .cubic_to((0.0, 0.0), (200.0, 0.0), (200.0, 100.0));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextPath {
+    points: Vec<(f32, f32)>,
+    /// The distance along the path of every point.
+    distances: Vec<f32>,
+}
+
+impl TextPath {
+    /// Creates a `TextPath` starting at the given point.
+    pub fn new(start: (f32, f32)) -> Self {
+        TextPath {
+            points: vec![start],
+            distances: vec![0.0],
+        }
+    }
+
+    /// Creates a `TextPath` joining the given points with straight lines.
+    ///
+    /// Returns `None` if there are no points.
+    pub fn polyline(
+        points: impl IntoIterator<Item = (f32, f32)>,
+    ) -> Option<Self> {
+        let mut points = points.into_iter();
+        let start = points.next()?;
+
+        Some(points.fold(TextPath::new(start), TextPath::line_to))
+    }
+
+    /// Creates a `TextPath` following a cubic Bézier curve.
+    pub fn cubic_bezier(
+        from: (f32, f32),
+        control_1: (f32, f32),
+        control_2: (f32, f32),
+        to: (f32, f32),
+    ) -> Self {
+        TextPath::new(from).cubic_to(control_1, control_2, to)
+    }
+
+    /// Adds a straight line to the given point.
+    pub fn line_to(mut self, point: (f32, f32)) -> Self {
+        let last = self.end();
+        let distance = (point.0 - last.0).hypot(point.1 - last.1);
+
+        if distance > 0.0 {
+            self.points.push(point);
+            self.distances.push(self.length() + distance);
+        }
+
+        self
+    }
+
+    /// Adds a cubic Bézier curve to the given point.
+    pub fn cubic_to(
+        self,
+        control_1: (f32, f32),
+        control_2: (f32, f32),
+        to: (f32, f32),
+    ) -> Self {
+        let from = self.end();
+
+        // Segments of about 4 pixels along the control polygon
+        let polygon = [from, control_1, control_2, to]
+            .windows(2)
+            .map(|points| {
+                (points[1].0 - points[0].0).hypot(points[1].1 - points[0].1)
+            })
+            .sum::<f32>();
+
+        let segments = (polygon / 4.0).ceil().clamp(1.0, 256.0) as usize;
+
+        (1..=segments).fold(self, |path, segment| {
+            let t = segment as f32 / segments as f32;
+            let u = 1.0 - t;
+
+            let [a, b, c, d] =
+                [u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t];
+
+            path.line_to((
+                a * from.0 + b * control_1.0 + c * control_2.0 + d * to.0,
+                a * from.1 + b * control_1.1 + c * control_2.1 + d * to.1,
+            ))
+        })
+    }
+
+    /// Returns the length of the path.
+    pub fn length(&self) -> f32 {
+        self.distances.last().copied().unwrap_or(0.0)
+    }
+
+    fn end(&self) -> (f32, f32) {
+        self.points.last().copied().unwrap_or((0.0, 0.0))
+    }
+
+    /// Returns the point at the given distance along the path and the angle
+    /// of the path there, in radians.
+    ///
+    /// Returns `None` if the distance is outside the path.
+    pub(crate) fn sample(&self, distance: f32) -> Option<((f32, f32), f32)> {
+        if self.points.len() < 2 || !(0.0..=self.length()).contains(&distance) {
+            return None;
+        }
+
+        let segment = self
+            .distances
+            .partition_point(|start| *start <= distance)
+            .clamp(1, self.points.len() - 1);
+
+        let (from, to) = (self.points[segment - 1], self.points[segment]);
+        let start = self.distances[segment - 1];
+        let t = (distance - start) / (self.distances[segment] - start);
+
+        Some((
+            (from.0 + (to.0 - from.0) * t, from.1 + (to.1 - from.1) * t),
+            (to.1 - from.1).atan2(to.0 - from.0),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn polyline_needs_a_point() {
+        assert_eq!(TextPath::polyline([]), None);
+
+        let point = TextPath::polyline([(3.0, 4.0)]).unwrap();
+
+        assert_eq!(point.length(), 0.0);
+        assert_eq!(point.sample(0.0), None);
+    }
+
+    #[test]
+    fn polyline_skips_repeated_points() {
+        let path = TextPath::polyline([
+            (0.0, 0.0),
+            (0.0, 0.0),
+            (3.0, 4.0),
+            (3.0, 4.0),
+        ])
+        .unwrap();
+
+        assert_eq!(path.points, vec![(0.0, 0.0), (3.0, 4.0)]);
+        assert_eq!(path.length(), 5.0);
+    }
+
+    #[test]
+    fn sample_interpolates_along_segments() {
+        let path = TextPath::polyline([(0.0, 0.0), (10.0, 0.0), (10.0, 10.0)])
+            .unwrap();
+
+        assert_eq!(path.sample(5.0), Some(((5.0, 0.0), 0.0)));
+        assert_eq!(
+            path.sample(15.0),
+            Some(((10.0, 5.0), std::f32::consts::FRAC_PI_2))
+        );
+        assert_eq!(path.sample(-1.0), None);
+        assert_eq!(path.sample(20.5), None);
+    }
+
+    #[test]
+    fn cubic_reaches_its_end() {
+        let path = TextPath::cubic_bezier(
+            (0.0, 100.0),
+            (0.0, 0.0),
+            (200.0, 0.0),
+            (200.0, 100.0),
+        );
+
+        assert_eq!(path.end(), (200.0, 100.0));
+        assert!(path.length() > 250.0 && path.length() < 300.0);
+
+        let ((x, _), angle) = path.sample(path.length() / 2.0).unwrap();
+
+        assert!((x - 100.0).abs() < 1.0);
+        assert!(angle.abs() < 0.05);
+    }
+}
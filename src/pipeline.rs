@@ -1,25 +1,40 @@
 mod cache;
 
-use crate::Region;
+use crate::animation::GlyphId;
+use crate::extra::GlyphExtra;
+use crate::fill::Gradients;
+use crate::inline::Images;
+use crate::palette::Palette;
+use crate::{Affine, BillboardMode, DecorationStyle, Fill, Region};
 use cache::Cache;
 
 use bytemuck::{Pod, Zeroable};
 use core::num::NonZeroU64;
 use glyph_brush::ab_glyph::{Rect, point};
+use std::collections::HashMap;
 use std::marker::PhantomData;
 use std::mem;
 
 pub struct Pipeline<Depth> {
-    transform: wgpu::Buffer,
+    globals: wgpu::Buffer,
     sampler: wgpu::Sampler,
     cache: Cache,
+    gradients: wgpu::Buffer,
+    fill_sampler: wgpu::Sampler,
+    fill_texture: wgpu::TextureView,
+    palette: wgpu::Buffer,
+    images: Cache,
+    max_texture_size: u32,
     uniform_layout: wgpu::BindGroupLayout,
     uniforms: wgpu::BindGroup,
     raw: wgpu::RenderPipeline,
     instances: wgpu::Buffer,
     current_instances: usize,
     supported_instances: usize,
-    current_transform: [f32; 16],
+    sections: wgpu::Buffer,
+    supported_sections: usize,
+    camera: [f32; 16],
+    current_globals: [f32; 32],
     depth: PhantomData<Depth>,
 }
 
@@ -126,13 +141,100 @@ impl<Depth> Pipeline<Depth> {
         height: u32,
     ) {
         self.cache = Cache::new(device, width, height);
+        self.rebuild_uniforms(device);
+    }
+
+    pub fn update_gradients(
+        &mut self,
+        staging_belt: &mut wgpu::util::StagingBelt,
+        encoder: &mut wgpu::CommandEncoder,
+        data: &[f32],
+    ) {
+        let bytes = bytemuck::cast_slice(data);
+
+        if let Some(size) = NonZeroU64::new(bytes.len() as u64) {
+            let mut gradients_view =
+                staging_belt.write_buffer(encoder, &self.gradients, 0, size);
+
+            gradients_view.copy_from_slice(bytes);
+        }
+    }
+
+    pub fn update_palette(
+        &mut self,
+        staging_belt: &mut wgpu::util::StagingBelt,
+        encoder: &mut wgpu::CommandEncoder,
+        entry: usize,
+        data: &[f32],
+    ) {
+        let bytes = bytemuck::cast_slice(data);
+        let offset = (entry * mem::size_of::<[f32; 4]>()) as u64;
+
+        if let Some(size) = NonZeroU64::new(bytes.len() as u64) {
+            let mut palette_view =
+                staging_belt.write_buffer(encoder, &self.palette, offset, size);
+
+            palette_view.copy_from_slice(bytes);
+        }
+    }
 
+    pub fn update_images(
+        &mut self,
+        device: &wgpu::Device,
+        staging_belt: &mut wgpu::util::StagingBelt,
+        encoder: &mut wgpu::CommandEncoder,
+        offset: [u16; 2],
+        size: [u16; 2],
+        data: &[u8],
+    ) {
+        self.images
+            .update(device, staging_belt, encoder, offset, size, data);
+    }
+
+    /// Grows the image atlas to the given size, keeping its images.
+    pub fn increase_images_size(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        size: u32,
+    ) {
+        self.images = self.images.grow(device, encoder, size, size);
+        self.rebuild_uniforms(device);
+    }
+
+    /// The largest width and height of the textures of the device.
+    pub fn max_texture_size(&self) -> u32 {
+        self.max_texture_size
+    }
+
+    pub fn set_camera(&mut self, camera: [f32; 16]) {
+        self.camera = camera;
+    }
+
+    pub fn set_fill_texture(
+        &mut self,
+        device: &wgpu::Device,
+        view: &wgpu::TextureView,
+    ) {
+        self.fill_texture = view.clone();
+        self.rebuild_uniforms(device);
+    }
+
+    fn rebuild_uniforms(&mut self, device: &wgpu::Device) {
         self.uniforms = create_uniforms(
             device,
             &self.uniform_layout,
-            &self.transform,
-            &self.sampler,
-            &self.cache.view,
+            Bindings {
+                globals: &self.globals,
+                sampler: &self.sampler,
+                cache: &self.cache.view,
+                gradients: &self.gradients,
+                fill_sampler: &self.fill_sampler,
+                fill_texture: &self.fill_texture,
+                palette: &self.palette,
+                images: &self.images.view,
+                sections: &self.sections,
+            },
         );
     }
 
@@ -141,13 +243,15 @@ impl<Depth> Pipeline<Depth> {
         device: &wgpu::Device,
         staging_belt: &mut wgpu::util::StagingBelt,
         encoder: &mut wgpu::CommandEncoder,
-        instances: &[Instance],
+        quads: &[Quad],
     ) {
-        if instances.is_empty() {
+        if quads.is_empty() {
             self.current_instances = 0;
             return;
         }
 
+        let (instances, sections) = Quad::instances(quads);
+
         if instances.len() > self.supported_instances {
             self.instances = device.create_buffer(&wgpu::BufferDescriptor {
                 label: Some("wgpu_glyph::Pipeline instances"),
@@ -161,17 +265,22 @@ impl<Depth> Pipeline<Depth> {
             self.supported_instances = instances.len();
         }
 
-        let instances_bytes = bytemuck::cast_slice(instances);
+        if sections.len() > self.supported_sections {
+            self.sections = create_sections(device, sections.len());
+            self.supported_sections = sections.len();
+            self.rebuild_uniforms(device);
+        }
 
-        if let Some(size) = NonZeroU64::new(instances_bytes.len() as u64) {
-            let mut instances_view = staging_belt.write_buffer(
-                encoder,
-                &self.instances,
-                0,
-                size,
-            );
+        for (buffer, bytes) in [
+            (&self.instances, bytemuck::cast_slice(&instances)),
+            (&self.sections, bytemuck::cast_slice(&sections)),
+        ] {
+            if let Some(size) = NonZeroU64::new(bytes.len() as u64) {
+                let mut view =
+                    staging_belt.write_buffer(encoder, buffer, 0, size);
 
-            instances_view.copy_from_slice(instances_bytes);
+                view.copy_from_slice(bytes);
+            }
         }
 
         self.current_instances = instances.len();
@@ -187,6 +296,15 @@ const IDENTITY_MATRIX: [f32; 16] = [
     0.0, 0.0, 0.0, 1.0,
 ];
 
+/// The size of the globals uniform buffer, holding the transform of the
+/// draw and the view-projection matrix of the camera of billboards, in
+/// bytes.
+const GLOBALS_SIZE: u64 = (2 * 16 * mem::size_of::<f32>()) as u64;
+
+/// The size of the palette uniform buffer, holding the colors of the styles
+/// and of the palette of the brush, in bytes.
+const PALETTE_SIZE: u64 = (Palette::ENTRIES * 4 * mem::size_of::<f32>()) as u64;
+
 fn build<D>(
     device: &wgpu::Device,
     filter_mode: wgpu::FilterMode,
@@ -198,10 +316,10 @@ fn build<D>(
 ) -> Pipeline<D> {
     use wgpu::util::DeviceExt;
 
-    let transform =
+    let globals =
         device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: None,
-            contents: bytemuck::cast_slice(&IDENTITY_MATRIX),
+            contents: bytemuck::cast_slice(&[IDENTITY_MATRIX; 2]),
             usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
         });
 
@@ -217,6 +335,59 @@ fn build<D>(
 
     let cache = Cache::new(device, cache_width, cache_height);
 
+    let gradients = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("wgpu_glyph::Pipeline gradients"),
+        size: Gradients::size(),
+        usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+
+    let fill_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+        address_mode_u: wgpu::AddressMode::Repeat,
+        address_mode_v: wgpu::AddressMode::Repeat,
+        address_mode_w: wgpu::AddressMode::Repeat,
+        mag_filter: wgpu::FilterMode::Linear,
+        min_filter: wgpu::FilterMode::Linear,
+        mipmap_filter: wgpu::MipmapFilterMode::Nearest,
+        ..Default::default()
+    });
+
+    // A transparent pixel, until a fill texture is set
+    let fill_texture = device
+        .create_texture(&wgpu::TextureDescriptor {
+            label: Some("wgpu_glyph::Pipeline fill texture"),
+            size: wgpu::Extent3d {
+                width: 1,
+                height: 1,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        })
+        .create_view(&wgpu::TextureViewDescriptor::default());
+
+    let palette = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("wgpu_glyph::Pipeline palette"),
+        size: PALETTE_SIZE,
+        usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+
+    let max_texture_size = device.limits().max_texture_dimension_2d;
+    let images_size = Images::INITIAL_SIZE.min(max_texture_size);
+
+    let images = Cache::with_format(
+        device,
+        images_size,
+        images_size,
+        wgpu::TextureFormat::Rgba8UnormSrgb,
+        4,
+    );
+
     let uniform_layout =
         device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
             label: Some("wgpu_glyph::Pipeline uniforms"),
@@ -227,9 +398,7 @@ fn build<D>(
                     ty: wgpu::BindingType::Buffer {
                         ty: wgpu::BufferBindingType::Uniform,
                         has_dynamic_offset: false,
-                        min_binding_size: wgpu::BufferSize::new(
-                            mem::size_of::<[f32; 16]>() as u64,
-                        ),
+                        min_binding_size: wgpu::BufferSize::new(GLOBALS_SIZE),
                     },
                     count: None,
                 },
@@ -253,15 +422,93 @@ fn build<D>(
                     },
                     count: None,
                 },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 3,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: wgpu::BufferSize::new(
+                            Gradients::size(),
+                        ),
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 4,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(
+                        wgpu::SamplerBindingType::Filtering,
+                    ),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 5,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float {
+                            filterable: true,
+                        },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 6,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: wgpu::BufferSize::new(PALETTE_SIZE),
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 7,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float {
+                            filterable: true,
+                        },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 8,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage {
+                            read_only: true,
+                        },
+                        has_dynamic_offset: false,
+                        min_binding_size: wgpu::BufferSize::new(
+                            mem::size_of::<Section>() as u64,
+                        ),
+                    },
+                    count: None,
+                },
             ],
         });
 
+    let sections = create_sections(device, Section::INITIAL_AMOUNT);
+
     let uniforms = create_uniforms(
         device,
         &uniform_layout,
-        &transform,
-        &sampler,
-        &cache.view,
+        Bindings {
+            globals: &globals,
+            sampler: &sampler,
+            cache: &cache.view,
+            gradients: &gradients,
+            fill_sampler: &fill_sampler,
+            fill_texture: &fill_texture,
+            palette: &palette,
+            images: &images.view,
+            sections: &sections,
+        },
     );
 
     let instances = device.create_buffer(&wgpu::BufferDescriptor {
@@ -302,6 +549,12 @@ fn build<D>(
                     2 => Float32x2,
                     3 => Float32x2,
                     4 => Float32x4,
+                    5 => Uint32,
+                    6 => Float32x4,
+                    7 => Float32x4,
+                    8 => Float32x2,
+                    9 => Uint32,
+                    10 => Uint32,
                 ],
             }],
             compilation_options: wgpu::PipelineCompilationOptions::default(),
@@ -339,20 +592,38 @@ fn build<D>(
     });
 
     Pipeline {
-        transform,
+        globals,
         sampler,
         cache,
+        gradients,
+        fill_sampler,
+        fill_texture,
+        palette,
+        images,
+        max_texture_size,
         uniform_layout,
         uniforms,
         raw,
         instances,
         current_instances: 0,
         supported_instances: Instance::INITIAL_AMOUNT,
-        current_transform: [0.0; 16],
+        sections,
+        supported_sections: Section::INITIAL_AMOUNT,
+        camera: IDENTITY_MATRIX,
+        current_globals: [0.0; 32],
         depth: PhantomData,
     }
 }
 
+fn create_sections(device: &wgpu::Device, amount: usize) -> wgpu::Buffer {
+    device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("wgpu_glyph::Pipeline sections"),
+        size: (mem::size_of::<Section>() * amount) as u64,
+        usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    })
+}
+
 fn draw<D>(
     pipeline: &mut Pipeline<D>,
     staging_belt: &mut wgpu::util::StagingBelt,
@@ -362,17 +633,21 @@ fn draw<D>(
     transform: [f32; 16],
     region: Option<Region>,
 ) {
-    if transform != pipeline.current_transform {
-        let mut transform_view = staging_belt.write_buffer(
+    let mut globals = [0.0; 32];
+    globals[..16].copy_from_slice(&transform);
+    globals[16..].copy_from_slice(&pipeline.camera);
+
+    if globals != pipeline.current_globals {
+        let mut globals_view = staging_belt.write_buffer(
             encoder,
-            &pipeline.transform,
+            &pipeline.globals,
             0,
-            unsafe { NonZeroU64::new_unchecked(16 * 4) },
+            unsafe { NonZeroU64::new_unchecked(GLOBALS_SIZE) },
         );
 
-        transform_view.copy_from_slice(bytemuck::cast_slice(&transform));
+        globals_view.copy_from_slice(bytemuck::cast_slice(&globals));
 
-        pipeline.current_transform = transform;
+        pipeline.current_globals = globals;
     }
 
     let mut render_pass =
@@ -409,13 +684,36 @@ fn draw<D>(
     render_pass.draw(0..4, 0..pipeline.current_instances as u32);
 }
 
+/// The resources bound to the uniforms of the pipeline, in binding order.
+struct Bindings<'a> {
+    globals: &'a wgpu::Buffer,
+    sampler: &'a wgpu::Sampler,
+    cache: &'a wgpu::TextureView,
+    gradients: &'a wgpu::Buffer,
+    fill_sampler: &'a wgpu::Sampler,
+    fill_texture: &'a wgpu::TextureView,
+    palette: &'a wgpu::Buffer,
+    images: &'a wgpu::TextureView,
+    sections: &'a wgpu::Buffer,
+}
+
 fn create_uniforms(
     device: &wgpu::Device,
     layout: &wgpu::BindGroupLayout,
-    transform: &wgpu::Buffer,
-    sampler: &wgpu::Sampler,
-    cache: &wgpu::TextureView,
+    bindings: Bindings<'_>,
 ) -> wgpu::BindGroup {
+    let Bindings {
+        globals,
+        sampler,
+        cache,
+        gradients,
+        fill_sampler,
+        fill_texture,
+        palette,
+        images,
+        sections,
+    } = bindings;
+
     device.create_bind_group(&wgpu::BindGroupDescriptor {
         label: Some("wgpu_glyph::Pipeline uniforms"),
         layout,
@@ -423,7 +721,7 @@ fn create_uniforms(
             wgpu::BindGroupEntry {
                 binding: 0,
                 resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
-                    buffer: transform,
+                    buffer: globals,
                     offset: 0,
                     size: None,
                 }),
@@ -436,38 +734,495 @@ fn create_uniforms(
                 binding: 2,
                 resource: wgpu::BindingResource::TextureView(cache),
             },
+            wgpu::BindGroupEntry {
+                binding: 3,
+                resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                    buffer: gradients,
+                    offset: 0,
+                    size: None,
+                }),
+            },
+            wgpu::BindGroupEntry {
+                binding: 4,
+                resource: wgpu::BindingResource::Sampler(fill_sampler),
+            },
+            wgpu::BindGroupEntry {
+                binding: 5,
+                resource: wgpu::BindingResource::TextureView(fill_texture),
+            },
+            wgpu::BindGroupEntry {
+                binding: 6,
+                resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                    buffer: palette,
+                    offset: 0,
+                    size: None,
+                }),
+            },
+            wgpu::BindGroupEntry {
+                binding: 7,
+                resource: wgpu::BindingResource::TextureView(images),
+            },
+            wgpu::BindGroupEntry {
+                binding: 8,
+                resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                    buffer: sections,
+                    offset: 0,
+                    size: None,
+                }),
+            },
         ],
     })
 }
 
+/// A quad drawn by the pipeline, like a glyph, a decoration or an image,
+/// with the data of its section.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quad {
+    instance: Instance,
+    section: Section,
+}
+
+/// The instance of a quad in the vertex buffer.
 #[repr(C)]
-#[derive(Debug, Clone, Copy, Zeroable, Pod)]
+#[derive(Debug, Clone, Copy, PartialEq, Zeroable, Pod)]
 pub struct Instance {
     left_top: [f32; 3],
     right_bottom: [f32; 2],
     tex_left_top: [f32; 2],
     tex_right_bottom: [f32; 2],
     color: [f32; 4],
+    kind: u32,
+    outline_color: [f32; 4],
+    tex_bounds: [f32; 4],
+    /// The radius of the outline or the blur of a glyph, in texture
+    /// coordinates.
+    radius: [f32; 2],
+    /// The entry of the palette uniform buffer with the color of the quad
+    /// plus one, or zero if it is drawn with `color`.
+    palette: u32,
+    /// The index of the section of the quad in the sections storage buffer.
+    section: u32,
+}
+
+/// The data shared by the quads of a section, or of a text of a section,
+/// stored once per frame in the sections storage buffer.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Zeroable, Pod)]
+pub struct Section {
+    /// The linear part of the transform of the quads, applied around their
+    /// origin after clipping.
+    transform: [f32; 4],
+    /// The translation of the transform of the quads, followed by their
+    /// origin, which their fill and transform are relative to.
+    translation: [f32; 4],
+    /// The anchor of a billboard in world space, followed by its mode: zero
+    /// if the quads are not a billboard, `BILLBOARD_WORLD` or
+    /// `BILLBOARD_SCREEN`.
+    billboard: [f32; 4],
+    /// The points of the gradient or the origin and size of the texture of
+    /// the fill of glyphs.
+    fill_geometry: [f32; 4],
+    /// The size of a pixel of a world billboard in world units, twice, or
+    /// the size of the target of a screen billboard.
+    billboard_size: [f32; 2],
+    /// The kind of fill of glyphs and the slot of its gradient.
+    fill: [u32; 2],
+}
+
+/// The instances of a glyph generated by `glyph_brush`: the glyph itself
+/// and its shadow, if any.
+#[derive(Debug, Clone, Copy)]
+pub struct Glyph {
+    pub instance: Quad,
+    pub shadow: Option<Quad>,
+    /// The coordinates of the glyph in the cache texture, kept to build its
+    /// instances again when it is animated.
+    pub tex_coords: Rect,
+    /// The coordinates of the glyph on screen.
+    pub pixel_coords: Rect,
+    /// The id of the glyph, if it belongs to an animated section.
+    pub animation: Option<GlyphId>,
+    /// The key of the section of the glyph, if quads are drawn around it.
+    pub section: Option<u64>,
+}
+
+impl Glyph {
+    pub fn from_vertex(
+        vertex: glyph_brush::GlyphVertex<'_, GlyphExtra>,
+        gradients: &Gradients,
+    ) -> Glyph {
+        Glyph {
+            shadow: Quad::shadow(&vertex),
+            tex_coords: vertex.tex_coords,
+            pixel_coords: vertex.pixel_coords,
+            animation: vertex.extra.animation,
+            section: vertex.extra.section,
+            instance: Quad::from_vertex(vertex, gradients),
+        }
+    }
 }
 
 impl Instance {
     const INITIAL_AMOUNT: usize = 50_000;
 
+    /// A glyph sampled from the cache texture.
+    const GLYPH: u32 = 0;
+
+    /// A quad filled with a solid color.
+    const SOLID: u32 = 1;
+
+    /// A dashed line, with pattern coordinates in units of its thickness.
+    const DASHED: u32 = 2;
+
+    /// A dotted line, with pattern coordinates in units of its thickness.
+    const DOTTED: u32 = 3;
+
+    /// A wavy line, with pattern coordinates in units of the height of the
+    /// quad.
+    const WAVY: u32 = 4;
+
+    /// The shadow of a glyph, blurring its coverage in the cache texture.
+    const SHADOW: u32 = 5;
+
+    /// An image sampled from the image atlas and multiplied by its color.
+    const IMAGE: u32 = 6;
+}
+
+impl Section {
+    const INITIAL_AMOUNT: usize = 256;
+
+    /// The section of quads drawn as is.
+    const DEFAULT: Section = Section {
+        transform: Affine::IDENTITY.matrix,
+        translation: [0.0; 4],
+        billboard: [0.0; 4],
+        fill_geometry: [0.0; 4],
+        billboard_size: [0.0; 2],
+        fill: [Section::FILL_SOLID, 0],
+    };
+
+    /// A glyph painted with its color.
+    const FILL_SOLID: u32 = 0;
+
+    /// A glyph painted with a gradient along a line.
+    const FILL_LINEAR: u32 = 1;
+
+    /// A glyph painted with a gradient around a center.
+    const FILL_RADIAL: u32 = 2;
+
+    /// A glyph painted with the fill texture.
+    const FILL_TEXTURE: u32 = 3;
+
+    /// A billboard sized in world space.
+    const BILLBOARD_WORLD: f32 = 1.0;
+
+    /// A billboard keeping the same size on screen.
+    const BILLBOARD_SCREEN: f32 = 2.0;
+}
+
+impl Quad {
+    /// Returns the instances of the given quads, and the sections they
+    /// index, each stored once.
+    fn instances(quads: &[Quad]) -> (Vec<Instance>, Vec<Section>) {
+        let mut sections = Vec::new();
+        let mut indices = HashMap::new();
+        let mut last = None;
+
+        let instances = quads
+            .iter()
+            .map(|quad| {
+                // The quads of a section usually follow each other
+                let section = match last {
+                    Some((section, index)) if section == quad.section => index,
+                    _ => {
+                        let key: [u32; 20] = bytemuck::cast(quad.section);
+                        let index = *indices.entry(key).or_insert_with(|| {
+                            sections.push(quad.section);
+
+                            sections.len() as u32 - 1
+                        });
+
+                        last = Some((quad.section, index));
+
+                        index
+                    }
+                };
+
+                Instance {
+                    section,
+                    ..quad.instance
+                }
+            })
+            .collect();
+
+        (instances, sections)
+    }
+
+    /// Creates a quad filled with a solid color, clipped to the given bounds.
+    ///
+    /// Returns `None` if the quad is completely out of bounds.
+    pub fn solid(
+        rect: Rect,
+        bounds: Rect,
+        z: f32,
+        color: [f32; 4],
+    ) -> Option<Quad> {
+        Quad::new(rect, Rect::default(), bounds, z, color, Instance::SOLID)
+    }
+
+    /// Creates a quad drawing the image at the given coordinates of the
+    /// image atlas, in pixels, clipped to the given bounds.
+    ///
+    /// Returns `None` if the quad is completely out of bounds.
+    pub fn image(
+        rect: Rect,
+        tex_coords: Rect,
+        bounds: Rect,
+        z: f32,
+        color: [f32; 4],
+    ) -> Option<Quad> {
+        Quad::new(rect, tex_coords, bounds, z, color, Instance::IMAGE)
+    }
+
+    /// Creates a decoration line covering the given rectangle, clipped to
+    /// the given bounds.
+    ///
+    /// A wavy line is three times as tall as the rectangle, so the wave
+    /// can oscillate around it.
+    ///
+    /// Returns `None` if the line is completely out of bounds.
+    pub fn decoration(
+        mut rect: Rect,
+        bounds: Rect,
+        z: f32,
+        color: [f32; 4],
+        style: DecorationStyle,
+    ) -> Option<Quad> {
+        let kind = match style {
+            DecorationStyle::Solid => Instance::SOLID,
+            DecorationStyle::Dashed => Instance::DASHED,
+            DecorationStyle::Dotted => Instance::DOTTED,
+            DecorationStyle::Wavy => {
+                let thickness = rect.height();
+
+                rect.min.y -= thickness;
+                rect.max.y += thickness;
+
+                Instance::WAVY
+            }
+        };
+
+        let pattern = Rect {
+            min: point(0.0, 0.0),
+            max: point(rect.width() / rect.height().max(f32::EPSILON), 1.0),
+        };
+
+        Quad::new(rect, pattern, bounds, z, color, kind)
+    }
+
+    /// Applies the transform and the billboard of the given `GlyphExtra` to
+    /// the instance, and moves its fill to the origin of the extra.
+    pub fn transformed(mut self, extra: &GlyphExtra) -> Quad {
+        let transform = extra.transform.unwrap_or(Affine::IDENTITY);
+        let (x, y) = transform.translation;
+        let (origin_x, origin_y) = extra.origin;
+
+        self.section.transform = transform.matrix;
+        self.section.translation = [x, y, origin_x, origin_y];
+
+        if let Some(billboard) = extra.billboard {
+            let [x, y, z] = billboard.anchor;
+
+            let (mode, size) = match billboard.mode {
+                BillboardMode::World { scale } => {
+                    (Section::BILLBOARD_WORLD, [scale; 2])
+                }
+                BillboardMode::Screen { width, height } => {
+                    (Section::BILLBOARD_SCREEN, [width, height])
+                }
+            };
+
+            self.section.billboard = [x, y, z, mode];
+            self.section.billboard_size = size;
+        }
+
+        self
+    }
+
+    /// Draws the instance with the palette color or the color of the style
+    /// of the given `GlyphExtra`, if any.
+    pub fn paletted(mut self, extra: &GlyphExtra) -> Quad {
+        self.instance.palette = extra.palette;
+        self
+    }
+
+    /// Replaces the color of the instance, ignoring its palette color and
+    /// style.
+    pub fn with_color(mut self, color: [f32; 4]) -> Quad {
+        self.instance.color = color;
+        self.instance.palette = 0;
+        self
+    }
+
+    /// Creates a quad clipped to the given bounds, cropping its texture
+    /// coordinates accordingly.
+    fn new(
+        rect: Rect,
+        tex_coords: Rect,
+        bounds: Rect,
+        z: f32,
+        color: [f32; 4],
+        kind: u32,
+    ) -> Option<Quad> {
+        let clipped = Rect {
+            min: point(
+                rect.min.x.max(bounds.min.x),
+                rect.min.y.max(bounds.min.y),
+            ),
+            max: point(
+                rect.max.x.min(bounds.max.x),
+                rect.max.y.min(bounds.max.y),
+            ),
+        };
+
+        if clipped.min.x >= clipped.max.x || clipped.min.y >= clipped.max.y {
+            return None;
+        }
+
+        let tex = |x: f32, y: f32| {
+            [
+                tex_coords.min.x
+                    + tex_coords.width() * (x - rect.min.x) / rect.width(),
+                tex_coords.min.y
+                    + tex_coords.height() * (y - rect.min.y) / rect.height(),
+            ]
+        };
+
+        Some(Quad {
+            instance: Instance {
+                left_top: [clipped.min.x, clipped.max.y, z],
+                right_bottom: [clipped.max.x, clipped.min.y],
+                tex_left_top: tex(clipped.min.x, clipped.max.y),
+                tex_right_bottom: tex(clipped.max.x, clipped.min.y),
+                color,
+                kind,
+                outline_color: [0.0; 4],
+                tex_bounds: [0.0; 4],
+                radius: [0.0; 2],
+                palette: 0,
+                section: 0,
+            },
+            section: Section::DEFAULT,
+        })
+    }
+
+    /// Creates the shadow of a glyph, if its text has one, clipped to the
+    /// bounds of its section.
+    pub fn shadow(
+        glyph_brush::GlyphVertex {
+            tex_coords,
+            pixel_coords,
+            bounds,
+            extra,
+        }: &glyph_brush::GlyphVertex<'_, GlyphExtra>,
+    ) -> Option<Quad> {
+        let shadow = extra.shadow?;
+        let (x, y) = shadow.offset;
+        let blur = shadow.blur_radius.max(0.0);
+
+        let radius = [
+            blur * tex_coords.width() / pixel_coords.width(),
+            blur * tex_coords.height() / pixel_coords.height(),
+        ];
+
+        let rect = Rect {
+            min: point(
+                pixel_coords.min.x + x - blur,
+                pixel_coords.min.y + y - blur,
+            ),
+            max: point(
+                pixel_coords.max.x + x + blur,
+                pixel_coords.max.y + y + blur,
+            ),
+        };
+
+        let tex = Rect {
+            min: point(
+                tex_coords.min.x - radius[0],
+                tex_coords.min.y - radius[1],
+            ),
+            max: point(
+                tex_coords.max.x + radius[0],
+                tex_coords.max.y + radius[1],
+            ),
+        };
+
+        let mut quad = Quad::new(
+            rect,
+            tex,
+            *bounds,
+            extra.z,
+            shadow.color,
+            Instance::SHADOW,
+        )?;
+
+        quad.instance.tex_bounds = [
+            tex_coords.min.x,
+            tex_coords.min.y,
+            tex_coords.max.x,
+            tex_coords.max.y,
+        ];
+        quad.instance.radius = radius;
+
+        Some(quad.transformed(extra))
+    }
+
     pub fn from_vertex(
         glyph_brush::GlyphVertex {
             mut tex_coords,
             pixel_coords,
             bounds,
             extra,
-        }: glyph_brush::GlyphVertex,
-    ) -> Instance {
+        }: glyph_brush::GlyphVertex<'_, GlyphExtra>,
+        gradients: &Gradients,
+    ) -> Quad {
         let gl_bounds = bounds;
+        let (fill, fill_geometry) = Quad::fill(&extra.fill, gradients);
 
         let mut gl_rect = Rect {
             min: point(pixel_coords.min.x, pixel_coords.min.y),
             max: point(pixel_coords.max.x, pixel_coords.max.y),
         };
 
+        let tex_bounds = [
+            tex_coords.min.x,
+            tex_coords.min.y,
+            tex_coords.max.x,
+            tex_coords.max.y,
+        ];
+
+        // grow the quad to fit the outline, sampling around the glyph
+        let outline = extra.outline.filter(|outline| outline.width > 0.0);
+        let mut radius = [0.0; 2];
+
+        if let Some(outline) = outline {
+            let texel_width = tex_coords.width() / gl_rect.width();
+            let texel_height = tex_coords.height() / gl_rect.height();
+
+            radius =
+                [outline.width * texel_width, outline.width * texel_height];
+
+            gl_rect.min.x -= outline.width;
+            gl_rect.min.y -= outline.width;
+            gl_rect.max.x += outline.width;
+            gl_rect.max.y += outline.width;
+
+            tex_coords.min.x -= radius[0];
+            tex_coords.min.y -= radius[1];
+            tex_coords.max.x += radius[0];
+            tex_coords.max.y += radius[1];
+        }
+
         // handle overlapping bounds, modify uv_rect to preserve texture aspect
         if gl_rect.max.x > gl_bounds.max.x {
             let old_width = gl_rect.width();
@@ -497,12 +1252,91 @@ impl Instance {
                 - tex_coords.height() * gl_rect.height() / old_height;
         }
 
-        Instance {
-            left_top: [gl_rect.min.x, gl_rect.max.y, extra.z],
-            right_bottom: [gl_rect.max.x, gl_rect.min.y],
-            tex_left_top: [tex_coords.min.x, tex_coords.max.y],
-            tex_right_bottom: [tex_coords.max.x, tex_coords.min.y],
-            color: extra.color,
+        Quad {
+            instance: Instance {
+                left_top: [gl_rect.min.x, gl_rect.max.y, extra.z],
+                right_bottom: [gl_rect.max.x, gl_rect.min.y],
+                tex_left_top: [tex_coords.min.x, tex_coords.max.y],
+                tex_right_bottom: [tex_coords.max.x, tex_coords.min.y],
+                color: extra.color,
+                kind: Instance::GLYPH,
+                outline_color: outline
+                    .map_or([0.0; 4], |outline| outline.color),
+                tex_bounds,
+                radius,
+                palette: 0,
+                section: 0,
+            },
+            section: Section {
+                fill,
+                fill_geometry,
+                ..Section::DEFAULT
+            },
+        }
+        .transformed(extra)
+        .paletted(extra)
+    }
+
+    /// Returns the fill of a glyph and its geometry.
+    ///
+    /// Gradients without a slot are drawn with the color of the glyph.
+    fn fill(fill: &Fill, gradients: &Gradients) -> ([u32; 2], [f32; 4]) {
+        match *fill {
+            Fill::LinearGradient {
+                start,
+                end,
+                gradient,
+            } => match gradients.slot(gradient) {
+                Some(slot) => (
+                    [Section::FILL_LINEAR, slot],
+                    [start.0, start.1, end.0, end.1],
+                ),
+                None => ([Section::FILL_SOLID, 0], [0.0; 4]),
+            },
+            Fill::RadialGradient {
+                center,
+                radius,
+                gradient,
+            } => match gradients.slot(gradient) {
+                Some(slot) => (
+                    [Section::FILL_RADIAL, slot],
+                    [center.0, center.1, radius, 0.0],
+                ),
+                None => ([Section::FILL_SOLID, 0], [0.0; 4]),
+            },
+            Fill::Texture { origin, size } => (
+                [Section::FILL_TEXTURE, 0],
+                [origin.0, origin.1, size.0, size.1],
+            ),
+            Fill::Solid => ([Section::FILL_SOLID, 0], [0.0; 4]),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quads_share_the_data_of_their_section() {
+        let rect = Rect {
+            min: point(0.0, 0.0),
+            max: point(1.0, 1.0),
+        };
+        let quad = Quad::solid(rect, rect, 0.0, [1.0; 4]).unwrap();
+        let moved = quad.transformed(&GlyphExtra {
+            transform: Some(Affine::translation(2.0, 3.0)),
+            ..GlyphExtra::default()
+        });
+
+        let (instances, sections) =
+            Quad::instances(&[quad, quad, moved, quad, moved]);
+
+        assert_eq!(sections, [quad.section, moved.section]);
+        assert_eq!(
+            instances.iter().map(|i| i.section).collect::<Vec<_>>(),
+            [0, 0, 1, 0, 1]
+        );
+        assert_eq!(mem::size_of::<Instance>(), 104);
+    }
+}
@@ -3,6 +3,7 @@ use core::num::NonZeroU64;
 pub struct Cache {
     texture: wgpu::Texture,
     pub(super) view: wgpu::TextureView,
+    bytes_per_pixel: usize,
     upload_buffer: wgpu::Buffer,
     upload_buffer_size: u64,
 }
@@ -12,6 +13,22 @@ impl Cache {
         wgpu::COPY_BYTES_PER_ROW_ALIGNMENT as u64 * 100;
 
     pub fn new(device: &wgpu::Device, width: u32, height: u32) -> Cache {
+        Cache::with_format(
+            device,
+            width,
+            height,
+            wgpu::TextureFormat::R8Unorm,
+            1,
+        )
+    }
+
+    pub fn with_format(
+        device: &wgpu::Device,
+        width: u32,
+        height: u32,
+        format: wgpu::TextureFormat,
+        bytes_per_pixel: usize,
+    ) -> Cache {
         let texture = device.create_texture(&wgpu::TextureDescriptor {
             label: Some("wgpu_glyph::Cache"),
             size: wgpu::Extent3d {
@@ -20,8 +37,9 @@ impl Cache {
                 depth_or_array_layers: 1,
             },
             dimension: wgpu::TextureDimension::D2,
-            format: wgpu::TextureFormat::R8Unorm,
+            format,
             usage: wgpu::TextureUsages::COPY_DST
+                | wgpu::TextureUsages::COPY_SRC
                 | wgpu::TextureUsages::TEXTURE_BINDING,
             mip_level_count: 1,
             sample_count: 1,
@@ -40,11 +58,37 @@ impl Cache {
         Cache {
             texture,
             view,
+            bytes_per_pixel,
             upload_buffer,
             upload_buffer_size: Self::INITIAL_UPLOAD_BUFFER_SIZE,
         }
     }
 
+    /// Creates a larger cache holding the contents of this one.
+    pub fn grow(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        width: u32,
+        height: u32,
+    ) -> Cache {
+        let cache = Cache::with_format(
+            device,
+            width,
+            height,
+            self.texture.format(),
+            self.bytes_per_pixel,
+        );
+
+        encoder.copy_texture_to_texture(
+            self.texture.as_image_copy(),
+            cache.texture.as_image_copy(),
+            self.texture.size(),
+        );
+
+        cache
+    }
+
     pub fn update(
         &mut self,
         device: &wgpu::Device,
@@ -54,7 +98,7 @@ impl Cache {
         size: [u16; 2],
         data: &[u8],
     ) {
-        let width = size[0] as usize;
+        let width = size[0] as usize * self.bytes_per_pixel;
         let height = size[1] as usize;
 
         // It is a webgpu requirement that:
@@ -0,0 +1,171 @@
+//! Quads, like selections, carets and decorations, drawn around the glyphs of
+//! their section.
+use crate::pipeline::{Glyph, Quad};
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// Quads drawn behind and in front of glyphs.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct Quads {
+    pub behind: Vec<Quad>,
+    pub front: Vec<Quad>,
+}
+
+impl Quads {
+    pub fn is_empty(&self) -> bool {
+        self.behind.is_empty() && self.front.is_empty()
+    }
+}
+
+/// The quads of a queued section, drawn around its glyphs in the order the
+/// sections are queued.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct SectionQuads {
+    pub glyphs: QuadGlyphs,
+    pub quads: Quads,
+}
+
+/// The glyphs the quads of a section are drawn around.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum QuadGlyphs {
+    /// The glyphs tagged with the given key.
+    Tagged(u64),
+    /// The glyphs of the animated section with the given index.
+    Animated(usize),
+}
+
+/// The range of the glyphs of every tagged section of a frame, by key.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Runs(HashMap<u64, Range<usize>>);
+
+impl Runs {
+    /// Finds the glyphs of every tagged section, which are contiguous as
+    /// the glyphs of a section are drawn together.
+    pub fn new(glyphs: &[Glyph]) -> Runs {
+        Runs::of(glyphs.iter().map(|glyph| glyph.section))
+    }
+
+    fn of(sections: impl Iterator<Item = Option<u64>>) -> Runs {
+        let mut runs = HashMap::new();
+
+        for (index, section) in sections.enumerate() {
+            if let Some(key) = section {
+                let _ = runs
+                    .entry(key)
+                    .and_modify(|run: &mut Range<usize>| run.end = index + 1)
+                    .or_insert(index..index + 1);
+            }
+        }
+
+        Runs(runs)
+    }
+
+    /// Appends the instances of the glyphs of a frame to `out`, with the
+    /// quads of every section around the glyphs of the section.
+    ///
+    /// Sections are drawn in the order they are queued. Sections without
+    /// quads are drawn between the sections with quads queued around them,
+    /// and sections with quads but without glyphs right after the section
+    /// with quads before them.
+    pub fn order(
+        &self,
+        instances: &[Quad],
+        sections: &[SectionQuads],
+        animated: &[Vec<Quad>],
+        out: &mut Vec<Quad>,
+    ) {
+        let mut drawn = 0;
+
+        for SectionQuads { glyphs, quads } in sections {
+            let run = match glyphs {
+                QuadGlyphs::Tagged(key) => {
+                    self.0.get(key).filter(|run| run.start >= drawn)
+                }
+                QuadGlyphs::Animated(_) => None,
+            };
+
+            let glyphs = match (glyphs, run) {
+                (_, Some(run)) => {
+                    out.extend(&instances[drawn..run.start]);
+                    drawn = run.end;
+
+                    &instances[run.clone()]
+                }
+                (QuadGlyphs::Animated(index), None) => &animated[*index],
+                (QuadGlyphs::Tagged(_), None) => &[],
+            };
+
+            out.extend(quads.behind.iter().chain(glyphs).chain(&quads.front));
+        }
+
+        out.extend(&instances[drawn..]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use glyph_brush::ab_glyph::{Rect, point};
+
+    /// A distinct instance for every id.
+    fn instance(id: u8) -> Quad {
+        let rect = Rect {
+            min: point(0.0, 0.0),
+            max: point(1.0, 1.0),
+        };
+
+        Quad::solid(rect, rect, 0.0, [f32::from(id), 0.0, 0.0, 1.0])
+            .unwrap()
+    }
+
+    fn instances(ids: &[u8]) -> Vec<Quad> {
+        ids.iter().copied().map(instance).collect()
+    }
+
+    fn quads(glyphs: QuadGlyphs, behind: &[u8], front: &[u8]) -> SectionQuads {
+        SectionQuads {
+            glyphs,
+            quads: Quads {
+                behind: instances(behind),
+                front: instances(front),
+            },
+        }
+    }
+
+    #[test]
+    fn quads_are_drawn_around_the_glyphs_of_their_section() {
+        // Untagged glyphs 1, the glyphs 2 and 3 of section 7, untagged
+        // glyph 4 and the glyph 5 of section 8
+        let runs =
+            Runs::of([None, Some(7), Some(7), None, Some(8)].into_iter());
+        let sections = [
+            quads(QuadGlyphs::Tagged(7), &[10], &[11, 12]),
+            quads(QuadGlyphs::Tagged(8), &[13], &[14]),
+        ];
+
+        let mut out = Vec::new();
+        runs.order(&instances(&[1, 2, 3, 4, 5]), &sections, &[], &mut out);
+
+        assert_eq!(out, instances(&[1, 10, 2, 3, 11, 12, 4, 13, 5, 14]));
+    }
+
+    #[test]
+    fn sections_without_tagged_glyphs_are_drawn_in_queue_order() {
+        let runs = Runs::of([Some(7), None, Some(8)].into_iter());
+        let sections = [
+            quads(QuadGlyphs::Tagged(7), &[10], &[]),
+            quads(QuadGlyphs::Animated(1), &[11], &[12]),
+            quads(QuadGlyphs::Tagged(9), &[13], &[]),
+            quads(QuadGlyphs::Animated(0), &[], &[]),
+            quads(QuadGlyphs::Tagged(8), &[], &[14]),
+        ];
+        let animated = [instances(&[20]), instances(&[21, 22])];
+
+        let mut out = Vec::new();
+        runs.order(&instances(&[1, 2, 3]), &sections, &animated, &mut out);
+
+        assert_eq!(out, instances(&[10, 1, 11, 21, 22, 12, 13, 20, 2, 3, 14]));
+    }
+}
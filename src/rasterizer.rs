@@ -0,0 +1,520 @@
+use glyph_brush::ab_glyph::{
+    CodepointIdIter, Font, Glyph, GlyphId, GlyphSvg, Outline, OutlineCurve,
+    OutlinedGlyph, Point, PxScale, PxScaleFactor, Rect, point, v2,
+};
+
+use std::fmt;
+use std::sync::Arc;
+
+/// Produces the coverage of glyphs cached by a brush, in place of the
+/// `ab_glyph` outline rasterizer.
+///
+/// A rasterizer is plugged into a brush with
+/// [`GlyphBrushBuilder::rasterizer`](struct.GlyphBrushBuilder.html#method.rasterizer),
+/// e.g. to hint glyphs at small sizes, to tweak their gamma or to draw the
+/// bitmap strikes of a font. Glyphs are rasterized once for every
+/// [`RasterKey`](struct.RasterKey.html) and kept in the cache texture.
+///
+/// ```
+/// use wgpu_glyph::ab_glyph::Font;
+/// use wgpu_glyph::{Coverage, GlyphRasterizer, OutlineRasterizer, RasterKey};
+///
+/// /// Thickens thin stems by brightening partially covered pixels.
+/// struct Gamma(f32);
+///
+/// impl<F: Font> GlyphRasterizer<F> for Gamma {
+///     fn rasterize(&self, font: &F, key: RasterKey) -> Option<Coverage> {
+///         let mut coverage = OutlineRasterizer.rasterize(font, key)?;
+///
+///         for value in &mut coverage.data {
+///             *value = ((*value as f32 / 255.0).powf(self.0) * 255.0) as u8;
+///         }
+///
+///         Some(coverage)
+///     }
+/// }
+/// ```
+pub trait GlyphRasterizer<F>: Send + Sync {
+    /// Returns the coverage of the glyph of the given key in the given
+    /// font, or `None` if it has no pixels to draw.
+    fn rasterize(&self, font: &F, key: RasterKey) -> Option<Coverage>;
+}
+
+/// The glyph to rasterize with a
+/// [`GlyphRasterizer`](trait.GlyphRasterizer.html).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RasterKey {
+    /// The glyph of the font.
+    pub glyph_id: GlyphId,
+    /// The scale of the glyph, in pixels.
+    pub scale: PxScale,
+    /// The offset of the origin of the glyph from the pixel grid, between
+    /// `0.0` and `1.0` on both axes.
+    pub subpixel_offset: (f32, f32),
+}
+
+/// The pixels covered by a glyph, as produced by a
+/// [`GlyphRasterizer`](trait.GlyphRasterizer.html).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Coverage {
+    /// The offset of the left edge of the pixels from the pixel containing
+    /// the origin of the glyph.
+    pub left: i32,
+    /// The offset of the top edge of the pixels from the pixel containing
+    /// the origin of the glyph, growing downwards.
+    pub top: i32,
+    /// The width of the pixels.
+    pub width: u32,
+    /// The height of the pixels.
+    pub height: u32,
+    /// The coverage of every pixel, row by row from the top, where `255`
+    /// covers a pixel completely.
+    pub data: Vec<u8>,
+}
+
+/// The default [`GlyphRasterizer`](trait.GlyphRasterizer.html), filling the
+/// outlines of the font with `ab_glyph`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OutlineRasterizer;
+
+impl<F: Font> GlyphRasterizer<F> for OutlineRasterizer {
+    fn rasterize(&self, font: &F, key: RasterKey) -> Option<Coverage> {
+        let (x, y) = key.subpixel_offset;
+        let outlined = font.outline_glyph(Glyph {
+            id: key.glyph_id,
+            scale: key.scale,
+            position: point(x, y),
+        })?;
+
+        let bounds = outlined.px_bounds();
+        let (width, height) =
+            (pixels(bounds.width())?, pixels(bounds.height())?);
+        let mut data = vec![0; (width as usize).checked_mul(height as usize)?];
+
+        outlined.draw(|x, y, v| {
+            data[y as usize * width as usize + x as usize] = (v * 255.0) as u8;
+        });
+
+        Some(Coverage {
+            left: bounds.min.x as i32,
+            top: bounds.min.y as i32,
+            width,
+            height,
+            data,
+        })
+    }
+}
+
+/// Returns the number of pixels spanned by a side of the pixel bounds of a
+/// glyph, or `None` if it is not a valid size.
+fn pixels(length: f32) -> Option<u32> {
+    (0.0..=u32::MAX as f32)
+        .contains(&length)
+        .then_some(length as u32)
+}
+
+/// A font with its glyphs rasterized by a
+/// [`GlyphRasterizer`](trait.GlyphRasterizer.html).
+///
+/// This is the font type of a brush built with
+/// [`GlyphBrushBuilder::rasterizer`](struct.GlyphBrushBuilder.html#method.rasterizer).
+/// Fonts added to such a brush later on are wrapped with
+/// [`with_font`](#method.with_font).
+pub struct RasterizedFont<F> {
+    font: F,
+    rasterizer: Arc<dyn GlyphRasterizer<F>>,
+}
+
+impl<F> RasterizedFont<F> {
+    /// Creates a `RasterizedFont` drawing the glyphs of the given font with
+    /// the given rasterizer.
+    pub fn new(font: F, rasterizer: impl GlyphRasterizer<F> + 'static) -> Self {
+        RasterizedFont {
+            font,
+            rasterizer: Arc::new(rasterizer),
+        }
+    }
+
+    /// Creates a `RasterizedFont` drawing the glyphs of the given font with
+    /// the rasterizer of this one.
+    pub fn with_font(&self, font: F) -> Self {
+        RasterizedFont {
+            font,
+            rasterizer: Arc::clone(&self.rasterizer),
+        }
+    }
+
+    /// Returns the wrapped font.
+    pub fn font(&self) -> &F {
+        &self.font
+    }
+
+    pub(crate) fn wrap_all(
+        fonts: Vec<F>,
+        rasterizer: impl GlyphRasterizer<F> + 'static,
+    ) -> Vec<Self> {
+        let rasterizer: Arc<dyn GlyphRasterizer<F>> = Arc::new(rasterizer);
+
+        fonts
+            .into_iter()
+            .map(|font| RasterizedFont {
+                font,
+                rasterizer: Arc::clone(&rasterizer),
+            })
+            .collect()
+    }
+}
+
+impl<F: Clone> Clone for RasterizedFont<F> {
+    fn clone(&self) -> Self {
+        self.with_font(self.font.clone())
+    }
+}
+
+impl<F: fmt::Debug> fmt::Debug for RasterizedFont<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RasterizedFont")
+            .field("font", &self.font)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<F: Font> Font for RasterizedFont<F> {
+    #[inline]
+    fn units_per_em(&self) -> Option<f32> {
+        self.font.units_per_em()
+    }
+
+    #[inline]
+    fn ascent_unscaled(&self) -> f32 {
+        self.font.ascent_unscaled()
+    }
+
+    #[inline]
+    fn descent_unscaled(&self) -> f32 {
+        self.font.descent_unscaled()
+    }
+
+    #[inline]
+    fn line_gap_unscaled(&self) -> f32 {
+        self.font.line_gap_unscaled()
+    }
+
+    #[inline]
+    fn italic_angle(&self) -> f32 {
+        self.font.italic_angle()
+    }
+
+    #[inline]
+    fn glyph_id(&self, c: char) -> GlyphId {
+        self.font.glyph_id(c)
+    }
+
+    #[inline]
+    fn h_advance_unscaled(&self, id: GlyphId) -> f32 {
+        self.font.h_advance_unscaled(id)
+    }
+
+    #[inline]
+    fn h_side_bearing_unscaled(&self, id: GlyphId) -> f32 {
+        self.font.h_side_bearing_unscaled(id)
+    }
+
+    #[inline]
+    fn v_advance_unscaled(&self, id: GlyphId) -> f32 {
+        self.font.v_advance_unscaled(id)
+    }
+
+    #[inline]
+    fn v_side_bearing_unscaled(&self, id: GlyphId) -> f32 {
+        self.font.v_side_bearing_unscaled(id)
+    }
+
+    #[inline]
+    fn kern_unscaled(&self, first: GlyphId, second: GlyphId) -> f32 {
+        self.font.kern_unscaled(first, second)
+    }
+
+    #[inline]
+    fn outline(&self, id: GlyphId) -> Option<Outline> {
+        self.font.outline(id)
+    }
+
+    #[inline]
+    fn glyph_count(&self) -> usize {
+        self.font.glyph_count()
+    }
+
+    #[inline]
+    fn codepoint_ids(&self) -> CodepointIdIter<'_> {
+        self.font.codepoint_ids()
+    }
+
+    #[inline]
+    fn glyph_raster_image2(
+        &self,
+        id: GlyphId,
+        pixel_size: u16,
+    ) -> Option<v2::GlyphImage<'_>> {
+        self.font.glyph_raster_image2(id, pixel_size)
+    }
+
+    #[inline]
+    fn glyph_svg_image(&self, id: GlyphId) -> Option<GlyphSvg<'_>> {
+        self.font.glyph_svg_image(id)
+    }
+
+    #[inline]
+    fn font_data(&self) -> &[u8] {
+        self.font.font_data()
+    }
+
+    fn outline_glyph(&self, glyph: Glyph) -> Option<OutlinedGlyph> {
+        let origin = point(glyph.position.x.floor(), glyph.position.y.floor());
+
+        let coverage = self.rasterizer.rasterize(
+            &self.font,
+            RasterKey {
+                glyph_id: glyph.id,
+                scale: glyph.scale,
+                subpixel_offset: (
+                    glyph.position.x - origin.x,
+                    glyph.position.y - origin.y,
+                ),
+            },
+        )?;
+
+        coverage_outline(glyph, origin, &coverage)
+    }
+}
+
+/// Traces the coverage of a glyph as an outline that the draw cache of
+/// `glyph_brush` rasterizes back into the same pixels.
+///
+/// The rasterizer of `ab_glyph` adds the height of the edges crossing a
+/// pixel to it and sums the pixels along the rows, so a vertical edge on
+/// the left side of a pixel, as tall as the change of coverage from the
+/// pixel before, gives every pixel its own coverage. A row is traced with
+/// one edge per change of coverage, at most a pixel tall each. Coverage is
+/// raised by half a step, so the truncation of the rasterized value gives
+/// back the original one, and the saturation of full coverage is kept.
+///
+/// Returns `None` if the coverage has no pixels.
+pub(crate) fn coverage_outline(
+    glyph: Glyph,
+    origin: Point,
+    coverage: &Coverage,
+) -> Option<OutlinedGlyph> {
+    let Coverage {
+        left,
+        top,
+        width,
+        height,
+        ref data,
+    } = *coverage;
+
+    let size = (width as usize).checked_mul(height as usize)?;
+
+    if size == 0 || data.len() < size {
+        return None;
+    }
+
+    let position = glyph.position;
+    let min = point(origin.x + left as f32, origin.y + top as f32);
+    let max = point(min.x + width as f32, min.y + height as f32);
+
+    // Outlines are relative to the position of the glyph, with the y axis
+    // growing upwards. The bounds are half a pixel inside the pixels, so the
+    // rounding of `ab_glyph` finds them again
+    let relative = |x: f32, y: f32| point(x - position.x, position.y - y);
+
+    let bounds = Rect {
+        min: relative(min.x + 0.5, min.y + 0.5),
+        max: relative(max.x - 0.5, max.y - 0.5),
+    };
+
+    // The rasterizer draws an outline point `p` at `(p.x, -p.y) + offset`
+    let offset = position - min;
+    let to_outline = |x: f32, y: f32| point(x - offset.x, offset.y - y);
+
+    let level = |value: u8| match value {
+        0 => 0.0,
+        value => (f32::from(value) + 0.5) / 255.0,
+    };
+
+    let mut curves = Vec::new();
+
+    for (y, row) in data[..size].chunks_exact(width as usize).enumerate() {
+        let y = y as f32;
+        let mut previous = 0.0;
+
+        // The row is closed by an edge past its last pixel
+        for (x, value) in row.iter().chain([&0]).enumerate() {
+            let x = x as f32;
+            let mut step = level(*value) - previous;
+
+            previous = level(*value);
+
+            // Edges going down add coverage, and edges going up remove it
+            while step != 0.0 {
+                let height = step.abs().min(1.0);
+                let (from, to) = if step > 0.0 {
+                    (y, y + height)
+                } else {
+                    (y + height, y)
+                };
+
+                curves.push(OutlineCurve::Line(
+                    to_outline(x, from),
+                    to_outline(x, to),
+                ));
+
+                step -= height.copysign(step);
+            }
+        }
+    }
+
+    Some(OutlinedGlyph::new(
+        glyph,
+        Outline { bounds, curves },
+        PxScaleFactor {
+            horizontal: 1.0,
+            vertical: 1.0,
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::font;
+
+    /// Draws an outlined glyph like the draw cache of `glyph_brush`.
+    fn draw(outlined: &OutlinedGlyph) -> (Rect, Vec<u8>) {
+        let bounds = outlined.px_bounds();
+        let width = bounds.width() as usize;
+        let mut data = vec![0; width * bounds.height() as usize];
+
+        outlined.draw(|x, y, v| {
+            data[y as usize * width + x as usize] = (v * 255.0) as u8;
+        });
+
+        (bounds, data)
+    }
+
+    #[test]
+    fn coverage_round_trips_exactly() {
+        let coverage = Coverage {
+            left: -3,
+            top: -12,
+            width: 16,
+            height: 16,
+            data: (0..=255).collect(),
+        };
+
+        let glyph = Glyph {
+            id: GlyphId(1),
+            scale: PxScale::from(16.0),
+            position: point(10.25, 20.75),
+        };
+
+        let outlined =
+            coverage_outline(glyph, point(10.0, 20.0), &coverage).unwrap();
+        let (bounds, data) = draw(&outlined);
+
+        assert_eq!(bounds.min, point(7.0, 8.0));
+        assert_eq!(bounds.max, point(23.0, 24.0));
+        assert_eq!(data, coverage.data);
+    }
+
+    #[test]
+    fn long_runs_of_coverage_round_trip_exactly() {
+        // Runs of full and partial coverage, with steps up and down
+        let row = [0, 255, 255, 255, 7, 7, 0, 254, 255, 1, 1, 0];
+        let coverage = Coverage {
+            left: 0,
+            top: -40,
+            width: 12 * 40,
+            height: 40,
+            data: row.repeat(40 * 40),
+        };
+
+        let glyph = Glyph {
+            id: GlyphId(1),
+            scale: PxScale::from(40.0),
+            position: point(500.5, 300.125),
+        };
+
+        let outlined =
+            coverage_outline(glyph, point(500.0, 300.0), &coverage).unwrap();
+
+        assert_eq!(draw(&outlined).1, coverage.data);
+    }
+
+    #[test]
+    fn rasterized_fonts_draw_the_pixels_of_the_outlines() {
+        let font = font();
+        let rasterized = RasterizedFont::new(font.clone(), OutlineRasterizer);
+
+        for c in ['g', '@', 'W'] {
+            let glyph = Glyph {
+                id: font.glyph_id(c),
+                scale: PxScale::from(23.0),
+                position: point(5.3, 17.6),
+            };
+
+            let expected = draw(&font.outline_glyph(glyph.clone()).unwrap());
+            let actual = draw(&rasterized.outline_glyph(glyph).unwrap());
+
+            assert_eq!(actual, expected, "{c:?}");
+        }
+    }
+
+    #[test]
+    fn invalid_coverage_has_no_outline() {
+        let glyph = Glyph {
+            id: GlyphId(1),
+            scale: PxScale::from(16.0),
+            position: point(0.0, 0.0),
+        };
+
+        let coverage = |width, height, data: Vec<u8>| Coverage {
+            left: 0,
+            top: 0,
+            width,
+            height,
+            data,
+        };
+
+        let origin = point(0.0, 0.0);
+
+        assert!(
+            coverage_outline(glyph.clone(), origin, &coverage(0, 4, vec![]))
+                .is_none()
+        );
+        assert!(
+            coverage_outline(
+                glyph.clone(),
+                origin,
+                &coverage(2, 2, vec![255; 3])
+            )
+            .is_none()
+        );
+        assert!(
+            coverage_outline(
+                glyph,
+                origin,
+                &coverage(u32::MAX, u32::MAX, vec![1])
+            )
+            .is_none()
+        );
+    }
+
+    #[test]
+    fn pixel_sizes_are_checked() {
+        assert_eq!(pixels(12.0), Some(12));
+        assert_eq!(pixels(-1.0), None);
+        assert_eq!(pixels(f32::NAN), None);
+        assert_eq!(pixels(f32::INFINITY), None);
+    }
+}
@@ -1,11 +1,48 @@
 struct Globals {
     transform: mat4x4<f32>,
+    // The view-projection matrix of the camera of billboards
+    camera: mat4x4<f32>,
 }
 
 @group(0) @binding(0) var<uniform> globals: Globals;
 @group(0) @binding(1) var font_sampler: sampler;
 @group(0) @binding(2) var font_tex: texture_2d<f32>;
 
+// The stops of 64 gradients of 8 stops each, with the offsets packed four
+// to a vector
+struct Gradients {
+    colors: array<vec4f, 512>,
+    offsets: array<vec4f, 128>,
+}
+
+@group(0) @binding(3) var<uniform> gradients: Gradients;
+@group(0) @binding(4) var fill_sampler: sampler;
+@group(0) @binding(5) var fill_tex: texture_2d<f32>;
+
+// The colors of the 256 styles of the brush, followed by the 256 colors of
+// its palette
+struct Palette {
+    colors: array<vec4f, 512>,
+}
+
+@group(0) @binding(6) var<uniform> palette: Palette;
+@group(0) @binding(7) var image_tex: texture_2d<f32>;
+
+// The data shared by the quads of a section, or of a text of a section
+struct Section {
+    transform: vec4f,
+    // The translation of the transform, followed by the origin it is
+    // applied around
+    translation: vec4f,
+    // The anchor of a billboard, followed by its mode
+    billboard: vec4f,
+    fill_geometry: vec4f,
+    billboard_size: vec2f,
+    fill: vec2u,
+}
+
+@group(0) @binding(8) var<storage, read> sections: array<Section>;
+
 struct VertexInput {
     @builtin(vertex_index) vertex_index: u32,
     @location(0) left_top: vec3f,
@@ -13,14 +50,46 @@ struct VertexInput {
     @location(2) tex_left_top: vec2f,
     @location(3) tex_right_bottom: vec2f,
     @location(4) color: vec4f,
+    @location(5) kind: u32,
+    @location(6) outline_color: vec4f,
+    @location(7) tex_bounds: vec4f,
+    @location(8) radius: vec2f,
+    @location(9) palette: u32,
+    // The index of the section of the quad in `sections`
+    @location(10) section: u32,
 }
 
 struct VertexOutput {
     @builtin(position) position: vec4f,
     @location(0) f_tex_pos: vec2f,
     @location(1) f_color: vec4f,
+    @location(2) @interpolate(flat) f_kind: u32,
+    @location(3) @interpolate(flat) f_outline_color: vec4f,
+    @location(4) @interpolate(flat) f_tex_bounds: vec4f,
+    @location(5) @interpolate(flat) f_radius: vec2f,
+    @location(6) f_pos: vec2f,
+    @location(7) @interpolate(flat) f_fill: vec2u,
+    @location(8) @interpolate(flat) f_fill_geometry: vec4f,
 }
 
+const KIND_GLYPH: u32 = 0u;
+const KIND_SOLID: u32 = 1u;
+const KIND_DASHED: u32 = 2u;
+const KIND_DOTTED: u32 = 3u;
+const KIND_WAVY: u32 = 4u;
+const KIND_SHADOW: u32 = 5u;
+const KIND_IMAGE: u32 = 6u;
+
+const FILL_LINEAR: u32 = 1u;
+const FILL_RADIAL: u32 = 2u;
+const FILL_TEXTURE: u32 = 3u;
+
+const STOPS: u32 = 8u;
+
+const BILLBOARD_WORLD: f32 = 1.0;
+
+const PI: f32 = 3.14159265;
+
 @vertex
 fn vs_main(input: VertexInput) -> VertexOutput {
     var out: VertexOutput;
@@ -52,15 +121,262 @@ fn vs_main(input: VertexInput) -> VertexOutput {
     }
 
     out.f_color = input.color;
-    out.position = globals.transform * vec4(pos, input.left_top.z, 1.0);
+
+    // Quads with a palette color or a style look up their color, so changing
+    // it keeps their vertices
+    if (input.palette > 0u) {
+        out.f_color = palette.colors[input.palette - 1u];
+    }
+
+    out.f_kind = input.kind;
+    out.f_outline_color = input.outline_color;
+    out.f_tex_bounds = input.tex_bounds;
+    out.f_radius = input.radius;
+    let section = sections[input.section];
+
+    out.f_fill = section.fill;
+    out.f_fill_geometry = section.fill_geometry;
+
+    // Fills and transforms are relative to the origin of the section, so
+    // moving it keeps the vertices
+    let origin = section.translation.zw;
+    out.f_pos = pos - origin;
+
+    // The transform of the section, applied after clipping to its bounds
+    let linear = mat2x2f(section.transform.xy, section.transform.zw);
+    let transformed = linear * (pos - origin) + section.translation.xy;
+
+    if (section.billboard.w > 0.0) {
+        out.position = billboard(transformed, section.billboard, section.billboard_size);
+    } else {
+        out.position = globals.transform * vec4(transformed + origin, input.left_top.z, 1.0);
+    }
 
     return out;
 }
 
+// Places a point of a billboard, relative to its origin, in clip space around
+// its anchor and at the depth of the anchor
+fn billboard(pos: vec2f, billboard: vec4f, size: vec2f) -> vec4f {
+    let anchor = globals.camera * vec4(billboard.xyz, 1.0);
+
+    // Billboards behind the camera collapse to a point outside of clip space
+    if (anchor.w <= 0.0) {
+        return vec4f(2.0, 2.0, 2.0, 1.0);
+    }
+
+    var right = vec2f(2.0 * anchor.w / size.x, 0.0);
+    var down = vec2f(0.0, -2.0 * anchor.w / size.y);
+
+    if (billboard.w == BILLBOARD_WORLD) {
+        // The rows of the matrix are the camera axes, scaled by the
+        // projection
+        let axes = transpose(globals.camera);
+        let x = axes[0].xyz;
+        let y = axes[1].xyz;
+
+        right = (globals.camera * vec4(x * size.x / max(length(x), 1e-6), 0.0)).xy;
+        down = (globals.camera * vec4(-y * size.y / max(length(y), 1e-6), 0.0)).xy;
+    }
+
+    return vec4(anchor.xy + right * pos.x + down * pos.y, anchor.zw);
+}
+
+// Returns the coverage of a pixel at the given signed distance from the
+// edge of a shape, with the size of a pixel in the same units
+fn coverage(distance: f32, pixel: f32) -> f32 {
+    return clamp(distance / max(pixel, 1e-4) + 0.5, 0.0, 1.0);
+}
+
+// Dashes of 3 units followed by gaps of 2 units
+fn dashed(pos: vec2f, pixel: vec2f) -> f32 {
+    let x = fract(pos.x / 5.0) * 5.0;
+    let distance = select(-min(x - 3.0, 5.0 - x), min(x, 3.0 - x), x <= 3.0);
+
+    return coverage(distance, pixel.x);
+}
+
+// Round dots of 1 unit every 2 units
+fn dotted(pos: vec2f, pixel: vec2f) -> f32 {
+    let x = fract(pos.x / 2.0) * 2.0;
+    let offset = vec2f(min(abs(x - 0.5), abs(x - 2.5)), pos.y - 0.5);
+
+    return coverage(0.5 - length(offset), max(pixel.x, pixel.y));
+}
+
+// A sine wave a third of the height thick, oscillating between the top and
+// the bottom of the quad every 2 units
+fn wavy(pos: vec2f, pixel: vec2f) -> f32 {
+    let center = 0.5 - sin(pos.x * PI) / 3.0;
+    let slope = -cos(pos.x * PI) * PI / 3.0;
+    let distance = (1.0 / 6.0 - abs(pos.y - center)) / sqrt(1.0 + slope * slope);
+
+    return coverage(distance, pixel.y);
+}
+
+// The coverage of the glyph at the given position, ignoring the texels of
+// other glyphs in the cache
+fn glyph_coverage(pos: vec2f, bounds: vec4f) -> f32 {
+    if (any(pos < bounds.xy) || any(pos > bounds.zw)) {
+        return 0.0;
+    }
+
+    return textureSampleLevel(font_tex, font_sampler, pos, 0.0).r;
+}
+
+// The color of a gradient at the given offset, interpolating between the
+// sorted stops of its slot
+fn gradient(slot: u32, t: f32) -> vec4f {
+    let first = slot * STOPS;
+    var color = gradients.colors[first];
+    var previous = gradients.offsets[first / 4u].x;
+
+    for (var i = first + 1u; i < first + STOPS; i++) {
+        let offset = gradients.offsets[i / 4u][i % 4u];
+        let weight = clamp((t - previous) / max(offset - previous, 1e-6), 0.0, 1.0);
+
+        color = mix(color, gradients.colors[i], weight);
+        previous = offset;
+    }
+
+    return color;
+}
+
+// The color of a glyph at its position in the section
+fn fill_color(input: VertexOutput) -> vec4f {
+    let geometry = input.f_fill_geometry;
+
+    switch input.f_fill.x {
+        case FILL_LINEAR: {
+            let direction = geometry.zw - geometry.xy;
+            let t = dot(input.f_pos - geometry.xy, direction)
+                / max(dot(direction, direction), 1e-6);
+
+            return gradient(input.f_fill.y, t);
+        }
+        case FILL_RADIAL: {
+            let t = length(input.f_pos - geometry.xy) / max(geometry.z, 1e-6);
+
+            return gradient(input.f_fill.y, t);
+        }
+        case FILL_TEXTURE: {
+            let uv = (input.f_pos - geometry.xy) / geometry.zw;
+
+            return textureSampleLevel(fill_tex, fill_sampler, uv, 0.0);
+        }
+        default: {
+            return input.f_color;
+        }
+    }
+}
+
+// Composites a glyph over its outline, found by dilating the coverage of the
+// glyph with samples on three rings around the pixel
+fn outlined(input: VertexOutput, glyph_color: vec4f) -> vec4f {
+    let fill = glyph_coverage(input.f_tex_pos, input.f_tex_bounds);
+    var stroke = fill;
+
+    for (var ring = 1; ring <= 3; ring++) {
+        let radius = input.f_radius * f32(ring) / 3.0;
+
+        for (var i = 0; i < 12; i++) {
+            let angle = (f32(i) + f32(ring) * 0.5) * PI / 6.0;
+            let offset = vec2f(cos(angle), sin(angle)) * radius;
+
+            stroke = max(
+                stroke,
+                glyph_coverage(input.f_tex_pos + offset, input.f_tex_bounds),
+            );
+        }
+    }
+
+    let fill_alpha = fill * glyph_color.a;
+    let stroke_alpha = stroke * input.f_outline_color.a * (1.0 - fill_alpha);
+    let alpha = fill_alpha + stroke_alpha;
+
+    if (alpha <= 0.0) {
+        discard;
+    }
+
+    let color = glyph_color.rgb * fill_alpha
+        + input.f_outline_color.rgb * stroke_alpha;
+
+    return vec4f(color / alpha, alpha);
+}
+
+// The coverage of the glyph blurred with a gaussian kernel, sampled on a
+// 7x7 grid spanning its blur radius
+fn blurred(input: VertexOutput) -> f32 {
+    if (all(input.f_radius == vec2f(0.0))) {
+        return glyph_coverage(input.f_tex_pos, input.f_tex_bounds);
+    }
+
+    var sum = 0.0;
+    var total = 0.0;
+
+    for (var y = -3; y <= 3; y++) {
+        for (var x = -3; x <= 3; x++) {
+            let offset = vec2f(f32(x), f32(y)) / 3.0;
+            let weight = exp(-2.0 * dot(offset, offset));
+            let pos = input.f_tex_pos + offset * input.f_radius;
+
+            sum += weight * glyph_coverage(pos, input.f_tex_bounds);
+            total += weight;
+        }
+    }
+
+    return sum / total;
+}
+
 @fragment
 fn fs_main(input: VertexOutput) -> @location(0) vec4f {
+    // Derivatives must be computed in uniform control flow
+    let pixel = fwidth(input.f_tex_pos);
     var alpha = textureSample(font_tex, font_sampler, input.f_tex_pos).r;
 
+    switch input.f_kind {
+        case KIND_SOLID: {
+            alpha = 1.0;
+        }
+        case KIND_DASHED: {
+            alpha = dashed(input.f_tex_pos, pixel);
+        }
+        case KIND_DOTTED: {
+            alpha = dotted(input.f_tex_pos, pixel);
+        }
+        case KIND_WAVY: {
+            alpha = wavy(input.f_tex_pos, pixel);
+        }
+        case KIND_SHADOW: {
+            alpha = blurred(input);
+        }
+        case KIND_IMAGE: {
+            // Images are placed in pixels, so the atlas can grow under them
+            let size = vec2f(textureDimensions(image_tex));
+            let texel = textureSampleLevel(image_tex, font_sampler, input.f_tex_pos / size, 0.0)
+                * input.f_color;
+
+            if (texel.a <= 0.0) {
+                discard;
+            }
+
+            return texel;
+        }
+        default: {
+            let color = fill_color(input);
+
+            if (input.f_radius.x > 0.0) {
+                return outlined(input, color);
+            }
+
+            if (alpha <= 0.0) {
+                discard;
+            }
+
+            return color * vec4f(1.0, 1.0, 1.0, alpha);
+        }
+    }
+
     if (alpha <= 0.0) {
         discard;
     }
@@ -0,0 +1,429 @@
+//! OpenType text shaping.
+//!
+//! Turns runs of text into positioned glyphs with [`rustybuzz`], which
+//! applies the `GSUB` and `GPOS` lookups of a font. This is needed for
+//! ligatures, contextual alternates and complex scripts like Arabic and
+//! Devanagari.
+//!
+//! [`rustybuzz`]: https://github.com/harfbuzz/rustybuzz
+use glyph_brush::ab_glyph::{Font, GlyphId, PxScale, ScaleFont};
+use unicode_script::{Script, UnicodeScript};
+
+use std::ops::Range;
+
+/// An OpenType feature toggle, e.g. `liga`, `calt` or `tnum`.
+///
+/// Features that are enabled by default, like `liga` or `kern`, can be
+/// disabled. Any other feature supported by the font can be enabled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Feature {
+    /// The OpenType tag of the feature.
+    pub tag: [u8; 4],
+    /// Whether the feature is enabled.
+    pub enabled: bool,
+}
+
+impl Feature {
+    /// Creates a feature toggle.
+    pub const fn new(tag: &[u8; 4], enabled: bool) -> Self {
+        Feature { tag: *tag, enabled }
+    }
+
+    /// Enables the given feature.
+    pub const fn on(tag: &[u8; 4]) -> Self {
+        Feature::new(tag, true)
+    }
+
+    /// Disables the given feature.
+    pub const fn off(tag: &[u8; 4]) -> Self {
+        Feature::new(tag, false)
+    }
+
+    fn to_rustybuzz(self) -> rustybuzz::Feature {
+        rustybuzz::Feature::new(
+            rustybuzz::ttf_parser::Tag::from_bytes(&self.tag),
+            u32::from(self.enabled),
+            ..,
+        )
+    }
+}
+
+/// A glyph produced by [`shape`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShapedGlyph {
+    /// The glyph of the font.
+    pub id: GlyphId,
+    /// The byte index of the first character of the cluster this glyph
+    /// belongs to.
+    pub cluster: usize,
+    /// How much the pen advances horizontally after drawing the glyph, in
+    /// pixels.
+    pub x_advance: f32,
+    /// How much the glyph is moved horizontally from the pen position, in
+    /// pixels.
+    pub x_offset: f32,
+    /// How much the glyph is moved upwards from the baseline, in pixels.
+    pub y_offset: f32,
+}
+
+/// Shapes a run of text that uses a single font, scale and direction.
+///
+/// The text is split by script and every script run is shaped with the
+/// default features of its script, plus or minus the given `features`.
+/// Right-to-left runs get their mirrored characters, like parentheses,
+/// from the shaper.
+///
+/// Clusters are returned in logical order, so right-to-left runs still need
+/// to be reordered for display. The glyphs of a cluster stay in visual order,
+/// so their offsets apply from left to right.
+pub fn shape<F: Font>(
+    font: &F,
+    scale: PxScale,
+    text: &str,
+    right_to_left: bool,
+    features: &[Feature],
+) -> Vec<ShapedGlyph> {
+    let scaled = font.as_scaled(scale);
+    let (h_factor, v_factor) =
+        (scaled.h_scale_factor(), scaled.v_scale_factor());
+
+    let Some(face) = crate::face::parse(font) else {
+        return fallback(font, text)
+            .into_iter()
+            .map(|glyph| glyph.scaled(h_factor, v_factor))
+            .collect();
+    };
+
+    let face = rustybuzz::Face::from_face(face);
+    let features: Vec<rustybuzz::Feature> = features
+        .iter()
+        .map(|feature| feature.to_rustybuzz())
+        .collect();
+
+    let mut glyphs = Vec::new();
+
+    for range in script_runs(text) {
+        let mut buffer = rustybuzz::UnicodeBuffer::new();
+        buffer.set_pre_context(&text[..range.start]);
+        buffer.set_post_context(&text[range.end..]);
+
+        for (index, c) in text[range.clone()].char_indices() {
+            buffer.add(c, (range.start + index) as u32);
+        }
+
+        buffer.set_direction(if right_to_left {
+            rustybuzz::Direction::RightToLeft
+        } else {
+            rustybuzz::Direction::LeftToRight
+        });
+
+        let output = rustybuzz::shape(&face, &features, buffer);
+
+        let run: Vec<ShapedGlyph> = output
+            .glyph_infos()
+            .iter()
+            .zip(output.glyph_positions())
+            .map(|(info, position)| {
+                ShapedGlyph {
+                    id: GlyphId(info.glyph_id as u16),
+                    cluster: info.cluster as usize,
+                    x_advance: position.x_advance as f32,
+                    x_offset: position.x_offset as f32,
+                    y_offset: position.y_offset as f32,
+                }
+                .scaled(h_factor, v_factor)
+            })
+            .collect();
+
+        if right_to_left {
+            // The shaper returns glyphs in visual order
+            glyphs.extend(
+                run.chunk_by(|a, b| a.cluster == b.cluster).rev().flatten(),
+            );
+        } else {
+            glyphs.extend(run);
+        }
+    }
+
+    glyphs
+}
+
+/// Maps characters to glyphs one by one with `kern` table kerning, used
+/// when the font cannot be parsed for layout tables.
+fn fallback<F: Font>(font: &F, text: &str) -> Vec<ShapedGlyph> {
+    let mut glyphs: Vec<ShapedGlyph> = Vec::with_capacity(text.len());
+
+    for (cluster, c) in text.char_indices() {
+        let id = font.glyph_id(c);
+
+        if let Some(last) = glyphs.last_mut() {
+            last.x_advance += font.kern_unscaled(last.id, id);
+        }
+
+        glyphs.push(ShapedGlyph {
+            id,
+            cluster,
+            x_advance: font.h_advance_unscaled(id),
+            x_offset: 0.0,
+            y_offset: 0.0,
+        });
+    }
+
+    glyphs
+}
+
+impl ShapedGlyph {
+    fn scaled(self, h_factor: f32, v_factor: f32) -> Self {
+        ShapedGlyph {
+            x_advance: self.x_advance * h_factor,
+            x_offset: self.x_offset * h_factor,
+            y_offset: self.y_offset * v_factor,
+            ..self
+        }
+    }
+}
+
+/// Splits the text in runs of a single script. Common, inherited and unknown
+/// characters are merged into the surrounding run.
+fn script_runs(text: &str) -> Vec<Range<usize>> {
+    let mut runs: Vec<(Script, Range<usize>)> = Vec::new();
+
+    for (index, c) in text.char_indices() {
+        let script = match c.script() {
+            Script::Inherited | Script::Unknown => Script::Common,
+            script => script,
+        };
+        let end = index + c.len_utf8();
+
+        match runs.last_mut() {
+            Some((current, range))
+                if *current == script || script == Script::Common =>
+            {
+                range.end = end;
+            }
+            Some((current @ Script::Common, range)) => {
+                *current = script;
+                range.end = end;
+            }
+            _ => runs.push((script, index..end)),
+        }
+    }
+
+    runs.into_iter().map(|(_, range)| range).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use glyph_brush::ab_glyph::{FontRef, FontVec};
+
+    fn dejavu() -> FontRef<'static> {
+        FontRef::try_from_slice(include_bytes!("../examples/DejaVuSans.ttf"))
+            .unwrap()
+    }
+
+    fn ids(glyphs: &[ShapedGlyph]) -> Vec<GlyphId> {
+        glyphs.iter().map(|glyph| glyph.id).collect()
+    }
+
+    fn clusters(glyphs: &[ShapedGlyph]) -> Vec<usize> {
+        glyphs.iter().map(|glyph| glyph.cluster).collect()
+    }
+
+    #[test]
+    fn latin_ligatures() {
+        let font = dejavu();
+        let scale = PxScale::from(32.0);
+
+        let glyphs = shape(&font, scale, "fit", false, &[]);
+
+        assert_eq!(
+            ids(&glyphs),
+            [font.glyph_id('\u{FB01}'), font.glyph_id('t')]
+        );
+        assert_eq!(clusters(&glyphs), [0, 2]);
+
+        let glyphs =
+            shape(&font, scale, "fit", false, &[Feature::off(b"liga")]);
+
+        assert_eq!(
+            ids(&glyphs),
+            [font.glyph_id('f'), font.glyph_id('i'), font.glyph_id('t')]
+        );
+        assert_eq!(clusters(&glyphs), [0, 1, 2]);
+    }
+
+    #[test]
+    fn latin_kerning() {
+        let font = dejavu();
+        let scale = PxScale::from(32.0);
+        let scaled = font.as_scaled(scale);
+        let a = scaled.h_advance(font.glyph_id('A'));
+
+        let kerned = shape(&font, scale, "AV", false, &[]);
+        let unkerned =
+            shape(&font, scale, "AV", false, &[Feature::off(b"kern")]);
+
+        assert!(kerned[0].x_advance < a - 1.0);
+        assert!((unkerned[0].x_advance - a).abs() < 0.01);
+        assert_eq!(ids(&kerned), ids(&unkerned));
+    }
+
+    #[test]
+    fn arabic_joining() {
+        let font = dejavu();
+
+        // Beh, beh, beh: initial, medial and final forms
+        let glyphs = shape(
+            &font,
+            PxScale::from(32.0),
+            "\u{628}\u{628}\u{628}",
+            true,
+            &[],
+        );
+
+        assert_eq!(
+            ids(&glyphs),
+            [
+                font.glyph_id('\u{FE91}'),
+                font.glyph_id('\u{FE92}'),
+                font.glyph_id('\u{FE90}'),
+            ]
+        );
+        assert_eq!(clusters(&glyphs), [0, 2, 4]);
+
+        // A single beh stays isolated
+        let glyphs = shape(&font, PxScale::from(32.0), "\u{628}", true, &[]);
+
+        assert_eq!(ids(&glyphs), [font.glyph_id('\u{628}')]);
+    }
+
+    #[test]
+    fn right_to_left_mirroring() {
+        let font = dejavu();
+
+        let glyphs = shape(&font, PxScale::from(32.0), "(\u{628})", true, &[]);
+
+        assert_eq!(
+            ids(&glyphs),
+            [
+                font.glyph_id(')'),
+                font.glyph_id('\u{628}'),
+                font.glyph_id('('),
+            ]
+        );
+        assert_eq!(clusters(&glyphs), [0, 1, 3]);
+    }
+
+    /// A font without layout tables mapping the given characters to glyphs
+    /// 1, 2 and so on, all 500 units wide.
+    fn font_with(chars: &[char]) -> FontVec {
+        fn table(tag: &[u8; 4], data: Vec<u8>) -> ([u8; 4], Vec<u8>) {
+            (*tag, data)
+        }
+
+        let glyphs = chars.len() as u16 + 1;
+
+        let mut head = vec![0, 1, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0];
+        head.extend(0x5F0F_3CF5_u32.to_be_bytes());
+        head.extend([0, 0, 0x03, 0xE8]);
+        head.resize(54, 0);
+
+        let mut hhea = vec![0, 1, 0, 0];
+        hhea.extend(800_i16.to_be_bytes());
+        hhea.extend((-200_i16).to_be_bytes());
+        hhea.resize(34, 0);
+        hhea.extend(glyphs.to_be_bytes());
+
+        let mut maxp = vec![0, 0, 0x50, 0];
+        maxp.extend(glyphs.to_be_bytes());
+
+        let hmtx = (0..glyphs)
+            .flat_map(|_| [0x01, 0xF4, 0, 0])
+            .collect::<Vec<u8>>();
+
+        let mut cmap = vec![0, 0, 0, 1, 0, 3, 0, 10, 0, 0, 0, 12];
+        cmap.extend(12_u16.to_be_bytes());
+        cmap.extend([0, 0]);
+        cmap.extend((16 + 12 * chars.len() as u32).to_be_bytes());
+        cmap.extend([0, 0, 0, 0]);
+        cmap.extend((chars.len() as u32).to_be_bytes());
+
+        for (glyph, c) in chars.iter().enumerate() {
+            cmap.extend((*c as u32).to_be_bytes());
+            cmap.extend((*c as u32).to_be_bytes());
+            cmap.extend((glyph as u32 + 1).to_be_bytes());
+        }
+
+        let tables = [
+            table(b"cmap", cmap),
+            table(b"head", head),
+            table(b"hhea", hhea),
+            table(b"hmtx", hmtx),
+            table(b"maxp", maxp),
+        ];
+
+        let mut data =
+            vec![0, 1, 0, 0, 0, tables.len() as u8, 0, 0, 0, 0, 0, 0];
+        let mut offset = data.len() + 16 * tables.len();
+
+        for (tag, table) in &tables {
+            data.extend(tag);
+            data.extend([0, 0, 0, 0]);
+            data.extend((offset as u32).to_be_bytes());
+            data.extend((table.len() as u32).to_be_bytes());
+            offset += table.len().next_multiple_of(4);
+        }
+
+        for (_, table) in &tables {
+            data.extend(table);
+            data.resize(data.len().next_multiple_of(4), 0);
+        }
+
+        FontVec::try_from_vec(data).unwrap()
+    }
+
+    #[test]
+    fn devanagari_reordering() {
+        // Ka, vowel sign I and a dotted circle
+        let font = font_with(&['\u{915}', '\u{93F}', '\u{25CC}']);
+        let (ka, i) = (font.glyph_id('\u{915}'), font.glyph_id('\u{93F}'));
+
+        // The vowel sign I is drawn before the consonant it follows
+        let glyphs =
+            shape(&font, PxScale::from(16.0), "\u{915}\u{93F}", false, &[]);
+
+        assert_eq!(ids(&glyphs), [i, ka]);
+        assert_eq!(clusters(&glyphs), [0, 0]);
+
+        let glyphs = shape(
+            &font,
+            PxScale::from(16.0),
+            "\u{915}\u{93F}\u{915}",
+            false,
+            &[],
+        );
+
+        assert_eq!(ids(&glyphs), [i, ka, ka]);
+        assert_eq!(clusters(&glyphs), [0, 0, 6]);
+    }
+
+    #[test]
+    fn script_runs_merge_common_characters() {
+        let text = "ab \u{628}\u{628} (c)";
+
+        assert_eq!(script_runs(text), [0..3, 3..9, 9..text.len()]);
+        assert_eq!(script_runs("1, 2"), vec![0..4]);
+        assert!(script_runs("").is_empty());
+    }
+
+    #[test]
+    fn unparsable_fonts_fall_back_to_characters() {
+        let font = dejavu();
+        let glyphs = fallback(&font, "ab");
+
+        assert_eq!(ids(&glyphs), [font.glyph_id('a'), font.glyph_id('b')]);
+        assert_eq!(clusters(&glyphs), [0, 1]);
+    }
+}
@@ -0,0 +1,213 @@
+use std::hash::{Hash, Hasher};
+
+/// The height of the lines of a section, replacing the line gap of their
+/// fonts.
+///
+/// The extra space is split evenly above and below the glyphs of a line.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LineHeight {
+    /// A multiple of the height of the tallest font of the line, i.e. its
+    /// ascent minus its descent, which is the size the font is scaled to.
+    Relative(f32),
+    /// A height in pixels.
+    Absolute(f32),
+}
+
+impl Hash for LineHeight {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+
+        match *self {
+            LineHeight::Relative(factor) => factor.to_bits().hash(state),
+            LineHeight::Absolute(height) => height.to_bits().hash(state),
+        }
+    }
+}
+
+/// The spacing of the lines and characters of a
+/// [`TextLayout`](struct.TextLayout.html).
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Spacing {
+    pub line_height: Option<LineHeight>,
+    pub paragraph_spacing: f32,
+    pub letter_spacing: f32,
+    pub text_letter_spacing: Vec<(usize, f32)>,
+}
+
+impl Spacing {
+    /// Returns the space added after every character of the `Text` at the
+    /// given index of a section.
+    pub fn letter_spacing(&self, text_index: usize) -> f32 {
+        self.text_letter_spacing
+            .iter()
+            .find(|(index, _)| *index == text_index)
+            .map_or(self.letter_spacing, |(_, spacing)| *spacing)
+    }
+
+    fn key(&self) -> impl PartialEq + Hash {
+        (
+            self.line_height.map(|line_height| match line_height {
+                LineHeight::Relative(factor) => (0, factor.to_bits()),
+                LineHeight::Absolute(height) => (1, height.to_bits()),
+            }),
+            self.paragraph_spacing.to_bits(),
+            self.letter_spacing.to_bits(),
+            self.text_letter_spacing
+                .iter()
+                .map(|(index, spacing)| (*index, spacing.to_bits()))
+                .collect::<Vec<_>>(),
+        )
+    }
+}
+
+impl PartialEq for Spacing {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for Spacing {}
+
+impl Hash for Spacing {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{self, advance, font};
+    use crate::{Layout, Section, SectionGlyph, Text, TextLayout};
+
+    use glyph_brush::ab_glyph::{Font, Point, ScaleFont};
+    use std::collections::hash_map::DefaultHasher;
+
+    /// Lays out the texts in bounds of the given width.
+    fn glyphs(
+        layout: TextLayout,
+        width: f32,
+        texts: &[&str],
+    ) -> Vec<SectionGlyph> {
+        let texts: Vec<Text<'_>> =
+            texts.iter().map(|text| Text::new(text)).collect();
+        let section = Section::default()
+            .with_bounds((width, 1000.0))
+            .with_text(texts);
+
+        test_util::glyphs(&layout, &section)
+    }
+
+    /// Returns the position of the glyph of the character at the given text
+    /// and byte index.
+    fn at(glyphs: &[SectionGlyph], text: usize, byte: usize) -> Point {
+        glyphs
+            .iter()
+            .find(|sg| (sg.section_index, sg.byte_index) == (text, byte))
+            .unwrap()
+            .glyph
+            .position
+    }
+
+    #[test]
+    fn letter_spacing_by_text() {
+        let spacing = Spacing {
+            letter_spacing: 2.0,
+            text_letter_spacing: vec![(1, -1.0)],
+            ..Spacing::default()
+        };
+
+        assert_eq!(spacing.letter_spacing(0), 2.0);
+        assert_eq!(spacing.letter_spacing(1), -1.0);
+        assert_eq!(spacing.letter_spacing(2), 2.0);
+    }
+
+    #[test]
+    fn letter_spacing_follows_every_character() {
+        let w = advance();
+
+        let layout = TextLayout::new(Layout::default_single_line())
+            .with_letter_spacing(3.0)
+            .with_text_letter_spacing(1, 0.0);
+        let glyphs = glyphs(layout, 1000.0, &["ab", "cd"]);
+
+        assert_eq!(at(&glyphs, 0, 1).x, w + 3.0);
+        assert_eq!(at(&glyphs, 1, 0).x, 2.0 * (w + 3.0));
+        assert_eq!(at(&glyphs, 1, 1).x, 2.0 * (w + 3.0) + w);
+    }
+
+    #[test]
+    fn line_heights() {
+        let font = font();
+        let height = font.as_scaled(16.0).height();
+        let gap = |layout: TextLayout| {
+            let glyphs = glyphs(layout, 1000.0, &["a\nb"]);
+
+            at(&glyphs, 0, 2).y - at(&glyphs, 0, 0).y
+        };
+
+        let absolute =
+            TextLayout::default().with_line_height(LineHeight::Absolute(40.0));
+        let relative =
+            TextLayout::default().with_line_height(LineHeight::Relative(2.0));
+
+        assert!((gap(absolute) - 40.0).abs() < 1e-3);
+        assert!((gap(relative) - 2.0 * height).abs() < 1e-3);
+    }
+
+    #[test]
+    fn paragraph_spacing_is_added_between_paragraphs() {
+        let line_height = LineHeight::Absolute(20.0);
+        let layout = TextLayout::new(Layout::default_wrap())
+            .with_line_height(line_height)
+            .with_paragraph_spacing(10.0);
+
+        // Only the hard break starts a paragraph
+        let lines = glyphs(layout.clone(), 1000.0, &["a\nb"]);
+
+        assert_eq!(at(&lines, 0, 2).y - at(&lines, 0, 0).y, 30.0);
+
+        let lines = glyphs(layout, 50.0, &["aaaa bbbb"]);
+
+        assert_eq!(at(&lines, 0, 5).y - at(&lines, 0, 0).y, 20.0);
+    }
+
+    #[test]
+    fn equality_and_hashing_use_bits() {
+        let hash = |value: &dyn Fn(&mut DefaultHasher)| {
+            let mut hasher = DefaultHasher::new();
+            value(&mut hasher);
+            hasher.finish()
+        };
+
+        let relative = LineHeight::Relative(1.5);
+        let absolute = LineHeight::Absolute(1.5);
+
+        assert_ne!(
+            hash(&|state| relative.hash(state)),
+            hash(&|state| absolute.hash(state))
+        );
+
+        let nan = Spacing {
+            letter_spacing: f32::NAN,
+            ..Spacing::default()
+        };
+
+        // Spacing compares the bits of its values, so a cached layout with a
+        // `NaN` spacing still matches itself
+        assert_eq!(nan, nan.clone());
+        assert_ne!(
+            Spacing::default(),
+            Spacing {
+                paragraph_spacing: -0.0,
+                ..Spacing::default()
+            }
+        );
+        assert_eq!(
+            hash(&|state| nan.hash(state)),
+            hash(&|state| nan.clone().hash(state))
+        );
+    }
+}
@@ -0,0 +1,324 @@
+use crate::FontId;
+
+use glyph_brush::ab_glyph::PxScale;
+use log::{log_enabled, warn};
+
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::sync::{Mutex, PoisonError};
+
+/// A font, scale and color shared by many texts, defined on the brush with
+/// [`define_style`](struct.GlyphBrush.html#method.define_style).
+///
+/// Texts refer to a style with
+/// [`TextAttributes::with_style`](struct.TextAttributes.html#method.with_style),
+/// by name or by the [`StyleId`](struct.StyleId.html) returned when defining
+/// it.
+/// The font and scale of the style replace the ones of the text when it is
+/// queued, while its color is looked up when drawing, so changing it
+/// recolors every queued text using the style without laying it out again.
+///
+/// ```
+/// use wgpu_glyph::{
+///     FontId, GlyphBrush, RichSection, Section, SectionAttributes, Text,
+///     TextAttributes, TextStyle,
+/// };
+///
+/// fn theme(brush: &mut GlyphBrush<()>, dark: bool) {
+///     let color = if dark { [0.9; 4] } else { [0.1, 0.1, 0.1, 1.0] };
+///
+///     brush.define_style("heading", TextStyle::new(FontId(1), 32.0, color));
+/// }
+///
+/// fn title(brush: &mut GlyphBrush<()>) {
+///     brush.queue(RichSection::new(
+///         Section::default().add_text(Text::new("Settings")),
+///         SectionAttributes::default()
+///             .with_text(0, TextAttributes::default().with_style("heading")),
+///     ));
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextStyle {
+    /// The font of the text.
+    pub font_id: FontId,
+    /// The scale of the text, in pixels.
+    pub scale: PxScale,
+    /// The color of the text.
+    pub color: [f32; 4],
+}
+
+impl TextStyle {
+    /// Creates a new `TextStyle`.
+    pub fn new(
+        font_id: FontId,
+        scale: impl Into<PxScale>,
+        color: [f32; 4],
+    ) -> Self {
+        TextStyle {
+            font_id,
+            scale: scale.into(),
+            color,
+        }
+    }
+}
+
+impl Default for TextStyle {
+    #[inline]
+    fn default() -> Self {
+        TextStyle::new(FontId::default(), 16.0, [0.0, 0.0, 0.0, 1.0])
+    }
+}
+
+/// A handle to a [`TextStyle`](struct.TextStyle.html) defined on a brush.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StyleId(u32);
+
+/// How a [`Text`](struct.Text.html) refers to its
+/// [`TextStyle`](struct.TextStyle.html).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum StyleRef {
+    /// The style with the given handle.
+    Id(StyleId),
+    /// The style with the given name.
+    Name(Cow<'static, str>),
+}
+
+impl From<StyleId> for StyleRef {
+    #[inline]
+    fn from(id: StyleId) -> Self {
+        StyleRef::Id(id)
+    }
+}
+
+impl From<&'static str> for StyleRef {
+    #[inline]
+    fn from(name: &'static str) -> Self {
+        StyleRef::Name(Cow::Borrowed(name))
+    }
+}
+
+impl From<String> for StyleRef {
+    #[inline]
+    fn from(name: String) -> Self {
+        StyleRef::Name(Cow::Owned(name))
+    }
+}
+
+/// The styles defined on a brush, with their colors stored in the palette
+/// uniform buffer.
+///
+/// The color of a style is found at the index of its handle, so glyphs keep
+/// their cached vertices when it changes.
+#[derive(Debug)]
+pub(crate) struct Styles {
+    styles: Vec<(String, TextStyle)>,
+    changed: bool,
+    /// The undefined styles already warned about, as they are looked up in
+    /// every frame.
+    warned: Mutex<HashSet<StyleRef>>,
+}
+
+impl Styles {
+    /// The amount of styles with their color in the palette. Texts with
+    /// other styles are drawn with the color of their style at the time
+    /// they are queued.
+    pub(crate) const SLOTS: usize = 256;
+
+    pub(crate) fn new() -> Self {
+        Styles {
+            styles: Vec::new(),
+            changed: true,
+            warned: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Defines the style with the given name, replacing the previous style
+    /// with the same name, if any.
+    pub(crate) fn define(&mut self, name: String, style: TextStyle) -> StyleId {
+        match self.id(&name) {
+            Some(id) => {
+                self.set(id, style);
+
+                id
+            }
+            None => {
+                self.styles.push((name, style));
+                self.changed = true;
+
+                StyleId(self.styles.len() as u32 - 1)
+            }
+        }
+    }
+
+    /// Returns the handle of the style with the given name.
+    pub(crate) fn id(&self, name: &str) -> Option<StyleId> {
+        self.styles
+            .iter()
+            .position(|(style_name, _)| style_name == name)
+            .map(|index| StyleId(index as u32))
+    }
+
+    pub(crate) fn get(&self, id: StyleId) -> Option<&TextStyle> {
+        self.styles.get(id.0 as usize).map(|(_, style)| style)
+    }
+
+    pub(crate) fn set(&mut self, id: StyleId, style: TextStyle) {
+        if let Some((_, current)) = self.styles.get_mut(id.0 as usize) {
+            self.changed |= current.color != style.color;
+            *current = style;
+        }
+    }
+
+    /// Returns the handle and the style a text refers to, if defined.
+    pub(crate) fn resolve(
+        &self,
+        style_ref: &StyleRef,
+    ) -> Option<(StyleId, &TextStyle)> {
+        let id = match style_ref {
+            StyleRef::Id(id) => Some(*id),
+            StyleRef::Name(name) => self.id(name),
+        };
+
+        let style = id.and_then(|id| Some((id, self.get(id)?)));
+
+        if style.is_none()
+            && log_enabled!(log::Level::Warn)
+            && self.warn(style_ref)
+        {
+            warn!("Undefined text style {style_ref:?}");
+        }
+
+        style
+    }
+
+    /// Returns whether the given undefined style was not warned about yet.
+    fn warn(&self, style_ref: &StyleRef) -> bool {
+        self.warned
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(style_ref.clone())
+    }
+
+    /// Returns the colors of the styles in the palette if they changed since
+    /// the last call.
+    pub(crate) fn take_changes(&mut self) -> Option<Vec<f32>> {
+        if !std::mem::take(&mut self.changed) {
+            return None;
+        }
+
+        Some(
+            self.styles
+                .iter()
+                .take(Self::SLOTS)
+                .flat_map(|(_, style)| style.color)
+                .collect(),
+        )
+    }
+
+    /// Returns the entry of the palette uniform buffer holding the color of
+    /// the style with the given handle plus one, if it has one.
+    pub(crate) fn slot(id: StyleId) -> Option<u32> {
+        ((id.0 as usize) < Self::SLOTS).then_some(id.0 + 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn red() -> TextStyle {
+        TextStyle::new(FontId(1), 24.0, [1.0, 0.0, 0.0, 1.0])
+    }
+
+    #[test]
+    fn define_and_resolve_by_name_or_id() {
+        let mut styles = Styles::new();
+
+        let heading = styles.define(String::from("heading"), red());
+        let body = styles.define(String::from("body"), TextStyle::default());
+
+        assert_ne!(heading, body);
+        assert_eq!(styles.id("heading"), Some(heading));
+        assert_eq!(styles.id("missing"), None);
+
+        assert_eq!(styles.resolve(&"heading".into()), Some((heading, &red())));
+        assert_eq!(
+            styles.resolve(&StyleRef::from(body)),
+            Some((body, &TextStyle::default()))
+        );
+    }
+
+    #[test]
+    fn undefined_styles_do_not_resolve() {
+        let mut styles = Styles::new();
+        let _ = styles.define(String::from("heading"), red());
+
+        assert_eq!(styles.resolve(&"missing".into()), None);
+        assert_eq!(styles.resolve(&StyleRef::Id(StyleId(7))), None);
+        assert_eq!(styles.get(StyleId(7)), None);
+    }
+
+    #[test]
+    fn undefined_styles_are_warned_about_once() {
+        let styles = Styles::new();
+
+        assert!(styles.warn(&"missing".into()));
+        assert!(!styles.warn(&"missing".into()));
+        assert!(styles.warn(&StyleRef::Id(StyleId(7))));
+        assert!(!styles.warn(&StyleRef::Id(StyleId(7))));
+        assert!(styles.warn(&"other".into()));
+    }
+
+    #[test]
+    fn redefining_keeps_the_id() {
+        let mut styles = Styles::new();
+
+        let first =
+            styles.define(String::from("heading"), TextStyle::default());
+        let second = styles.define(String::from("heading"), red());
+
+        assert_eq!(first, second);
+        assert_eq!(styles.get(first), Some(&red()));
+    }
+
+    #[test]
+    fn color_changes_are_taken_once() {
+        let mut styles = Styles::new();
+        let id = styles.define(String::from("heading"), red());
+
+        assert_eq!(styles.take_changes(), Some(vec![1.0, 0.0, 0.0, 1.0]));
+        assert_eq!(styles.take_changes(), None);
+
+        // Changing the font keeps the colors in the palette
+        styles.set(id, TextStyle::new(FontId(2), 10.0, red().color));
+        assert_eq!(styles.take_changes(), None);
+
+        styles.set(id, TextStyle::default());
+        assert_eq!(styles.take_changes(), Some(vec![0.0, 0.0, 0.0, 1.0]));
+
+        // Setting an undefined style does nothing
+        styles.set(StyleId(7), red());
+        assert_eq!(styles.take_changes(), None);
+    }
+
+    #[test]
+    fn only_the_first_styles_have_a_slot() {
+        let mut styles = Styles::new();
+
+        for index in 0..Styles::SLOTS + 1 {
+            let _ = styles.define(index.to_string(), red());
+        }
+
+        assert_eq!(Styles::slot(StyleId(0)), Some(1));
+        assert_eq!(
+            Styles::slot(StyleId(Styles::SLOTS as u32 - 1)),
+            Some(Styles::SLOTS as u32)
+        );
+        assert_eq!(Styles::slot(StyleId(Styles::SLOTS as u32)), None);
+        assert_eq!(
+            styles.take_changes().map(|colors| colors.len()),
+            Some(4 * Styles::SLOTS)
+        );
+    }
+}
@@ -0,0 +1,230 @@
+use std::hash::{Hash, Hasher};
+
+/// Where a [`TextLayout`](struct.TextLayout.html) moves the text after a
+/// tab character.
+///
+/// Tabs move the text after them to the next stop past the caret. Once past
+/// the explicit stops of a line, they move it to the next multiple of
+/// `interval` spaces, measured with the font of the tab.
+///
+/// ```
+/// use wgpu_glyph::{Layout, TabAlignment, TabStop, TabStops, TextLayout};
+///
+/// // A column of names, and one of prices aligned on their decimal point
+/// let layout = TextLayout::new(Layout::default_wrap()).with_tab_stops(
+///     TabStops::new(vec![
+///         TabStop::new(120.0, TabAlignment::Left),
+///         TabStop::new(300.0, TabAlignment::Decimal('.')),
+///     ]),
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct TabStops {
+    /// The explicit stops, in pixels from the start of the line.
+    pub stops: Vec<TabStop>,
+    /// The distance between the stops after the explicit ones, in spaces.
+    /// Defaults to 8.
+    pub interval: u32,
+}
+
+impl TabStops {
+    /// Creates `TabStops` with the given explicit stops.
+    pub fn new(stops: Vec<TabStop>) -> Self {
+        TabStops {
+            stops,
+            ..TabStops::default()
+        }
+    }
+
+    /// Creates `TabStops` every given number of spaces.
+    pub fn every(interval: u32) -> Self {
+        TabStops {
+            stops: Vec::new(),
+            interval,
+        }
+    }
+
+    /// Sets the distance between the stops after the explicit ones, in
+    /// spaces.
+    pub fn with_interval(mut self, interval: u32) -> Self {
+        self.interval = interval;
+        self
+    }
+}
+
+impl Default for TabStops {
+    #[inline]
+    fn default() -> Self {
+        TabStops::every(8)
+    }
+}
+
+impl Eq for TabStops {}
+
+impl Hash for TabStops {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.stops.hash(state);
+        self.interval.hash(state);
+    }
+}
+
+/// An explicit stop of [`TabStops`](struct.TabStops.html).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TabStop {
+    /// The position of the stop, in pixels from the start of the line.
+    pub position: f32,
+    /// How the text after the tab is aligned on the stop.
+    pub alignment: TabAlignment,
+}
+
+impl TabStop {
+    /// Creates a new `TabStop`.
+    pub fn new(position: f32, alignment: TabAlignment) -> Self {
+        TabStop {
+            position,
+            alignment,
+        }
+    }
+}
+
+impl Hash for TabStop {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.position.to_bits().hash(state);
+        self.alignment.hash(state);
+    }
+}
+
+/// How the text after a tab is aligned on its
+/// [`TabStop`](struct.TabStop.html).
+///
+/// The text after a tab spans up to the next tab or the end of its line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TabAlignment {
+    /// The text starts at the stop.
+    #[default]
+    Left,
+    /// The text ends at the stop.
+    Right,
+    /// The text is centered on the stop.
+    Center,
+    /// The first occurrence of the given character in the text, like a
+    /// decimal point, is placed at the stop. Text without it ends at the
+    /// stop.
+    Decimal(char),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{advance, glyphs};
+    use crate::{Layout, Section, Text, TextLayout};
+
+    /// Lays out a line and returns the left edge of the glyph at every byte
+    /// index that has one.
+    fn line(tab_stops: TabStops, text: &str) -> Vec<(usize, f32)> {
+        let layout = TextLayout::new(Layout::default_single_line())
+            .with_tab_stops(tab_stops);
+        let section = Section::default()
+            .with_bounds((1000.0, 100.0))
+            .add_text(Text::new(text));
+
+        glyphs(&layout, &section)
+            .into_iter()
+            .map(|sg| (sg.byte_index, sg.glyph.position.x))
+            .collect()
+    }
+
+    fn x_of(positions: &[(usize, f32)], byte_index: usize) -> f32 {
+        positions
+            .iter()
+            .find(|(index, _)| *index == byte_index)
+            .unwrap()
+            .1
+    }
+
+    #[test]
+    fn intervals_of_spaces() {
+        let w = advance();
+        let positions = line(TabStops::every(4), "a\tb\tc");
+
+        assert_eq!(x_of(&positions, 2), 4.0 * w);
+        assert_eq!(x_of(&positions, 4), 8.0 * w);
+
+        // A tab right at a stop moves to the next one
+        let positions = line(TabStops::every(4), "abcd\te");
+
+        assert_eq!(x_of(&positions, 5), 8.0 * w);
+    }
+
+    #[test]
+    fn explicit_stops_align_the_text_after_them() {
+        let w = advance();
+        let stops =
+            |alignment| TabStops::new(vec![TabStop::new(100.0, alignment)]);
+
+        let left = line(stops(TabAlignment::Left), "a\tbc");
+        assert_eq!(x_of(&left, 2), 100.0);
+
+        let right = line(stops(TabAlignment::Right), "a\tbc");
+        assert_eq!(x_of(&right, 2), 100.0 - 2.0 * w);
+
+        let center = line(stops(TabAlignment::Center), "a\tbc");
+        assert_eq!(x_of(&center, 2), 100.0 - w);
+
+        // The decimal point lands on the stop
+        let decimal = line(stops(TabAlignment::Decimal('.')), "a\t12.5");
+        assert_eq!(x_of(&decimal, 4), 100.0);
+
+        // Text without a decimal point ends at the stop
+        let decimal = line(stops(TabAlignment::Decimal('.')), "a\t12");
+        assert_eq!(x_of(&decimal, 2), 100.0 - 2.0 * w);
+    }
+
+    #[test]
+    fn intervals_follow_the_explicit_stops() {
+        let w = advance();
+        let stops =
+            TabStops::new(vec![TabStop::new(5.0 * w, TabAlignment::Left)])
+                .with_interval(4);
+
+        // The first tab goes to the explicit stop, the second to the next
+        // interval past it
+        let positions = line(stops.clone(), "a\tb\tc");
+
+        assert_eq!(x_of(&positions, 2), 5.0 * w);
+        assert_eq!(x_of(&positions, 4), 8.0 * w);
+
+        // Stops behind the caret are skipped
+        let positions = line(stops, "abcdef\tg");
+
+        assert_eq!(x_of(&positions, 7), 8.0 * w);
+    }
+
+    #[test]
+    fn a_zero_interval_leaves_tabs_as_spaces() {
+        let w = advance();
+        let positions = line(TabStops::every(0), "a\tb");
+
+        assert_eq!(x_of(&positions, 2), w);
+    }
+
+    #[test]
+    fn stops_hash_by_position() {
+        use std::collections::hash_map::DefaultHasher;
+
+        let hash = |stops: &TabStops| {
+            let mut hasher = DefaultHasher::new();
+            stops.hash(&mut hasher);
+            hasher.finish()
+        };
+
+        let a = TabStops::new(vec![TabStop::new(10.0, TabAlignment::Left)]);
+        let b = TabStops::new(vec![TabStop::new(10.5, TabAlignment::Left)]);
+
+        assert_eq!(hash(&a), hash(&a.clone()));
+        assert_ne!(hash(&a), hash(&b));
+        assert_ne!(hash(&a), hash(&a.clone().with_interval(4)));
+    }
+}
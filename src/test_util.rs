@@ -0,0 +1,45 @@
+//! Fixtures shared by the tests of the layouts and of the glyphs they place.
+use crate::{GlyphPositioner, SectionGeometry, SectionGlyph, SectionText};
+
+use glyph_brush::ToSectionText;
+use glyph_brush::ab_glyph::{Font, FontRef, ScaleFont};
+
+/// Inconsolata, the monospace font of the tests.
+pub const INCONSOLATA: &[u8] =
+    include_bytes!("../examples/Inconsolata-Regular.ttf");
+
+/// Returns the monospace font of the tests.
+pub fn font() -> FontRef<'static> {
+    FontRef::try_from_slice(INCONSOLATA).unwrap()
+}
+
+/// The width of a character of the monospace font of the tests, at the
+/// default scale of a `Text`.
+pub fn advance() -> f32 {
+    let font = font();
+
+    font.as_scaled(16.0).h_advance(font.glyph_id('a'))
+}
+
+/// Lays out a section with the monospace font of the tests.
+pub fn glyphs<X>(
+    layout: &impl GlyphPositioner,
+    section: &glyph_brush::Section<'_, X>,
+) -> Vec<SectionGlyph> {
+    let font = font();
+    let texts: Vec<SectionText<'_>> = section
+        .text
+        .iter()
+        .map(|text| text.to_section_text())
+        .collect();
+
+    layout.calculate_glyphs(
+        std::slice::from_ref(&font),
+        &SectionGeometry::from(section),
+        &texts,
+    )
+}
+
+pub fn assert_near(a: f32, b: f32) {
+    assert!((a - b).abs() < 1e-3, "{a} != {b}");
+}
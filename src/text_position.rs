@@ -0,0 +1,810 @@
+use crate::bidi;
+use crate::extra::GlyphSection;
+use crate::{SectionGlyph, VerticalAlign};
+
+use glyph_brush::ab_glyph::{Font, Rect, ScaleFont, point};
+
+use std::ops::Range;
+
+/// A position between two characters of the text of a section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TextPosition {
+    /// The index of the `Text` in the section.
+    pub text_index: usize,
+    /// The byte offset in the `Text`.
+    pub byte_offset: usize,
+    /// The line the position belongs to when it is at a line wrap.
+    pub affinity: Affinity,
+}
+
+impl TextPosition {
+    /// Creates a new `TextPosition` with downstream affinity.
+    pub fn new(text_index: usize, byte_offset: usize) -> Self {
+        TextPosition {
+            text_index,
+            byte_offset,
+            affinity: Affinity::Downstream,
+        }
+    }
+
+    /// Sets the affinity of the position.
+    pub fn with_affinity(mut self, affinity: Affinity) -> Self {
+        self.affinity = affinity;
+        self
+    }
+
+    fn key(&self) -> (usize, usize) {
+        (self.text_index, self.byte_offset)
+    }
+}
+
+/// The side a [`TextPosition`](struct.TextPosition.html) leans to when it is
+/// both the end of a line and the start of the next one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Affinity {
+    /// The position belongs to the character before it, i.e. the end of the
+    /// first line.
+    Upstream,
+    /// The position belongs to the character after it, i.e. the start of the
+    /// second line.
+    #[default]
+    Downstream,
+}
+
+/// The laid out lines of a section, with the text range and horizontal
+/// extent of every cluster of glyphs.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Lines {
+    lines: Vec<Line>,
+}
+
+#[derive(Debug, Clone)]
+struct Line {
+    baseline: f32,
+    top: f32,
+    bottom: f32,
+    /// The clusters of the line, in visual order.
+    clusters: Vec<Cluster>,
+    /// The start of the line if it has no clusters, like the line of an
+    /// empty paragraph.
+    empty: Option<(usize, usize)>,
+}
+
+impl Line {
+    fn empty(start: (usize, usize)) -> Self {
+        Line {
+            baseline: 0.0,
+            top: 0.0,
+            bottom: 0.0,
+            clusters: Vec::new(),
+            empty: Some(start),
+        }
+    }
+
+    /// Returns the first position of the line in the text.
+    fn start(&self) -> (usize, usize) {
+        self.clusters
+            .iter()
+            .map(Cluster::start)
+            .min()
+            .or(self.empty)
+            .unwrap_or_default()
+    }
+
+    /// Returns the rectangle of the caret at the given horizontal position.
+    fn caret(&self, x: f32) -> Rect {
+        Rect {
+            min: point(x, self.top),
+            max: point(x, self.bottom),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Cluster {
+    text_index: usize,
+    range: Range<usize>,
+    chars: usize,
+    left: f32,
+    right: f32,
+    rtl: bool,
+}
+
+impl Cluster {
+    fn start(&self) -> (usize, usize) {
+        (self.text_index, self.range.start)
+    }
+
+    fn end(&self) -> (usize, usize) {
+        (self.text_index, self.range.end)
+    }
+
+    fn contains(&self, key: (usize, usize)) -> bool {
+        self.start() <= key && key <= self.end()
+    }
+
+    /// Returns the horizontal position of the caret at the given byte
+    /// offset, splitting ligatures evenly between their characters.
+    fn x_at(&self, text: &str, byte_offset: usize) -> f32 {
+        let before = text
+            .get(self.range.start..byte_offset.min(self.range.end))
+            .map_or(0, |text| text.chars().count());
+
+        let advance =
+            (self.right - self.left) * before as f32 / self.chars.max(1) as f32;
+
+        if self.rtl {
+            self.right - advance
+        } else {
+            self.left + advance
+        }
+    }
+
+    /// Returns the byte offset of the character boundary closest to the
+    /// given horizontal position.
+    fn offset_at(&self, text: &str, x: f32) -> usize {
+        let width = self.right - self.left;
+
+        let fraction = if width > 0.0 {
+            ((x - self.left) / width).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+
+        let fraction = if self.rtl { 1.0 - fraction } else { fraction };
+        let boundary = (fraction * self.chars as f32).round() as usize;
+
+        text[self.range.clone()]
+            .char_indices()
+            .nth(boundary)
+            .map_or(self.range.end, |(index, _)| self.range.start + index)
+    }
+}
+
+impl Lines {
+    /// Groups the glyphs of a section, as returned by
+    /// [`GlyphCruncher::glyphs`](trait.GlyphCruncher.html#method.glyphs),
+    /// into lines of clusters.
+    pub(crate) fn new<F: Font>(
+        fonts: &[F],
+        section: &GlyphSection<'_>,
+        glyphs: &[SectionGlyph],
+    ) -> Self {
+        let mut lines: Vec<Line> = Vec::new();
+
+        for sg in glyphs {
+            let scaled = fonts[sg.font_id.0].as_scaled(sg.glyph.scale);
+            let left = sg.glyph.position.x;
+            let right = left + scaled.h_advance(sg.glyph.id);
+            let baseline = sg.glyph.position.y;
+
+            // Glyphs of the same cluster, like marks, are merged even if
+            // they are offset vertically
+            if let Some(cluster) = lines
+                .last_mut()
+                .and_then(|line| line.clusters.last_mut())
+                .filter(|cluster| {
+                    cluster.start() == (sg.section_index, sg.byte_index)
+                })
+            {
+                cluster.left = cluster.left.min(left);
+                cluster.right = cluster.right.max(right);
+                continue;
+            }
+
+            let line = match lines.last_mut() {
+                Some(line) if (line.baseline - baseline).abs() < 0.5 => line,
+                _ => {
+                    lines.push(Line {
+                        baseline,
+                        top: baseline,
+                        bottom: baseline,
+                        clusters: Vec::new(),
+                        empty: None,
+                    });
+
+                    lines.last_mut().unwrap()
+                }
+            };
+
+            line.top = line.top.min(baseline - scaled.ascent());
+            line.bottom = line.bottom.max(baseline - scaled.descent());
+            line.clusters.push(Cluster {
+                text_index: sg.section_index,
+                range: sg.byte_index..sg.byte_index,
+                chars: 0,
+                left,
+                right,
+                rtl: false,
+            });
+        }
+
+        let mut starts: Vec<(usize, usize)> = lines
+            .iter()
+            .flat_map(|line| line.clusters.iter().map(Cluster::start))
+            .collect();
+
+        starts.sort_unstable();
+
+        for line in &mut lines {
+            for cluster in &mut line.clusters {
+                let text = section.text[cluster.text_index].text;
+
+                let next =
+                    starts.partition_point(|start| *start <= cluster.start());
+
+                let mut end = starts
+                    .get(next)
+                    .filter(|(text_index, _)| *text_index == cluster.text_index)
+                    .map_or(text.len(), |(_, start)| *start);
+
+                // Line breaks do not belong to the clusters before them
+                while let Some(c) = text[cluster.range.start..end]
+                    .chars()
+                    .next_back()
+                    .filter(|c| c.is_control())
+                {
+                    end -= c.len_utf8();
+                }
+
+                cluster.range.end = end;
+                cluster.chars = text[cluster.range.clone()].chars().count();
+            }
+        }
+
+        // Layouts without bidi, like the built-in `Layout`, keep
+        // right-to-left text in logical order
+        let reordered = lines.iter().any(|line| {
+            line.clusters
+                .windows(2)
+                .any(|pair| pair[1].start() < pair[0].start())
+        });
+
+        let text: String = section.text.iter().map(|text| text.text).collect();
+
+        if let Some(levels) = bidi::levels(&text).filter(|_| reordered) {
+            let offsets: Vec<usize> = section
+                .text
+                .iter()
+                .scan(0, |offset, text| {
+                    let start = *offset;
+                    *offset += text.text.len();
+                    Some(start)
+                })
+                .collect();
+
+            for cluster in lines.iter_mut().flat_map(|line| &mut line.clusters)
+            {
+                cluster.rtl = levels
+                    .get(offsets[cluster.text_index] + cluster.range.start)
+                    .is_some_and(|level| level.level % 2 == 1);
+            }
+        }
+
+        // Paragraphs without glyphs still have a line
+        for (start, end) in paragraphs(section) {
+            let next = starts.partition_point(|cluster| *cluster < start);
+
+            if starts.get(next).is_some_and(|cluster| *cluster < end) {
+                continue;
+            }
+
+            let index = lines.partition_point(|line| line.start() < start);
+            lines.insert(index, Line::empty(start));
+        }
+
+        place_empty_lines(fonts, section, &mut lines);
+
+        Lines { lines }
+    }
+
+    /// Returns the position closest to the given point.
+    pub(crate) fn hit_test(
+        &self,
+        section: &GlyphSection<'_>,
+        (x, y): (f32, f32),
+    ) -> Option<TextPosition> {
+        let line = self
+            .lines
+            .iter()
+            .find(|line| y < line.bottom)
+            .or(self.lines.last())?;
+
+        if let Some((text_index, byte_offset)) = line.empty {
+            return Some(TextPosition::new(text_index, byte_offset));
+        }
+
+        let first = line.clusters.first()?;
+        let last = line.clusters.last()?;
+
+        let (cluster, offset) = if x < first.left {
+            let offset = if first.rtl {
+                first.range.end
+            } else {
+                first.range.start
+            };
+
+            (first, offset)
+        } else if x >= last.right {
+            let offset = if last.rtl {
+                last.range.start
+            } else {
+                last.range.end
+            };
+
+            (last, offset)
+        } else {
+            let cluster = line
+                .clusters
+                .iter()
+                .find(|cluster| x < cluster.right)
+                .unwrap_or(last);
+
+            let text = section.text[cluster.text_index].text;
+
+            (cluster, cluster.offset_at(text, x))
+        };
+
+        // Only the end of a line belongs to the character before it
+        let line_end = line.clusters.iter().map(Cluster::end).max();
+        let position = TextPosition::new(cluster.text_index, offset);
+
+        Some(if Some(position.key()) == line_end {
+            position.with_affinity(Affinity::Upstream)
+        } else {
+            position
+        })
+    }
+
+    /// Returns a zero-width rectangle spanning the line of the given
+    /// position at the horizontal position of its caret.
+    pub(crate) fn caret_rect(
+        &self,
+        section: &GlyphSection<'_>,
+        position: TextPosition,
+    ) -> Option<Rect> {
+        let key = position.key();
+
+        let mut candidates = self.lines.iter().flat_map(|line| {
+            line.clusters
+                .iter()
+                .filter(move |cluster| cluster.contains(key))
+                .map(move |cluster| (line, cluster))
+        });
+
+        let preferred =
+            |(_, cluster): &(&Line, &Cluster)| match position.affinity {
+                Affinity::Upstream => key > cluster.start(),
+                Affinity::Downstream => key < cluster.end(),
+            };
+
+        let found = match candidates.clone().find(preferred) {
+            Some(found) => Some(found),
+            None => candidates.next(),
+        };
+
+        if let Some((line, cluster)) = found {
+            let text = section.text[cluster.text_index].text;
+
+            return Some(line.caret(cluster.x_at(text, position.byte_offset)));
+        }
+
+        if let Some(line) = self.lines.iter().find(|line| {
+            line.empty.is_some_and(|start| is_same(section, start, key))
+        }) {
+            return Some(line.caret(section.screen_position.0));
+        }
+
+        // The end of a text is also the start of the next one
+        let at_end = section
+            .text
+            .get(key.0)
+            .is_some_and(|text| key.1 >= text.text.len());
+
+        if at_end && key.0 + 1 < section.text.len() {
+            return self.caret_rect(
+                section,
+                TextPosition::new(key.0 + 1, 0)
+                    .with_affinity(position.affinity),
+            );
+        }
+
+        // The position has no glyphs, like the end of trailing whitespace
+        let (line, cluster) = self
+            .lines
+            .iter()
+            .flat_map(|line| {
+                line.clusters.iter().map(move |cluster| (line, cluster))
+            })
+            .filter(|(_, cluster)| cluster.end() <= key)
+            .max_by_key(|(_, cluster)| cluster.end())?;
+
+        Some(line.caret(if cluster.rtl {
+            cluster.left
+        } else {
+            cluster.right
+        }))
+    }
+
+    /// Returns the rectangles covering the text in the given range, one per
+    /// contiguous run of selected clusters in a line.
+    pub(crate) fn selection_rects(
+        &self,
+        section: &GlyphSection<'_>,
+        range: Range<TextPosition>,
+    ) -> Vec<Rect> {
+        let (start, end) = if range.start.key() <= range.end.key() {
+            (range.start.key(), range.end.key())
+        } else {
+            (range.end.key(), range.start.key())
+        };
+
+        let mut rects: Vec<Rect> = Vec::new();
+
+        for line in &self.lines {
+            let first = rects.len();
+
+            for cluster in &line.clusters {
+                let from = start.max(cluster.start());
+                let to = end.min(cluster.end());
+
+                if from >= to {
+                    continue;
+                }
+
+                let text = section.text[cluster.text_index].text;
+                let a = cluster.x_at(text, from.1);
+                let b = cluster.x_at(text, to.1);
+                let (left, right) = (a.min(b), a.max(b));
+
+                match rects[first..].last_mut() {
+                    Some(rect) if (rect.max.x - left).abs() < 0.5 => {
+                        rect.max.x = right;
+                    }
+                    _ => rects.push(Rect {
+                        min: point(left, line.top),
+                        max: point(right, line.bottom),
+                    }),
+                }
+            }
+        }
+
+        rects
+    }
+}
+
+/// Returns the start and the end of every laid out paragraph of the
+/// section, ended by a hard line break. The last paragraph ends at
+/// `(usize::MAX, 0)`.
+///
+/// Single line layouts stop at the first hard line break.
+fn paragraphs(
+    section: &GlyphSection<'_>,
+) -> Vec<((usize, usize), (usize, usize))> {
+    let mut paragraphs = Vec::new();
+    let mut start = (0, 0);
+
+    for (text_index, text) in section.text.iter().enumerate() {
+        let mut chars = text.text.char_indices().peekable();
+
+        while let Some((index, c)) = chars.next() {
+            // A carriage return followed by a line feed is a single break
+            let crlf =
+                c == '\r' && chars.peek().is_some_and(|(_, c)| *c == '\n');
+
+            if is_hard_break(c) && !crlf {
+                paragraphs.push((start, (text_index, index)));
+                start = (text_index, index + c.len_utf8());
+            }
+        }
+    }
+
+    paragraphs.push((start, (usize::MAX, 0)));
+
+    if let crate::Layout::SingleLine { .. } = section.layout {
+        paragraphs.truncate(1);
+    }
+
+    paragraphs
+}
+
+/// Returns whether the character always ends its line.
+fn is_hard_break(c: char) -> bool {
+    matches!(
+        c,
+        '\n' | '\r' | '\u{b}' | '\u{c}' | '\u{85}' | '\u{2028}' | '\u{2029}'
+    )
+}
+
+/// Returns whether the positions are at the same place in the text, like
+/// the end of a text and the start of the next one.
+fn is_same(
+    section: &GlyphSection<'_>,
+    a: (usize, usize),
+    b: (usize, usize),
+) -> bool {
+    let (from, to) = (a.min(b), a.max(b));
+
+    from == to
+        || section.text.get(from.0..=to.0).is_some_and(|texts| {
+            texts.iter().enumerate().all(|(index, text)| {
+                let start = if index == 0 { from.1 } else { 0 };
+                let end = if index == to.0 - from.0 {
+                    to.1
+                } else {
+                    text.text.len()
+                };
+
+                start >= end
+            })
+        })
+}
+
+/// Places the empty lines between the lines around them, spacing them
+/// evenly, or like the lines of the first text of the section if it has no
+/// glyphs.
+fn place_empty_lines<F: Font>(
+    fonts: &[F],
+    section: &GlyphSection<'_>,
+    lines: &mut [Line],
+) {
+    let mut index = 0;
+
+    while index < lines.len() {
+        if lines[index].empty.is_none() {
+            index += 1;
+            continue;
+        }
+
+        let count = lines[index..]
+            .iter()
+            .take_while(|line| line.empty.is_some())
+            .count();
+        let end = index + count;
+
+        let previous = index.checked_sub(1).map(|i| &lines[i]);
+        let next = lines.get(end);
+
+        // The first baseline, the space between baselines and the extent of
+        // the lines around their baseline
+        let (first, step, ascent, descent) = match (previous, next) {
+            (Some(previous), Some(next)) => {
+                let step =
+                    (next.baseline - previous.baseline) / (count + 1) as f32;
+
+                (
+                    previous.baseline + step,
+                    step,
+                    previous.baseline - previous.top,
+                    previous.bottom - previous.baseline,
+                )
+            }
+            (Some(previous), None) => {
+                let step = index
+                    .checked_sub(2)
+                    .map(|i| previous.baseline - lines[i].baseline)
+                    .unwrap_or(previous.bottom - previous.top);
+
+                (
+                    previous.baseline + step,
+                    step,
+                    previous.baseline - previous.top,
+                    previous.bottom - previous.baseline,
+                )
+            }
+            (None, Some(next)) => {
+                let step = next.bottom - next.top;
+
+                (
+                    next.baseline - step * count as f32,
+                    step,
+                    next.baseline - next.top,
+                    next.bottom - next.baseline,
+                )
+            }
+            (None, None) => {
+                let Some(text) = section.text.first() else {
+                    return;
+                };
+
+                let scaled = fonts[text.font_id.0].as_scaled(text.scale);
+                let height = scaled.height();
+                let total = height * count as f32;
+                let y = section.screen_position.1;
+
+                let top = match section.layout {
+                    crate::Layout::SingleLine { v_align, .. }
+                    | crate::Layout::Wrap { v_align, .. } => match v_align {
+                        VerticalAlign::Top => y,
+                        VerticalAlign::Center => y - total / 2.0,
+                        VerticalAlign::Bottom => y - total,
+                    },
+                };
+
+                (
+                    top + scaled.ascent(),
+                    height,
+                    scaled.ascent(),
+                    -scaled.descent(),
+                )
+            }
+        };
+
+        for (offset, line) in lines[index..end].iter_mut().enumerate() {
+            line.baseline = first + step * offset as f32;
+            line.top = line.baseline - ascent;
+            line.bottom = line.baseline + descent;
+        }
+
+        index = end;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extra::GlyphExtra;
+    use crate::test_util::{assert_near, font, glyphs};
+    use crate::{
+        BuiltInLineBreaker, GlyphPositioner, HorizontalAlign, Layout,
+        TextLayout,
+    };
+
+    fn section(
+        text: &str,
+        layout: Layout<BuiltInLineBreaker>,
+    ) -> GlyphSection<'_> {
+        glyph_brush::Section::<GlyphExtra>::new()
+            .with_screen_position((100.0, 20.0))
+            .with_layout(layout)
+            .add_text(glyph_brush::Text::new(text))
+    }
+
+    fn lines(
+        section: &GlyphSection<'_>,
+        layout: &impl GlyphPositioner,
+    ) -> Lines {
+        let glyphs = glyphs(layout, section);
+
+        Lines::new(std::slice::from_ref(&font()), section, &glyphs)
+    }
+
+    fn caret(
+        lines: &Lines,
+        section: &GlyphSection<'_>,
+        position: TextPosition,
+    ) -> Rect {
+        lines.caret_rect(section, position).unwrap()
+    }
+
+    #[test]
+    fn empty_lines_have_a_caret() {
+        let section = section("a\n\nb", Layout::default_wrap());
+        let lines = lines(&section, &section.layout);
+
+        let a = caret(&lines, &section, TextPosition::new(0, 0));
+        let empty = caret(&lines, &section, TextPosition::new(0, 2));
+        let b = caret(&lines, &section, TextPosition::new(0, 3));
+
+        assert_eq!(lines.lines.len(), 3);
+        assert_near(empty.min.x, 100.0);
+        assert_near(empty.min.y, a.max.y);
+        assert_near(b.min.y, empty.max.y);
+
+        let middle = (empty.min.y + empty.max.y) / 2.0;
+
+        assert_eq!(
+            lines.hit_test(&section, (140.0, middle)),
+            Some(TextPosition::new(0, 2))
+        );
+    }
+
+    #[test]
+    fn trailing_line_break_caret_follows_the_alignment() {
+        for h_align in [HorizontalAlign::Center, HorizontalAlign::Right] {
+            let layout = Layout::default_wrap().h_align(h_align);
+            let section = section("abc\n", layout);
+            let lines = lines(&section, &layout);
+
+            let start = caret(&lines, &section, TextPosition::new(0, 0));
+            let end = caret(&lines, &section, TextPosition::new(0, 3));
+            let next = caret(&lines, &section, TextPosition::new(0, 4));
+
+            // An empty line is aligned like the lines with text
+            let anchor = match h_align {
+                HorizontalAlign::Center => (start.min.x + end.min.x) / 2.0,
+                _ => end.min.x,
+            };
+
+            assert_near(next.min.x, anchor);
+            assert_near(next.min.y, start.max.y);
+        }
+    }
+
+    #[test]
+    fn empty_sections_have_a_caret() {
+        let layout = Layout::default_wrap().v_align(VerticalAlign::Center);
+        let section = section("", layout);
+        let lines = lines(&section, &layout);
+
+        let rect = caret(&lines, &section, TextPosition::new(0, 0));
+        let height = font().as_scaled(16.0).height();
+
+        assert_near(rect.min.x, 100.0);
+        assert_near(rect.min.y, 20.0 - height / 2.0);
+        assert_near(rect.max.y, 20.0 + height / 2.0);
+        assert_eq!(
+            lines.hit_test(&section, (0.0, 0.0)),
+            Some(TextPosition::new(0, 0))
+        );
+    }
+
+    #[test]
+    fn single_lines_stop_at_the_first_line_break() {
+        let layout = Layout::default_single_line();
+        let section = section("a\n\nb", layout);
+        let lines = lines(&section, &layout);
+
+        assert_eq!(lines.lines.len(), 1);
+    }
+
+    #[test]
+    fn right_to_left_carets_use_bidi_levels() {
+        // Hebrew letters, drawn with the missing glyph of the font
+        let text = "ab \u{5D0}\u{5D1}";
+        let layout = TextLayout::new(Layout::default_wrap());
+        let section = section(text, Layout::default_wrap());
+        let lines = lines(&section, &layout);
+
+        let right = lines.lines[0]
+            .clusters
+            .iter()
+            .map(|cluster| cluster.right)
+            .fold(f32::MIN, f32::max);
+
+        let space = caret(
+            &lines,
+            &section,
+            TextPosition::new(0, 3).with_affinity(Affinity::Upstream),
+        );
+
+        // The first right-to-left letter starts at the right
+        assert_near(
+            caret(&lines, &section, TextPosition::new(0, 3)).min.x,
+            right,
+        );
+        assert_near(
+            caret(
+                &lines,
+                &section,
+                TextPosition::new(0, text.len())
+                    .with_affinity(Affinity::Upstream),
+            )
+            .min
+            .x,
+            space.min.x,
+        );
+        assert_eq!(
+            lines.hit_test(&section, (right - 1.0, 25.0)),
+            Some(TextPosition::new(0, 3))
+        );
+    }
+
+    #[test]
+    fn layouts_without_bidi_keep_logical_order() {
+        let text = "ab \u{5D0}\u{5D1}";
+        let section = section(text, Layout::default_wrap());
+        let lines = lines(&section, &section.layout);
+
+        let space = caret(
+            &lines,
+            &section,
+            TextPosition::new(0, 3).with_affinity(Affinity::Upstream),
+        );
+
+        assert_near(
+            caret(&lines, &section, TextPosition::new(0, 3)).min.x,
+            space.min.x,
+        );
+    }
+}
@@ -0,0 +1,195 @@
+use std::hash::{Hash, Hasher};
+
+/// A 2D affine transform of the glyphs of a [`Section`](struct.Section.html),
+/// applied when drawing them.
+///
+/// Glyphs are still rasterized at the scale of their text, so scaling them
+/// up makes them blurry. Positions are relative to the `screen_position` of
+/// the section, with `y` growing downwards.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Affine {
+    /// The linear part of the transform, as the columns of a 2x2 matrix:
+    /// `[xx, xy, yx, yy]`.
+    pub matrix: [f32; 4],
+    /// The translation applied after the linear part.
+    pub translation: (f32, f32),
+}
+
+impl Affine {
+    /// The transform that leaves glyphs unchanged.
+    pub const IDENTITY: Affine = Affine {
+        matrix: [1.0, 0.0, 0.0, 1.0],
+        translation: (0.0, 0.0),
+    };
+
+    /// A translation by the given offset.
+    pub fn translation(x: f32, y: f32) -> Self {
+        Affine {
+            translation: (x, y),
+            ..Affine::IDENTITY
+        }
+    }
+
+    /// A rotation around the origin by the given angle, in radians.
+    ///
+    /// Positive angles rotate clockwise on screen.
+    pub fn rotation(angle: f32) -> Self {
+        let (sin, cos) = angle.sin_cos();
+
+        Affine {
+            matrix: [cos, sin, -sin, cos],
+            ..Affine::IDENTITY
+        }
+    }
+
+    /// A scale around the origin by the given factors.
+    pub fn scale(x: f32, y: f32) -> Self {
+        Affine {
+            matrix: [x, 0.0, 0.0, y],
+            ..Affine::IDENTITY
+        }
+    }
+
+    /// A skew around the origin by the given angles, in radians, along the
+    /// `x` and `y` axes.
+    ///
+    /// Since `y` grows downwards, a negative `x` angle around the baseline
+    /// slants the glyphs to the right, like synthetic italics.
+    pub fn skew(x: f32, y: f32) -> Self {
+        Affine {
+            matrix: [1.0, y.tan(), x.tan(), 1.0],
+            ..Affine::IDENTITY
+        }
+    }
+
+    /// Returns the transform applying this one, then `next`.
+    pub fn then(self, next: Affine) -> Self {
+        let [a, b, c, d] = next.matrix;
+        let [xx, xy, yx, yy] = self.matrix;
+
+        Affine {
+            matrix: [
+                a * xx + c * xy,
+                b * xx + d * xy,
+                a * yx + c * yy,
+                b * yx + d * yy,
+            ],
+            translation: next.transform_point(self.translation),
+        }
+    }
+
+    /// Returns this transform applied around the given anchor instead of
+    /// the origin, e.g. to rotate a label around its center.
+    pub fn around(self, (x, y): (f32, f32)) -> Self {
+        Affine::translation(-x, -y)
+            .then(self)
+            .then(Affine::translation(x, y))
+    }
+
+    /// Returns the transform undoing this one, or `None` if it flattens
+    /// glyphs into a line or a point.
+    pub fn inverse(&self) -> Option<Self> {
+        let [xx, xy, yx, yy] = self.matrix;
+        let determinant = xx * yy - yx * xy;
+
+        if determinant == 0.0 || !determinant.is_finite() {
+            return None;
+        }
+
+        let linear = Affine {
+            matrix: [
+                yy / determinant,
+                -xy / determinant,
+                -yx / determinant,
+                xx / determinant,
+            ],
+            ..Affine::IDENTITY
+        };
+
+        let (x, y) = linear.transform_point(self.translation);
+
+        Some(linear.then(Affine::translation(-x, -y)))
+    }
+
+    /// Transforms the given point.
+    pub fn transform_point(&self, (x, y): (f32, f32)) -> (f32, f32) {
+        let [xx, xy, yx, yy] = self.matrix;
+
+        (
+            xx * x + yx * y + self.translation.0,
+            xy * x + yy * y + self.translation.1,
+        )
+    }
+}
+
+impl Default for Affine {
+    #[inline]
+    fn default() -> Self {
+        Affine::IDENTITY
+    }
+}
+
+impl Hash for Affine {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.matrix.map(f32::to_bits).hash(state);
+        self.translation.0.to_bits().hash(state);
+        self.translation.1.to_bits().hash(state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near((x, y): (f32, f32), (expected_x, expected_y): (f32, f32)) {
+        assert!(
+            (x - expected_x).abs() < 1e-4 && (y - expected_y).abs() < 1e-4,
+            "{:?} != {:?}",
+            (x, y),
+            (expected_x, expected_y)
+        );
+    }
+
+    #[test]
+    fn then_applies_transforms_in_order() {
+        let transform =
+            Affine::scale(2.0, 3.0).then(Affine::translation(1.0, 1.0));
+
+        assert_near(transform.transform_point((1.0, 1.0)), (3.0, 4.0));
+    }
+
+    #[test]
+    fn rotation_turns_clockwise_on_screen() {
+        let turn =
+            Affine::rotation(std::f32::consts::FRAC_PI_2).around((10.0, 10.0));
+
+        assert_near(turn.transform_point((20.0, 10.0)), (10.0, 20.0));
+    }
+
+    #[test]
+    fn inverse_undoes_the_transform() {
+        let transform = Affine::skew(-0.2, 0.0)
+            .then(Affine::rotation(0.7))
+            .then(Affine::scale(2.0, 0.5))
+            .around((30.0, -12.0));
+
+        let inverse = transform.inverse().unwrap();
+        let point = (17.0, 42.0);
+
+        assert_near(
+            inverse.transform_point(transform.transform_point(point)),
+            point,
+        );
+        assert_near(
+            transform.then(inverse).transform_point((-5.0, 3.0)),
+            (-5.0, 3.0),
+        );
+    }
+
+    #[test]
+    fn flat_transforms_have_no_inverse() {
+        assert_eq!(Affine::scale(0.0, 1.0).inverse(), None);
+        assert_eq!(Affine::scale(f32::NAN, 1.0).inverse(), None);
+    }
+}
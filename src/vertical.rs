@@ -0,0 +1,235 @@
+use crate::{Affine, SectionAttributes};
+use glyph_brush::ab_glyph::{Point, Rect, point};
+
+/// The direction lines of text flow in, set on a
+/// [`TextLayout`](struct.TextLayout.html) or on a whole section with
+/// [`SectionAttributes::with_writing_mode`](struct.SectionAttributes.html#method.with_writing_mode).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum WritingMode {
+    /// Lines flow from left to right and stack from top to bottom.
+    #[default]
+    HorizontalTopToBottom,
+    /// Lines flow from top to bottom and stack from right to left, like in
+    /// vertical Japanese and Chinese.
+    ///
+    /// The bounds of the section wrap the columns at their height. Ideographs,
+    /// kana, hangul and fullwidth forms stay upright and use the vertical
+    /// metrics of their font when it has them. Other characters, like Latin
+    /// letters, are turned sideways: when queued with
+    /// [`queue`](struct.GlyphBrush.html#method.queue), they are rotated a
+    /// quarter turn clockwise, while custom layouts place them unrotated.
+    ///
+    /// The horizontal alignment of the layout places the columns around the
+    /// `screen_position` of the section, and the vertical alignment aligns
+    /// the text in every column. Cursors and hit testing still assume
+    /// horizontal lines. Decorations, inline objects and animations are not
+    /// drawn in vertical sections, and queuing them logs a warning.
+    VerticalRightToLeft,
+}
+
+/// Returns whether the lines of the section flow vertically.
+pub(crate) fn is_vertical(attributes: &SectionAttributes) -> bool {
+    attributes.writing_mode == WritingMode::VerticalRightToLeft
+}
+
+/// Returns the quarter turn clockwise applied to the sideways glyphs of a
+/// section, around its origin.
+pub(crate) fn quarter_turn(origin: (f32, f32)) -> Affine {
+    Affine::rotation(std::f32::consts::FRAC_PI_2).around(origin)
+}
+
+/// Returns the position to queue a sideways glyph at, so that the
+/// [`quarter_turn`] around the origin of its section turns it around the
+/// given pivot instead.
+///
+/// Turning every glyph around the same origin lets them share the extras
+/// and the bounds of their section.
+pub(crate) fn sideways_position(
+    position: Point,
+    pivot: (f32, f32),
+    (x, y): (f32, f32),
+) -> Point {
+    let (dx, dy) = (pivot.0 - x, pivot.1 - y);
+
+    point(position.x + dy - dx, position.y - dx - dy)
+}
+
+/// Returns the bounds to clip the sideways glyphs of a section with before
+/// the [`quarter_turn`] around its origin, so they are clipped to the given
+/// bounds once turned.
+pub(crate) fn sideways_bounds(bounds: Rect, (x, y): (f32, f32)) -> Rect {
+    Rect {
+        min: point(x + bounds.min.y - y, y + x - bounds.max.x),
+        max: point(x + bounds.max.y - y, y + x - bounds.min.x),
+    }
+}
+
+/// Returns whether the character stays upright in vertical text, following
+/// the `Vertical_Orientation` property of Unicode.
+///
+/// Characters that transform in vertical text are upright if they are
+/// usually drawn upright, like ideographic punctuation, and sideways
+/// otherwise, like brackets.
+pub(crate) fn is_upright(c: char) -> bool {
+    let c = u32::from(c);
+
+    UPRIGHT
+        .binary_search_by(|&(start, end)| {
+            if end < c {
+                std::cmp::Ordering::Less
+            } else if start > c {
+                std::cmp::Ordering::Greater
+            } else {
+                std::cmp::Ordering::Equal
+            }
+        })
+        .is_ok()
+}
+
+/// The sorted ranges of upright characters.
+const UPRIGHT: &[(u32, u32)] = &[
+    (0x00A7, 0x00A7),
+    (0x00A9, 0x00A9),
+    (0x00AE, 0x00AE),
+    (0x00B1, 0x00B1),
+    (0x00BC, 0x00BE),
+    (0x00D7, 0x00D7),
+    (0x00F7, 0x00F7),
+    (0x02EA, 0x02EB),
+    (0x1100, 0x11FF),
+    (0x1401, 0x167F),
+    (0x18B0, 0x18FF),
+    (0x2016, 0x2016),
+    (0x2020, 0x2021),
+    (0x2030, 0x2031),
+    (0x203B, 0x203C),
+    (0x2042, 0x2042),
+    (0x2047, 0x2049),
+    (0x2051, 0x2051),
+    (0x20DD, 0x20E0),
+    (0x20E2, 0x20E4),
+    (0x2100, 0x2101),
+    (0x2103, 0x2109),
+    (0x210F, 0x210F),
+    (0x2113, 0x2114),
+    (0x2116, 0x2117),
+    (0x211E, 0x2123),
+    (0x2125, 0x2125),
+    (0x2127, 0x2127),
+    (0x2129, 0x2129),
+    (0x212E, 0x212E),
+    (0x2135, 0x213F),
+    (0x2145, 0x214A),
+    (0x214C, 0x214D),
+    (0x214F, 0x2189),
+    (0x218C, 0x218F),
+    (0x221E, 0x221E),
+    (0x2234, 0x2235),
+    (0x2300, 0x2307),
+    (0x230C, 0x231F),
+    (0x2322, 0x232B),
+    (0x237D, 0x239A),
+    (0x23BE, 0x23CD),
+    (0x23CF, 0x23CF),
+    (0x23D1, 0x23DB),
+    (0x23E2, 0x2422),
+    (0x2424, 0x24FF),
+    (0x25A0, 0x2619),
+    (0x2620, 0x2767),
+    (0x2776, 0x2793),
+    (0x2B12, 0x2B2F),
+    (0x2B50, 0x2B59),
+    (0x2BB8, 0x2BFF),
+    // CJK radicals to ideographic punctuation, without brackets
+    (0x2E80, 0x3007),
+    (0x3012, 0x3013),
+    // Kana, without the double hyphen and the prolonged sound mark, to Yi
+    (0x3020, 0x309F),
+    (0x30A1, 0x30FB),
+    (0x30FD, 0xA4CF),
+    (0xA960, 0xA97F),
+    (0xAC00, 0xD7FF),
+    (0xE000, 0xFAFF),
+    (0xFE10, 0xFE1F),
+    (0xFE30, 0xFE48),
+    (0xFE50, 0xFE57),
+    (0xFE59, 0xFE62),
+    (0xFE67, 0xFE6F),
+    // Fullwidth forms, without brackets, colons and the low line
+    (0xFF01, 0xFF07),
+    (0xFF0A, 0xFF0C),
+    (0xFF0E, 0xFF19),
+    (0xFF1F, 0xFF3A),
+    (0xFF3C, 0xFF3C),
+    (0xFF3E, 0xFF3E),
+    (0xFF40, 0xFF5A),
+    (0xFFE0, 0xFFE2),
+    (0xFFE4, 0xFFE7),
+    (0x1F000, 0x1FAFF),
+    (0x20000, 0x3FFFD),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near((x, y): (f32, f32), (expected_x, expected_y): (f32, f32)) {
+        assert!(
+            (x - expected_x).abs() < 1e-3 && (y - expected_y).abs() < 1e-3,
+            "{:?} != {:?}",
+            (x, y),
+            (expected_x, expected_y)
+        );
+    }
+
+    #[test]
+    fn sideways_glyphs_turn_around_their_pivot() {
+        let origin = (40.0, 10.0);
+        let pivot = (100.0, 72.0);
+        let position = point(100.0, 80.0);
+
+        let moved = sideways_position(position, pivot, origin);
+
+        let expected = Affine::rotation(std::f32::consts::FRAC_PI_2)
+            .around(pivot)
+            .transform_point((position.x, position.y));
+
+        assert_near(
+            quarter_turn(origin).transform_point((moved.x, moved.y)),
+            expected,
+        );
+        assert_near(expected, (92.0, 72.0));
+    }
+
+    #[test]
+    fn sideways_bounds_turn_into_the_bounds_of_the_section() {
+        let origin = (40.0, 10.0);
+        let bounds = Rect {
+            min: point(0.0, 5.0),
+            max: point(60.0, 200.0),
+        };
+
+        let sideways = sideways_bounds(bounds, origin);
+        let turn = quarter_turn(origin);
+
+        let (a, b) = (
+            turn.transform_point((sideways.min.x, sideways.min.y)),
+            turn.transform_point((sideways.max.x, sideways.max.y)),
+        );
+
+        assert!(sideways.min.x < sideways.max.x);
+        assert!(sideways.min.y < sideways.max.y);
+        assert_near((a.0.min(b.0), a.1.min(b.1)), (0.0, 5.0));
+        assert_near((a.0.max(b.0), a.1.max(b.1)), (60.0, 200.0));
+    }
+
+    #[test]
+    fn upright_characters() {
+        assert!(is_upright('漢'));
+        assert!(is_upright('か'));
+        assert!(is_upright('、'));
+        assert!(!is_upright('a'));
+        assert!(!is_upright('「'));
+        assert!(!is_upright('ー'));
+    }
+}